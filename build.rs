@@ -1,21 +1,49 @@
 fn main() {
     let mut builder = cc::Build::new();
 
-    builder.file("src/c/rounding_mode.c");
+    // asm backend is available only on x86_64, otherwise it falls back to C
+    let x86_64 = std::env::var("CARGO_CFG_TARGET_ARCH").is_ok_and(|arch| arch == "x86_64");
+
+    // the thread's rounding mode is accessed by <fenv.h> unless on x86_64 without C backends,
+    // there it is accessed by MXCSR and x87 FPU control word (see src/env.rs)
+    #[allow(unused_mut)]
+    let mut fenv = !x86_64;
 
     #[cfg(feature = "f16")]
-    builder.file("src/c/f16.c");
+    {
+        builder.file("src/c/f16.c");
+        fenv = true;
+    }
 
-    #[cfg(not(feature = "f32_softfloat"))]
-    builder.file("src/c/f32.c");
+    #[cfg(not(any(
+        feature = "f32_softfloat",
+        feature = "f32_pure_rust",
+        feature = "f32_widening"
+    )))]
+    if !(cfg!(feature = "f32_asm") && x86_64) {
+        builder.file("src/c/f32.c");
+        fenv = true;
+    }
 
-    #[cfg(not(feature = "f64_softfloat"))]
-    builder.file("src/c/f64.c");
+    #[cfg(not(any(feature = "f64_softfloat", feature = "f64_pure_rust")))]
+    if !(cfg!(feature = "f64_asm") && x86_64) {
+        builder.file("src/c/f64.c");
+        fenv = true;
+    }
 
     #[cfg(feature = "f128")]
-    builder.file("src/c/f128.c");
+    {
+        builder.file("src/c/f128.c");
+        fenv = true;
+    }
+
+    println!("cargo:rustc-check-cfg=cfg(srmfpa_fenv)");
+    if fenv {
+        println!("cargo:rustc-cfg=srmfpa_fenv");
 
-    builder.compile("c_impl");
+        builder.file("src/c/rounding_mode.c");
+        builder.compile("c_impl");
 
-    println!("cc:build-command={:?}", builder.get_compiler().to_command());
+        println!("cc:build-command={:?}", builder.get_compiler().to_command());
+    }
 }
@@ -1,4 +1,5 @@
 #include "fenv_access.h"
+#include "slice.h"
 #include "unlikely.h"
 #include <fenv.h>
 #include <math.h>
@@ -161,3 +162,10 @@ int c_sqrt_f32(int const mode, float32_t const a,
 
   return 0;
 }
+
+C_SLICE_BINARY(c_add_f32_slice, float32_t, a[i] + b[i])
+C_SLICE_BINARY(c_sub_f32_slice, float32_t, a[i] - b[i])
+C_SLICE_BINARY(c_mul_f32_slice, float32_t, a[i] * b[i])
+C_SLICE_BINARY(c_div_f32_slice, float32_t, a[i] / b[i])
+C_SLICE_TERNARY(c_fma_f32_slice, float32_t, fmaf(a[i], b[i], c[i]))
+C_SLICE_UNARY(c_sqrt_f32_slice, float32_t, sqrtf(a[i]))
@@ -1,3 +1,4 @@
+#include "fenv_access.h"
 #include <fenv.h>
 #include <stdbool.h>
 
@@ -28,3 +29,11 @@ int const c_TOWARD_ZERO = -1;
 bool c_supported(int const round) {
     return 0 <= round;
 }
+
+int c_get_round(void) {
+    return fegetround();
+}
+
+int c_set_round(int const round) {
+    return fesetround(round);
+}
@@ -0,0 +1,46 @@
+#pragma once
+
+#include "fenv_access.h"
+#include "unlikely.h"
+#include <fenv.h>
+#include <stddef.h>
+
+// Defines `int name(int mode, size_t n, T const *a, ..., T *dst)`,
+// which computes `dst[i] = expr` for each `i` under a single rounding mode switch.
+#define C_SLICE_BODY(expr)                                                     \
+  int err = 0;                                                                 \
+                                                                               \
+  int const dflt = fegetround();                                               \
+  if (unlikely(dflt < 0)) {                                                    \
+    return dflt;                                                               \
+  }                                                                            \
+                                                                               \
+  err = fesetround(mode);                                                      \
+  if (unlikely(err != 0)) {                                                    \
+    return err;                                                                \
+  }                                                                            \
+                                                                               \
+  for (size_t i = 0; i < n; ++i) {                                             \
+    dst[i] = (expr);                                                           \
+  }                                                                            \
+                                                                               \
+  return fesetround(dflt);
+
+#define C_SLICE_UNARY(name, T, expr)                                           \
+  int name(int const mode, size_t const n, T const *const restrict a,          \
+           T *const restrict dst) {                                            \
+    C_SLICE_BODY(expr)                                                         \
+  }
+
+#define C_SLICE_BINARY(name, T, expr)                                          \
+  int name(int const mode, size_t const n, T const *const restrict a,          \
+           T const *const restrict b, T *const restrict dst) {                 \
+    C_SLICE_BODY(expr)                                                         \
+  }
+
+#define C_SLICE_TERNARY(name, T, expr)                                         \
+  int name(int const mode, size_t const n, T const *const restrict a,          \
+           T const *const restrict b, T const *const restrict c,               \
+           T *const restrict dst) {                                            \
+    C_SLICE_BODY(expr)                                                         \
+  }
@@ -0,0 +1,72 @@
+use core::fmt::{Display, Formatter};
+
+use crate::r#impl::print::format_abs;
+use crate::{RoundingFloat, RoundingMode};
+
+/// Formatter adaptor which prints a float in decimal with specified rounding mode.
+///
+/// It prints the value rounded to the precision, the number of fractional digits,
+/// given by the formatter (e.g. `{:.6}`) or by [`Directed::precision`].
+/// It prints the exact value of the float when no precision is given.
+///
+/// ```
+/// use srmfpa::Directed;
+/// use srmfpa::RoundingMode::{TowardNegInf, TowardPosInf};
+///
+/// assert_eq!(format!("{:.3}", Directed(0.1, TowardPosInf)), "0.101");
+/// assert_eq!(format!("{:.3}", Directed(0.1, TowardNegInf)), "0.100");
+/// assert_eq!(format!("{}", Directed(-0.1, TowardNegInf).precision(3)), "-0.101");
+///
+/// // exact value
+/// assert_eq!(format!("{}", Directed(0.375, TowardNegInf)), "0.375");
+///
+/// // generic code
+/// fn upper<T: srmfpa::RoundingFloat>(x: T) -> String {
+///     format!("{:.1}", Directed(x, TowardPosInf))
+/// }
+/// assert_eq!(upper(0.11f32), "0.2");
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct Directed<T>(pub T, pub RoundingMode);
+
+/// [`Directed`] with a fixed precision, see [`Directed::precision`].
+#[derive(Debug, Copy, Clone)]
+pub struct DirectedPrecision<T> {
+    inner: Directed<T>,
+    digits: usize,
+}
+
+impl<T> Directed<T> {
+    /// Fixes the number of fractional digits,
+    /// it takes precedence over the formatter's precision.
+    #[must_use]
+    #[inline]
+    pub fn precision(self, digits: usize) -> DirectedPrecision<T> {
+        DirectedPrecision {
+            inner: self,
+            digits,
+        }
+    }
+}
+
+fn fmt<T: RoundingFloat>(
+    x: T,
+    digits: Option<usize>,
+    mode: &RoundingMode,
+    f: &mut Formatter<'_>,
+) -> core::fmt::Result {
+    let (sign, s) = format_abs(x, digits, mode);
+    f.pad_integral(!sign, "", &s)
+}
+
+impl<T: RoundingFloat> Display for Directed<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        fmt(self.0, f.precision(), &self.1, f)
+    }
+}
+
+impl<T: RoundingFloat> Display for DirectedPrecision<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        fmt(self.inner.0, Some(self.digits), &self.inner.1, f)
+    }
+}
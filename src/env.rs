@@ -0,0 +1,807 @@
+#[cfg(any(
+    feature = "f16_softfloat",
+    feature = "f32_softfloat",
+    feature = "f64_softfloat",
+    feature = "f128_softfloat"
+))]
+use core::cell::Cell;
+use core::ffi::c_int;
+
+use crate::{DenormalMode, DenormalModeError, RoundingMode, RoundingModeError};
+#[cfg(any(
+    feature = "f16_softfloat",
+    feature = "f32_softfloat",
+    feature = "f64_softfloat",
+    feature = "f128_softfloat"
+))]
+use crate::{NanSemantics, Tininess};
+
+#[cfg(srmfpa_fenv)]
+extern "C" {
+    fn c_get_round() -> c_int;
+    fn c_set_round(round: c_int) -> c_int;
+}
+
+/// Returns the rounding mode as `fegetround` on x86_64 without C.
+///
+/// It reads MXCSR, which Rust's float ops follow.
+#[cfg(not(srmfpa_fenv))]
+#[inline]
+unsafe fn c_get_round() -> c_int {
+    (((get_mxcsr() & MXCSR_RC) >> 13) << crate::FE_SHIFT) as c_int
+}
+
+/// Sets the rounding mode as `fesetround` on x86_64 without C.
+///
+/// It sets both MXCSR and x87 FPU control word.
+#[cfg(not(srmfpa_fenv))]
+#[inline]
+unsafe fn c_set_round(round: c_int) -> c_int {
+    if round & !(3 << crate::FE_SHIFT) != 0 {
+        return -1;
+    }
+    let rc = (round >> crate::FE_SHIFT) as u32;
+
+    set_mxcsr((get_mxcsr() & !MXCSR_RC) | (rc << 13));
+
+    let mut cw = 0u16;
+    unsafe {
+        core::arch::asm!("fnstcw [{cw}]", cw = in(reg) &mut cw, options(nostack, preserves_flags));
+    }
+    cw = (cw & !0x0c00) | ((rc as u16) << 10);
+    unsafe {
+        core::arch::asm!("fldcw [{cw}]", cw = in(reg) &cw, options(nostack, preserves_flags));
+    }
+    0
+}
+
+/// Returns the current rounding mode of the thread's floating-point environment (`<fenv.h>`).
+///
+/// It returns `None` when fails to get the mode or the mode is not an IEEE 754 one.
+///
+/// ```
+/// use srmfpa::{current_rounding_mode, RoundingMode};
+///
+/// assert!(matches!(current_rounding_mode(), Some(RoundingMode::NearestTiesEven)));
+/// ```
+#[must_use]
+#[inline]
+pub fn current_rounding_mode() -> Option<RoundingMode> {
+    let round = unsafe { c_get_round() };
+    if round < 0 {
+        return None;
+    }
+    RoundingMode::from_c_int(round)
+}
+
+/// Returns `true` if the thread's rounding mode (`<fenv.h>`) is to nearest, ties to even.
+///
+/// Rust's float ops follow the thread's mode, thus the ops evaluated by them
+/// (e.g. error-free transformations) are valid only if this is `true`.
+#[inline]
+pub(crate) fn is_nearest() -> bool {
+    unsafe { c_get_round() == RoundingMode::NearestTiesEven.as_c_int() }
+}
+
+/// Sets the rounding mode of the thread's floating-point environment (`<fenv.h>`),
+/// and of Berkeley SoftFloat with `fN_softfloat` features.
+///
+/// The ops of the default, `fN_asm` and `fN_softfloat` backends do not depend on the current mode,
+/// they set and restore the mode by themselves (or do not use it),
+/// nor do `const_round_*` and the ops of [`FpEnv`][crate::FpEnv].
+/// This is for interoperating with C libraries which depend on the current mode.
+///
+/// Notes, Rust's float ops (e.g. `+`) are optimized by the compiler under rounding to nearest,
+/// they may not follow the current mode.
+/// Thus, the ops of the `fN_pure_rust` and `fN_widening` backends, which evaluate Rust's float ops,
+/// return wrong results unless the current mode is to nearest.
+///
+/// # Errors
+///
+/// Returns an error when the mode is not supported or fails to set the mode.
+///
+/// ```
+/// use srmfpa::{current_rounding_mode, set_rounding_mode, RoundingMode};
+///
+/// set_rounding_mode(&RoundingMode::TowardZero).unwrap();
+/// assert!(matches!(current_rounding_mode(), Some(RoundingMode::TowardZero)));
+///
+/// set_rounding_mode(&RoundingMode::NearestTiesEven).unwrap();
+/// ```
+#[inline]
+pub fn set_rounding_mode(mode: &RoundingMode) -> Result<(), RoundingModeError> {
+    if !mode.supported() || unsafe { c_set_round(mode.as_c_int()) } != 0 {
+        return Err(RoundingModeError::new(*mode));
+    }
+
+    #[cfg(any(
+        feature = "f16_softfloat",
+        feature = "f32_softfloat",
+        feature = "f64_softfloat",
+        feature = "f128_softfloat"
+    ))]
+    unsafe {
+        srmfpa_berkeley_softfloat_3::c_set_rounding_mode(mode.as_berkeley_softfloat())
+    };
+
+    Ok(())
+}
+
+/// Returns the current rounding mode of Berkeley SoftFloat (`softfloat_roundingMode`) of the thread.
+///
+/// It returns `None` when the mode is not an IEEE 754 one (e.g. round to odd).
+#[cfg(any(
+    feature = "f16_softfloat",
+    feature = "f32_softfloat",
+    feature = "f64_softfloat",
+    feature = "f128_softfloat"
+))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(
+        feature = "f16_softfloat",
+        feature = "f32_softfloat",
+        feature = "f64_softfloat",
+        feature = "f128_softfloat"
+    )))
+)]
+#[must_use]
+#[inline]
+pub fn current_softfloat_rounding_mode() -> Option<RoundingMode> {
+    let mode = unsafe { srmfpa_berkeley_softfloat_3::c_get_rounding_mode() };
+    RoundingMode::from_berkeley_softfloat(mode)
+}
+
+/// Returns the tininess detection of Berkeley SoftFloat (`softfloat_detectTininess`) of the thread.
+///
+/// It is initialized by the specialization SoftFloat is built with (after rounding on x86 and RISC-V).
+#[cfg(any(
+    feature = "f16_softfloat",
+    feature = "f32_softfloat",
+    feature = "f64_softfloat",
+    feature = "f128_softfloat"
+))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(
+        feature = "f16_softfloat",
+        feature = "f32_softfloat",
+        feature = "f64_softfloat",
+        feature = "f128_softfloat"
+    )))
+)]
+#[must_use]
+#[inline]
+pub fn current_softfloat_tininess() -> Tininess {
+    let tininess = unsafe { srmfpa_berkeley_softfloat_3::c_get_detect_tininess() };
+    Tininess::from_berkeley_softfloat(tininess)
+}
+
+/// Sets the tininess detection of Berkeley SoftFloat (`softfloat_detectTininess`) of the thread,
+/// which determines when the ops with `fN_softfloat` features signal underflow.
+///
+/// [`FpEnv`][crate::FpEnv] has its own setting, see [`FpEnv::set_tininess`][crate::FpEnv::set_tininess].
+#[cfg(any(
+    feature = "f16_softfloat",
+    feature = "f32_softfloat",
+    feature = "f64_softfloat",
+    feature = "f128_softfloat"
+))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(
+        feature = "f16_softfloat",
+        feature = "f32_softfloat",
+        feature = "f64_softfloat",
+        feature = "f128_softfloat"
+    )))
+)]
+#[inline]
+pub fn set_softfloat_tininess(tininess: Tininess) {
+    unsafe { srmfpa_berkeley_softfloat_3::c_set_detect_tininess(tininess.as_berkeley_softfloat()) };
+}
+
+/// Calls `f` under the tininess detection of Berkeley SoftFloat, see [`set_softfloat_tininess`].
+///
+/// It restores the previous one after `f` returns or panics.
+#[cfg(any(
+    feature = "f16_softfloat",
+    feature = "f32_softfloat",
+    feature = "f64_softfloat",
+    feature = "f128_softfloat"
+))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(
+        feature = "f16_softfloat",
+        feature = "f32_softfloat",
+        feature = "f64_softfloat",
+        feature = "f128_softfloat"
+    )))
+)]
+pub fn with_softfloat_tininess<R>(tininess: Tininess, f: impl FnOnce() -> R) -> R {
+    struct Restore(Tininess);
+
+    impl Drop for Restore {
+        #[inline]
+        fn drop(&mut self) {
+            set_softfloat_tininess(self.0);
+        }
+    }
+
+    let _restore = Restore(current_softfloat_tininess());
+    set_softfloat_tininess(tininess);
+    f()
+}
+
+/// Returns the NaN semantics of the ops with `fN_softfloat` features of the thread,
+/// `None` if it is the one of the SoftFloat specialization (chosen by the build host).
+#[cfg(any(
+    feature = "f16_softfloat",
+    feature = "f32_softfloat",
+    feature = "f64_softfloat",
+    feature = "f128_softfloat"
+))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(
+        feature = "f16_softfloat",
+        feature = "f32_softfloat",
+        feature = "f64_softfloat",
+        feature = "f128_softfloat"
+    )))
+)]
+#[must_use]
+#[inline]
+pub fn current_softfloat_nan_semantics() -> Option<NanSemantics> {
+    SOFTFLOAT_NAN.with(Cell::get)
+}
+
+/// Sets the NaN semantics of the ops with `fN_softfloat` features of the thread,
+/// the NaN results are replaced with the ones of [`FpEnv`][crate::FpEnv] of the semantics.
+/// `None` restores the one of the SoftFloat specialization.
+///
+/// The SoftFloat specialization (8086-SSE, ARM-VFPv2 or RISCV) is chosen by the build host,
+/// this gives identical NaN bits across hosts.
+#[cfg(any(
+    feature = "f16_softfloat",
+    feature = "f32_softfloat",
+    feature = "f64_softfloat",
+    feature = "f128_softfloat"
+))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(
+        feature = "f16_softfloat",
+        feature = "f32_softfloat",
+        feature = "f64_softfloat",
+        feature = "f128_softfloat"
+    )))
+)]
+#[inline]
+pub fn set_softfloat_nan_semantics(nan: Option<NanSemantics>) {
+    SOFTFLOAT_NAN.with(|cell| cell.set(nan));
+}
+
+#[cfg(any(
+    feature = "f16_softfloat",
+    feature = "f32_softfloat",
+    feature = "f64_softfloat",
+    feature = "f128_softfloat"
+))]
+std::thread_local! {
+    static SOFTFLOAT_NAN: Cell<Option<NanSemantics>> = const { Cell::new(None) };
+}
+
+/// FTZ and DAZ bits of MXCSR.
+#[cfg(target_arch = "x86_64")]
+const MXCSR_FTZ: u32 = 1 << 15;
+#[cfg(target_arch = "x86_64")]
+const MXCSR_DAZ: u32 = 1 << 6;
+/// Rounding control bits of MXCSR.
+#[cfg(not(srmfpa_fenv))]
+const MXCSR_RC: u32 = 0x6000;
+
+#[cfg(target_arch = "x86_64")]
+#[inline]
+fn get_mxcsr() -> u32 {
+    let mut csr = 0u32;
+    unsafe {
+        core::arch::asm!(
+            "stmxcsr [{csr}]",
+            csr = in(reg) &mut csr,
+            options(nostack, preserves_flags),
+        );
+    }
+    csr
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline]
+fn set_mxcsr(csr: u32) {
+    unsafe {
+        core::arch::asm!(
+            "ldmxcsr [{csr}]",
+            csr = in(reg) &csr,
+            options(nostack, preserves_flags),
+        );
+    }
+}
+
+#[cfg(any(
+    feature = "f16_softfloat",
+    feature = "f32_softfloat",
+    feature = "f64_softfloat",
+    feature = "f128_softfloat"
+))]
+std::thread_local! {
+    static SOFTFLOAT_DENORMAL: Cell<DenormalMode> = const { Cell::new(DenormalMode::IEEE) };
+}
+
+/// Returns the handling of subnormal numbers of the thread, see [`set_denormal_mode`].
+#[must_use]
+#[inline]
+pub fn current_denormal_mode() -> DenormalMode {
+    #[cfg(target_arch = "x86_64")]
+    {
+        let csr = get_mxcsr();
+        DenormalMode {
+            flush_to_zero: csr & MXCSR_FTZ != 0,
+            denormals_are_zero: csr & MXCSR_DAZ != 0,
+        }
+    }
+    #[cfg(all(
+        not(target_arch = "x86_64"),
+        any(
+            feature = "f16_softfloat",
+            feature = "f32_softfloat",
+            feature = "f64_softfloat",
+            feature = "f128_softfloat"
+        )
+    ))]
+    {
+        SOFTFLOAT_DENORMAL.with(Cell::get)
+    }
+    #[cfg(all(
+        not(target_arch = "x86_64"),
+        not(any(
+            feature = "f16_softfloat",
+            feature = "f32_softfloat",
+            feature = "f64_softfloat",
+            feature = "f128_softfloat"
+        ))
+    ))]
+    {
+        DenormalMode::IEEE
+    }
+}
+
+/// Sets the handling of subnormal numbers, flush-to-zero and denormals-are-zero, of the thread.
+///
+/// It sets FTZ and DAZ bits of MXCSR on x86_64, which the default backend and `asm` features follow,
+/// and the ops with `fN_softfloat` features follow it.
+/// The results which are not flushed are rounded with the rounding mode of the op.
+/// [`FpEnv`][crate::FpEnv] has its own setting, see [`FpEnv::set_denormal_mode`][crate::FpEnv::set_denormal_mode].
+///
+/// Notes, MXCSR also affects Rust's float ops (e.g. `+`) of the thread,
+/// and `pure_rust` and `fN_widening` features which depend on them do not support the modes.
+/// The default backend skipping the mode switch on exact results and `round_*_bounds` of `f32` and `f64`
+/// also evaluate Rust's float ops, but only on operands and residuals which are not subnormal,
+/// thus they follow the modes as the directed ops.
+///
+/// # Errors
+///
+/// Returns an error when the mode is not [`DenormalMode::IEEE`] on other than x86_64
+/// without `fN_softfloat` features.
+///
+/// ```
+/// use srmfpa::{current_denormal_mode, set_denormal_mode, DenormalMode};
+///
+/// # if cfg!(target_arch = "x86_64") {
+/// set_denormal_mode(&DenormalMode::FTZ).unwrap();
+/// assert_eq!(current_denormal_mode(), DenormalMode::FTZ);
+/// # }
+///
+/// set_denormal_mode(&DenormalMode::IEEE).unwrap();
+/// ```
+#[inline]
+pub fn set_denormal_mode(mode: &DenormalMode) -> Result<(), DenormalModeError> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        let mut csr = get_mxcsr() & !(MXCSR_FTZ | MXCSR_DAZ);
+        if mode.flush_to_zero {
+            csr |= MXCSR_FTZ;
+        }
+        if mode.denormals_are_zero {
+            csr |= MXCSR_DAZ;
+        }
+        set_mxcsr(csr);
+    }
+    #[cfg(all(
+        not(target_arch = "x86_64"),
+        not(any(
+            feature = "f16_softfloat",
+            feature = "f32_softfloat",
+            feature = "f64_softfloat",
+            feature = "f128_softfloat"
+        ))
+    ))]
+    if *mode != DenormalMode::IEEE {
+        return Err(DenormalModeError::new(*mode));
+    }
+
+    #[cfg(any(
+        feature = "f16_softfloat",
+        feature = "f32_softfloat",
+        feature = "f64_softfloat",
+        feature = "f128_softfloat"
+    ))]
+    SOFTFLOAT_DENORMAL.with(|cell| cell.set(*mode));
+
+    Ok(())
+}
+
+/// Returns the handling of subnormal numbers of the ops with `fN_softfloat` features of the thread.
+#[cfg(any(
+    feature = "f16_softfloat",
+    feature = "f32_softfloat",
+    feature = "f64_softfloat",
+    feature = "f128_softfloat"
+))]
+#[inline]
+pub(crate) fn current_softfloat_denormal_mode() -> DenormalMode {
+    SOFTFLOAT_DENORMAL.with(Cell::get)
+}
+
+/// Calls `f` under the handling of subnormal numbers, see [`set_denormal_mode`].
+///
+/// It restores the previous one after `f` returns or panics.
+///
+/// # Safety
+///
+/// Panics when fail to set the mode.
+///
+/// ```
+/// use srmfpa::{current_denormal_mode, with_denormal_mode, DenormalMode};
+///
+/// # if cfg!(target_arch = "x86_64") {
+/// let mode = with_denormal_mode(&DenormalMode::FTZ_DAZ, current_denormal_mode);
+/// assert_eq!(mode, DenormalMode::FTZ_DAZ);
+///
+/// assert_eq!(current_denormal_mode(), DenormalMode::IEEE);
+/// # }
+/// ```
+pub fn with_denormal_mode<R>(mode: &DenormalMode, f: impl FnOnce() -> R) -> R {
+    struct Restore(DenormalMode);
+
+    impl Drop for Restore {
+        #[inline]
+        fn drop(&mut self) {
+            // it never fails to set the mode which has been set
+            let _ = set_denormal_mode(&self.0);
+        }
+    }
+
+    let _restore = Restore(current_denormal_mode());
+    if set_denormal_mode(mode).is_err() {
+        panic!("fail to set denormal mode")
+    }
+    f()
+}
+
+/// Restores the rounding modes on drop, even on unwinding.
+struct Restore {
+    round: c_int,
+    #[cfg(any(
+        feature = "f16_softfloat",
+        feature = "f32_softfloat",
+        feature = "f64_softfloat",
+        feature = "f128_softfloat"
+    ))]
+    softfloat: u8,
+}
+
+impl Restore {
+    #[inline]
+    fn save() -> Self {
+        let round = unsafe { c_get_round() };
+        if round < 0 {
+            crate::r#impl::error()
+        }
+        Self {
+            round,
+            #[cfg(any(
+                feature = "f16_softfloat",
+                feature = "f32_softfloat",
+                feature = "f64_softfloat",
+                feature = "f128_softfloat"
+            ))]
+            softfloat: unsafe { srmfpa_berkeley_softfloat_3::c_get_rounding_mode() },
+        }
+    }
+}
+
+impl Drop for Restore {
+    #[inline]
+    fn drop(&mut self) {
+        #[cfg(any(
+            feature = "f16_softfloat",
+            feature = "f32_softfloat",
+            feature = "f64_softfloat",
+            feature = "f128_softfloat"
+        ))]
+        unsafe {
+            srmfpa_berkeley_softfloat_3::c_set_rounding_mode(self.softfloat)
+        };
+
+        // do not panic while panicking, it aborts
+        if unsafe { c_set_round(self.round) } != 0 && !std::thread::panicking() {
+            crate::r#impl::error()
+        }
+    }
+}
+
+/// Calls `f` under the rounding mode, see [`set_rounding_mode`].
+///
+/// It restores the previous mode after `f` returns or panics.
+/// The ops of the `fN_pure_rust` and `fN_widening` backends must not be called in `f`
+/// unless the mode is to nearest.
+///
+/// # Safety
+///
+/// Panics when fail to set/restore rounding mode.
+///
+/// ```
+/// use srmfpa::{current_rounding_mode, with_rounding_mode, RoundingMode};
+///
+/// let mode = with_rounding_mode(&RoundingMode::TowardPosInf, current_rounding_mode);
+/// assert!(matches!(mode, Some(RoundingMode::TowardPosInf)));
+///
+/// assert!(matches!(current_rounding_mode(), Some(RoundingMode::NearestTiesEven)));
+/// ```
+pub fn with_rounding_mode<R>(mode: &RoundingMode, f: impl FnOnce() -> R) -> R {
+    let _restore = Restore::save();
+    if set_rounding_mode(mode).is_err() {
+        crate::r#impl::error()
+    }
+    f()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_set() {
+        assert!(matches!(
+            current_rounding_mode(),
+            Some(RoundingMode::NearestTiesEven)
+        ));
+
+        for mode in &RoundingMode::ALL {
+            set_rounding_mode(mode).unwrap();
+            assert_eq!(current_rounding_mode(), Some(*mode));
+        }
+
+        set_rounding_mode(&RoundingMode::NearestTiesEven).unwrap();
+    }
+
+    #[test]
+    fn test_set_native() {
+        use core::hint::black_box;
+
+        // Rust's float ops follow the mode
+        let up = with_rounding_mode(&RoundingMode::TowardPosInf, || {
+            assert!(!is_nearest());
+            black_box(0.1f64) + black_box(0.2f64)
+        });
+        let down = with_rounding_mode(&RoundingMode::TowardNegInf, || {
+            black_box(0.1f64) + black_box(0.2f64)
+        });
+        assert!(is_nearest());
+        assert_eq!(up, 0.30000000000000004);
+        assert_eq!(down, 0.3);
+    }
+
+    #[test]
+    fn test_with() {
+        let r = with_rounding_mode(&RoundingMode::TowardNegInf, || {
+            assert!(matches!(
+                current_rounding_mode(),
+                Some(RoundingMode::TowardNegInf)
+            ));
+
+            // nested
+            with_rounding_mode(&RoundingMode::TowardZero, || {
+                assert!(matches!(
+                    current_rounding_mode(),
+                    Some(RoundingMode::TowardZero)
+                ));
+            });
+            assert!(matches!(
+                current_rounding_mode(),
+                Some(RoundingMode::TowardNegInf)
+            ));
+
+            1
+        });
+        assert_eq!(r, 1);
+        assert!(matches!(
+            current_rounding_mode(),
+            Some(RoundingMode::NearestTiesEven)
+        ));
+    }
+
+    #[test]
+    fn test_with_unwind() {
+        let r = std::panic::catch_unwind(|| {
+            with_rounding_mode(&RoundingMode::TowardPosInf, || panic!("unwind"))
+        });
+        assert!(r.is_err());
+        assert!(matches!(
+            current_rounding_mode(),
+            Some(RoundingMode::NearestTiesEven)
+        ));
+    }
+
+    #[cfg(feature = "f64_softfloat")]
+    #[test]
+    fn test_softfloat_tininess() {
+        use crate::{catch_exception, set_exception_handler, Action, ExceptionKind};
+
+        // 2^emin - 2^(emin - 104) rounds to 2^emin, tiny only before rounding
+        let x = f64::from_bits(f64::MIN_POSITIVE.to_bits() + 1);
+        let y = 1.0 - f64::EPSILON;
+        let mul = || crate::f64::round_mul(x, y, &RoundingMode::NearestTiesEven);
+
+        set_exception_handler(ExceptionKind::Underflow, Some(|_| Action::Abort));
+
+        with_softfloat_tininess(Tininess::AfterRounding, || {
+            assert_eq!(current_softfloat_tininess(), Tininess::AfterRounding);
+            assert_eq!(catch_exception(mul), Ok(f64::MIN_POSITIVE));
+        });
+        with_softfloat_tininess(Tininess::BeforeRounding, || {
+            assert_eq!(current_softfloat_tininess(), Tininess::BeforeRounding);
+            assert!(catch_exception(mul).is_err());
+        });
+
+        set_exception_handler(ExceptionKind::Underflow, None);
+    }
+
+    #[cfg(feature = "f64_softfloat")]
+    #[test]
+    fn test_softfloat_nan_semantics() {
+        use crate::NanSemantics;
+
+        let nan = f64::from_bits(0x7FF8_0000_0000_0001);
+        let mode = &RoundingMode::NearestTiesEven;
+
+        for (semantics, invalid, propagated) in [
+            (NanSemantics::X86, 0xFFF8 << 48, 0x7FF8_0000_0000_0001),
+            (NanSemantics::RiscVCanonical, 0x7FF8 << 48, 0x7FF8 << 48),
+            (
+                NanSemantics::PreservePayload,
+                0x7FF8 << 48,
+                0x7FF8_0000_0000_0001,
+            ),
+        ] {
+            set_softfloat_nan_semantics(Some(semantics));
+            assert_eq!(current_softfloat_nan_semantics(), Some(semantics));
+
+            let r = crate::f64::round_sub(f64::INFINITY, f64::INFINITY, mode);
+            assert_eq!(r.to_bits(), invalid);
+            let r = crate::f64::round_mul_add(1.0, 2.0, nan, mode);
+            assert_eq!(r.to_bits(), propagated);
+        }
+
+        set_softfloat_nan_semantics(None);
+        assert_eq!(current_softfloat_nan_semantics(), None);
+    }
+
+    // pure_rust and widening do not support the modes
+    #[cfg(all(
+        target_arch = "x86_64",
+        not(any(
+            feature = "f32_pure_rust",
+            feature = "f64_pure_rust",
+            feature = "f32_widening"
+        ))
+    ))]
+    #[test]
+    fn test_denormal() {
+        let sub = f64::MIN_POSITIVE / 4.0;
+        let mode = &RoundingMode::TowardPosInf;
+
+        assert_eq!(current_denormal_mode(), DenormalMode::IEEE);
+        let r = crate::f64::round_mul(f64::MIN_POSITIVE, 0.1, mode);
+        assert!(0.0 < r && r < f64::MIN_POSITIVE);
+
+        with_denormal_mode(&DenormalMode::FTZ, || {
+            assert_eq!(current_denormal_mode(), DenormalMode::FTZ);
+            assert_eq!(crate::f64::round_mul(f64::MIN_POSITIVE, 0.1, mode), 0.0);
+            assert_eq!(crate::f32::round_div(f32::MIN_POSITIVE, 3.0, mode), 0.0);
+            // the others are rounded with the mode
+            assert_eq!(crate::f64::round_add(0.1, 0.2, mode), 0.30000000000000004);
+            assert_eq!(crate::f64::round_add(sub, 1.0, mode), 1.0f64.next_up());
+        });
+
+        with_denormal_mode(&DenormalMode::DAZ, || {
+            assert_eq!(crate::f64::round_add(sub, 1.0, mode), 1.0);
+        });
+
+        assert_eq!(current_denormal_mode(), DenormalMode::IEEE);
+    }
+
+    #[cfg(all(
+        target_arch = "x86_64",
+        not(any(
+            feature = "f32_pure_rust",
+            feature = "f64_pure_rust",
+            feature = "f32_widening"
+        ))
+    ))]
+    #[test]
+    fn test_denormal_ops() {
+        use crate::f64::*;
+
+        let f = |x: f64| x.to_bits();
+        let g = |(a, b): (f64, f64)| (a.to_bits(), b.to_bits());
+        let (lo, hi) = (&RoundingMode::TowardNegInf, &RoundingMode::TowardPosInf);
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        let mut next = || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        with_denormal_mode(&DenormalMode::FTZ, || {
+            for _ in 0..20000 {
+                // operands close to underflow or subnormal, and the ones around 1
+                let a = f64::from_bits(next() & 0x800F_FFFF_FFFF_FFFF | 0x3FF0_0000_0000_0000);
+                let r = next();
+                let b = f64::from_bits(r & 0x800F_FFFF_FFFF_FFFF | ((r >> 52) % 130) << 52);
+                let r = next();
+                let c = f64::from_bits(r & 0x800F_FFFF_FFFF_FFFF | ((r >> 52) % 130) << 52);
+
+                // the bounds are identical to the directed ops, which are flushed
+                assert_eq!(
+                    g(round_add_bounds(b, c)),
+                    g((floor_add(b, c), ciel_add(b, c)))
+                );
+                assert_eq!(
+                    g(round_mul_bounds(a, b)),
+                    g((floor_mul(a, b), ciel_mul(a, b)))
+                );
+                assert_eq!(
+                    g(round_div_bounds(b, a)),
+                    g((floor_div(b, a), ciel_div(b, a)))
+                );
+                assert_eq!(
+                    g(round_sqrt_bounds(b.abs())),
+                    g((floor_sqrt(b.abs()), ciel_sqrt(b.abs())))
+                );
+                assert_eq!(
+                    g(round_mul_add_bounds(a, b, c)),
+                    g((floor_mul_add(a, b, c), ciel_mul_add(a, b, c)))
+                );
+
+                // the normal results are not flushed
+                let normal = |x: f64| x.abs() >= 2.0 * f64::MIN_POSITIVE;
+                for (e, r) in [
+                    (const_round_add(b, c, lo), floor_add(b, c)),
+                    (const_round_add(b, c, hi), ciel_add(b, c)),
+                    (const_round_mul(a, b, lo), floor_mul(a, b)),
+                    (const_round_mul(a, b, hi), ciel_mul(a, b)),
+                    (const_round_div(b, a, lo), floor_div(b, a)),
+                    (const_round_div(b, a, hi), ciel_div(b, a)),
+                    (const_round_mul_add(a, b, c, lo), floor_mul_add(a, b, c)),
+                    (const_round_mul_add(a, b, c, hi), ciel_mul_add(a, b, c)),
+                ] {
+                    if normal(e) {
+                        assert_eq!(f(r), f(e), "{a:e} {b:e} {c:e}");
+                    }
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,125 @@
+use core::fmt::{Display, Formatter};
+
+use crate::{DenormalMode, RoundingMode};
+
+/// An error which can be returned when parsing a float.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+}
+
+/// Kinds of [`ParseError`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseErrorKind {
+    /// The string is empty.
+    Empty,
+    /// The string is not a valid float literal.
+    Invalid,
+}
+
+impl ParseError {
+    #[inline]
+    pub(crate) const fn new(kind: ParseErrorKind) -> Self {
+        Self { kind }
+    }
+
+    /// Returns the detailed cause of the error.
+    #[must_use]
+    #[inline]
+    pub fn kind(&self) -> &ParseErrorKind {
+        &self.kind
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self.kind {
+            ParseErrorKind::Empty => f.write_str("cannot parse float from empty string"),
+            ParseErrorKind::Invalid => f.write_str("invalid float literal"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// An error which can be returned when setting rounding mode,
+/// see [`set_rounding_mode`][crate::set_rounding_mode].
+#[derive(Debug, Clone)]
+pub struct RoundingModeError {
+    mode: RoundingMode,
+}
+
+impl RoundingModeError {
+    #[inline]
+    pub(crate) const fn new(mode: RoundingMode) -> Self {
+        Self { mode }
+    }
+
+    /// Returns the rounding mode which failed to be set.
+    #[must_use]
+    #[inline]
+    pub fn mode(&self) -> &RoundingMode {
+        &self.mode
+    }
+}
+
+impl Display for RoundingModeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "fail to set rounding mode to {:?}", self.mode)
+    }
+}
+
+impl std::error::Error for RoundingModeError {}
+
+/// An error which can be returned when parsing a [`RoundingMode`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRoundingModeError {
+    _priv: (),
+}
+
+impl ParseRoundingModeError {
+    #[inline]
+    pub(crate) const fn new() -> Self {
+        Self { _priv: () }
+    }
+}
+
+impl Display for ParseRoundingModeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str("invalid rounding mode name")
+    }
+}
+
+impl std::error::Error for ParseRoundingModeError {}
+
+/// An error which can be returned when setting the handling of subnormal numbers,
+/// see [`set_denormal_mode`][crate::set_denormal_mode].
+#[derive(Debug, Clone)]
+pub struct DenormalModeError {
+    mode: DenormalMode,
+}
+
+impl DenormalModeError {
+    // used only on the targets which do not support the modes
+    #[allow(dead_code)]
+    #[inline]
+    pub(crate) const fn new(mode: DenormalMode) -> Self {
+        Self { mode }
+    }
+
+    /// Returns the mode which failed to be set.
+    #[must_use]
+    #[inline]
+    pub fn mode(&self) -> &DenormalMode {
+        &self.mode
+    }
+}
+
+impl Display for DenormalModeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "fail to set denormal mode to {:?}", self.mode)
+    }
+}
+
+impl std::error::Error for DenormalModeError {}
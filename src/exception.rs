@@ -0,0 +1,523 @@
+use core::cell::Cell;
+use core::fmt::{Display, Formatter};
+use core::panic::Location;
+use std::panic::AssertUnwindSafe;
+
+use crate::{ExceptionFlags, RoundingFloat, RoundingMode};
+
+/// Exceptions which can be handled by an [`ExceptionHandler`], see [`set_exception_handler`].
+///
+/// Inexact is not handled, it only raises the flag.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ExceptionKind {
+    /// Invalid operation, see [`ExceptionFlags::INVALID`].
+    Invalid,
+    /// Division by zero, see [`ExceptionFlags::DIV_BY_ZERO`].
+    DivByZero,
+    /// Overflow, see [`ExceptionFlags::OVERFLOW`].
+    Overflow,
+    /// Underflow, see [`ExceptionFlags::UNDERFLOW`].
+    Underflow,
+}
+
+impl ExceptionKind {
+    /// All kinds in the order they are handled.
+    pub const ALL: [Self; 4] = [
+        Self::Invalid,
+        Self::DivByZero,
+        Self::Overflow,
+        Self::Underflow,
+    ];
+
+    /// Returns the flag of the exception.
+    #[must_use]
+    #[inline]
+    pub const fn flag(&self) -> ExceptionFlags {
+        match self {
+            Self::Invalid => ExceptionFlags::INVALID,
+            Self::DivByZero => ExceptionFlags::DIV_BY_ZERO,
+            Self::Overflow => ExceptionFlags::OVERFLOW,
+            Self::Underflow => ExceptionFlags::UNDERFLOW,
+        }
+    }
+}
+
+impl Display for ExceptionKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.pad(match self {
+            Self::Invalid => "invalid",
+            Self::DivByZero => "division by zero",
+            Self::Overflow => "overflow",
+            Self::Underflow => "underflow",
+        })
+    }
+}
+
+/// Ops which signal an [`Exception`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// `a + b`
+    Add,
+    /// `a - b`
+    Sub,
+    /// `a * b`
+    Mul,
+    /// `a / b`
+    Div,
+    /// `a * b + c`
+    MulAdd,
+    /// `a.sqrt()`
+    Sqrt,
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.pad(match self {
+            Self::Add => "add",
+            Self::Sub => "sub",
+            Self::Mul => "mul",
+            Self::Div => "div",
+            Self::MulAdd => "mul_add",
+            Self::Sqrt => "sqrt",
+        })
+    }
+}
+
+/// An exception signaled by an op, passed to the [`ExceptionHandler`].
+///
+/// It is also the error of [`catch_exception`] when the handler aborts.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Exception {
+    kind: ExceptionKind,
+    op: Operation,
+    mode: RoundingMode,
+    location: &'static Location<'static>,
+}
+
+impl Exception {
+    /// Returns the kind of the exception.
+    #[must_use]
+    #[inline]
+    pub const fn kind(&self) -> ExceptionKind {
+        self.kind
+    }
+
+    /// Returns the op which signaled the exception.
+    #[must_use]
+    #[inline]
+    pub const fn op(&self) -> Operation {
+        self.op
+    }
+
+    /// Returns the rounding mode of the op.
+    #[must_use]
+    #[inline]
+    pub const fn mode(&self) -> RoundingMode {
+        self.mode
+    }
+
+    /// Returns the source location which called the op.
+    #[must_use]
+    #[inline]
+    pub const fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+}
+
+impl Display for Exception {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "floating-point exception ({}) in {} at {}",
+            self.kind, self.op, self.location
+        )
+    }
+}
+
+impl std::error::Error for Exception {}
+
+/// Alternate exception handling attributes, returned by an [`ExceptionHandler`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Action {
+    /// Delivers the default result and raises the flag.
+    Default,
+    /// Delivers the value (rounded to the format with the rounding mode) instead of the default result,
+    /// and does not raise the flag.
+    Substitute(f64),
+    /// Delivers the largest finite number with the sign instead of infinity,
+    /// and does not raise the flag, for overflow and division by zero.
+    /// Otherwise the same as [`Action::Default`].
+    Saturate,
+    /// Aborts the computation, the op panics with the [`Exception`] as the payload
+    /// and [`catch_exception`] returns it.
+    ///
+    /// It requires unwinding, the process aborts under `panic = "abort"`.
+    Abort,
+}
+
+/// A handler called when an op signals an exception.
+///
+/// It is a function pointer, record state (e.g. the first location invalid happens)
+/// to a `thread_local!` or a `static` if needed.
+pub type ExceptionHandler = fn(&Exception) -> Action;
+
+/// Handlers indexed by [`ExceptionKind`].
+#[derive(Debug, Copy, Clone, Default)]
+pub(crate) struct Handlers([Option<ExceptionHandler>; 4]);
+
+impl Handlers {
+    #[inline]
+    pub(crate) const fn new() -> Self {
+        Self([None; 4])
+    }
+
+    #[inline]
+    pub(crate) const fn get(&self, kind: ExceptionKind) -> Option<ExceptionHandler> {
+        self.0[kind as usize]
+    }
+
+    #[inline]
+    pub(crate) fn replace(
+        &mut self,
+        kind: ExceptionKind,
+        handler: Option<ExceptionHandler>,
+    ) -> Option<ExceptionHandler> {
+        core::mem::replace(&mut self.0[kind as usize], handler)
+    }
+
+    #[cfg(any(
+        feature = "f16_softfloat",
+        feature = "f32_softfloat",
+        feature = "f64_softfloat",
+        feature = "f128_softfloat"
+    ))]
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.0.iter().all(Option::is_none)
+    }
+}
+
+std::thread_local! {
+    static HANDLERS: Cell<Handlers> = const { Cell::new(Handlers::new()) };
+}
+
+/// Sets the handler of the exception of the thread, returns the previous one.
+/// `None` restores the default handling, that is, delivers the default result and raises the flag.
+///
+/// The handlers are called by the ops of [`FpEnv`][crate::FpEnv]
+/// (unless it has its own handler of the exception, see [`FpEnv::set_handler`][crate::FpEnv::set_handler])
+/// and by the ops with `fN_softfloat` features.
+/// The other backends do not detect exceptions.
+///
+/// ```
+/// use srmfpa::{set_exception_handler, Action, ExceptionKind, FpEnv};
+///
+/// set_exception_handler(ExceptionKind::Overflow, Some(|_| Action::Saturate));
+///
+/// let mut env = FpEnv::default();
+/// assert_eq!(env.mul(f64::MAX, 2.0), f64::MAX);
+/// assert_eq!(env.mul(f64::MIN, 2.0), f64::MIN);
+///
+/// set_exception_handler(ExceptionKind::Overflow, None);
+/// assert_eq!(env.mul(f64::MAX, 2.0), f64::INFINITY);
+/// ```
+#[inline]
+pub fn set_exception_handler(
+    kind: ExceptionKind,
+    handler: Option<ExceptionHandler>,
+) -> Option<ExceptionHandler> {
+    HANDLERS.with(|handlers| {
+        let mut h = handlers.get();
+        let prev = h.replace(kind, handler);
+        handlers.set(h);
+        prev
+    })
+}
+
+/// Returns the handler of the exception of the thread, see [`set_exception_handler`].
+#[must_use]
+#[inline]
+pub fn exception_handler(kind: ExceptionKind) -> Option<ExceptionHandler> {
+    HANDLERS.with(|handlers| handlers.get().get(kind))
+}
+
+/// Calls `f` and returns the exception when a handler aborts it by [`Action::Abort`].
+///
+/// The abort is a panic whose payload is the [`Exception`] (by [`std::panic::panic_any`]),
+/// the other panics are propagated.
+/// Notes, the panic hook is called on the abort,
+/// and it requires unwinding, the process aborts under `panic = "abort"`.
+///
+/// ```
+/// use srmfpa::{catch_exception, Action, ExceptionKind, FpEnv, Operation};
+///
+/// let mut env = FpEnv::default();
+/// env.set_handler(ExceptionKind::Invalid, Some(|_| Action::Abort));
+///
+/// let r = catch_exception(|| {
+///     let x = env.sub(f64::INFINITY, f64::INFINITY);
+///     env.mul(x, 2.0)
+/// });
+///
+/// let e = r.unwrap_err();
+/// assert_eq!(e.kind(), ExceptionKind::Invalid);
+/// assert_eq!(e.op(), Operation::Sub);
+/// ```
+pub fn catch_exception<R>(f: impl FnOnce() -> R) -> Result<R, Exception> {
+    match std::panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(r) => Ok(r),
+        Err(payload) => match payload.downcast::<Exception>() {
+            Ok(exception) => Err(*exception),
+            Err(payload) => std::panic::resume_unwind(payload),
+        },
+    }
+}
+
+#[cold]
+fn abort(exception: Exception) -> ! {
+    std::panic::panic_any(exception)
+}
+
+/// Handles the exceptions `raised` by the op,
+/// returns the result and the flags to be raised.
+///
+/// The handler of `handlers` precedes the thread's one.
+pub(crate) fn handle<T: RoundingFloat>(
+    handlers: &Handlers,
+    mut raised: ExceptionFlags,
+    mut result: T,
+    op: Operation,
+    mode: &RoundingMode,
+    location: &'static Location<'static>,
+) -> (T, ExceptionFlags) {
+    for kind in ExceptionKind::ALL {
+        if !raised.contains(kind.flag()) {
+            continue;
+        }
+
+        let Some(handler) = handlers.get(kind).or_else(|| exception_handler(kind)) else {
+            continue;
+        };
+
+        let exception = Exception {
+            kind,
+            op,
+            mode: *mode,
+            location,
+        };
+        match handler(&exception) {
+            Action::Default => {}
+            Action::Substitute(value) => {
+                result = T::round_from_f64(value, mode);
+                raised.remove(kind.flag());
+            }
+            Action::Saturate => {
+                if result == T::INFINITY {
+                    result = T::MAX;
+                    raised.remove(kind.flag());
+                } else if result == T::NEG_INFINITY {
+                    result = T::MIN;
+                    raised.remove(kind.flag());
+                }
+            }
+            Action::Abort => abort(exception),
+        }
+    }
+
+    (result, raised)
+}
+
+/// Calls the SoftFloat op `f`, and handles the exceptions it raises
+/// (`softfloat_raiseFlags`) by the thread's handlers.
+///
+/// The flags of SoftFloat (`softfloat_exceptionFlags`) are kept sticky,
+/// except the ones the handlers substitute.
+#[cfg(any(
+    feature = "f16_softfloat",
+    feature = "f32_softfloat",
+    feature = "f64_softfloat",
+    feature = "f128_softfloat"
+))]
+#[track_caller]
+#[inline]
+pub(crate) fn handle_softfloat<T: RoundingFloat>(
+    op: Operation,
+    mode: &RoundingMode,
+    f: impl FnOnce() -> T,
+) -> T {
+    use srmfpa_berkeley_softfloat_3::{c_get_exception_flags, c_set_exception_flags};
+
+    if HANDLERS.with(|handlers| handlers.get().is_empty()) {
+        return f();
+    }
+
+    let location = Location::caller();
+
+    let prev = unsafe { c_get_exception_flags() };
+    unsafe { c_set_exception_flags(0) };
+    let result = f();
+    let raw = unsafe { c_get_exception_flags() };
+    unsafe { c_set_exception_flags(prev | raw) };
+
+    let raised = ExceptionFlags::from_berkeley_softfloat(raw);
+    let (result, flags) = handle(&Handlers::new(), raised, result, op, mode, location);
+    if flags != raised {
+        unsafe { c_set_exception_flags(prev | flags.as_berkeley_softfloat()) };
+    }
+    result
+}
+
+#[cfg(any(
+    feature = "f16_softfloat",
+    feature = "f32_softfloat",
+    feature = "f64_softfloat",
+    feature = "f128_softfloat"
+))]
+impl ExceptionFlags {
+    const BERKELEY_SOFTFLOAT: [(u8, Self); 5] = {
+        use srmfpa_berkeley_softfloat_3::*;
+
+        [
+            (softfloat_flag_invalid, Self::INVALID),
+            (softfloat_flag_infinite, Self::DIV_BY_ZERO),
+            (softfloat_flag_overflow, Self::OVERFLOW),
+            (softfloat_flag_underflow, Self::UNDERFLOW),
+            (softfloat_flag_inexact, Self::INEXACT),
+        ]
+    };
+
+    /// Returns the flags of SoftFloat's flags (e.g. `softfloat_flag_infinite` is division by zero).
+    fn from_berkeley_softfloat(flags: u8) -> Self {
+        Self::BERKELEY_SOFTFLOAT
+            .into_iter()
+            .filter(|(sf, _)| flags & sf != 0)
+            .fold(Self::empty(), |r, (_, flag)| r | flag)
+    }
+
+    /// Returns SoftFloat's flags of the flags.
+    pub(crate) fn as_berkeley_softfloat(&self) -> u8 {
+        Self::BERKELEY_SOFTFLOAT
+            .into_iter()
+            .filter(|(_, flag)| self.contains(*flag))
+            .fold(0, |r, (sf, _)| r | sf)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::FpEnv;
+
+    #[test]
+    fn test_thread_handler() {
+        fn zero(_: &Exception) -> Action {
+            Action::Substitute(0.0)
+        }
+
+        let prev = set_exception_handler(ExceptionKind::DivByZero, Some(zero));
+        assert!(prev.is_none());
+
+        let mut env = FpEnv::default();
+        assert_eq!(env.div(1.0, 0.0), 0.0);
+        assert_eq!(env.div(1.0f32, 0.0), 0.0);
+        assert!(env.flags().is_empty());
+
+        // the other exceptions are not handled
+        assert!(env.div(0.0f64, 0.0).is_nan());
+        assert_eq!(env.flags(), ExceptionFlags::INVALID);
+
+        // handled in other threads by their own handlers
+        std::thread::spawn(|| {
+            assert_eq!(FpEnv::default().div(1.0, 0.0), f64::INFINITY);
+        })
+        .join()
+        .unwrap();
+
+        assert!(set_exception_handler(ExceptionKind::DivByZero, None).is_some());
+        assert_eq!(env.div(1.0, 0.0), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_env_handler() {
+        set_exception_handler(ExceptionKind::Overflow, Some(|_| Action::Abort));
+
+        let mut env = FpEnv::default();
+        env.set_handler(ExceptionKind::Overflow, Some(|_| Action::Saturate));
+        env.set_handler(
+            ExceptionKind::Underflow,
+            Some(|e| {
+                assert_eq!(e.op(), Operation::Div);
+                assert_eq!(e.mode(), RoundingMode::NearestTiesEven);
+                Action::Substitute(f64::MIN_POSITIVE)
+            }),
+        );
+
+        // the handler of env precedes
+        assert_eq!(env.mul(f64::MAX, -2.0), f64::MIN);
+        assert_eq!(env.div(f64::MIN_POSITIVE, 3.0), f64::MIN_POSITIVE);
+        // rounded to the format, 2^-1022 is zero in f32
+        assert_eq!(env.div(f32::MIN_POSITIVE, 3.0), 0.0);
+        assert_eq!(env.flags(), ExceptionFlags::INEXACT);
+
+        // saturate does not handle division by zero of the default handling
+        assert_eq!(env.div(1.0, 0.0), f64::INFINITY);
+
+        env.set_handler(ExceptionKind::Overflow, None);
+        let e = catch_exception(|| env.add(f64::MAX, f64::MAX)).unwrap_err();
+        assert_eq!(e.kind(), ExceptionKind::Overflow);
+        assert_eq!(e.op(), Operation::Add);
+        assert_eq!(e.location().file(), file!());
+        assert_eq!(
+            e.to_string(),
+            format!(
+                "floating-point exception (overflow) in add at {}",
+                e.location()
+            )
+        );
+
+        set_exception_handler(ExceptionKind::Overflow, None);
+        assert_eq!(env.add(f64::MAX, f64::MAX), f64::INFINITY);
+        assert_eq!(
+            env.flags(),
+            ExceptionFlags::DIV_BY_ZERO | ExceptionFlags::OVERFLOW | ExceptionFlags::INEXACT
+        );
+
+        // saturate does not change finite default results
+        env.set_mode(RoundingMode::TowardZero);
+        env.clear_flags();
+        env.set_handler(ExceptionKind::Overflow, Some(|_| Action::Saturate));
+        assert_eq!(env.mul(f64::MAX, 2.0), f64::MAX);
+        assert_eq!(
+            env.flags(),
+            ExceptionFlags::OVERFLOW | ExceptionFlags::INEXACT
+        );
+    }
+
+    #[test]
+    fn test_catch() {
+        assert_eq!(catch_exception(|| 1), Ok(1));
+
+        // the other panics are propagated
+        let r = std::panic::catch_unwind(|| catch_exception(|| panic!("other")));
+        assert!(r.is_err());
+
+        // even if the message is the same as an exception
+        let r = std::panic::catch_unwind(|| {
+            catch_exception(|| {
+                panic!(
+                    "floating-point exception (invalid) in sqrt at {}",
+                    Location::caller()
+                )
+            })
+        });
+        assert!(r.unwrap_err().downcast::<String>().is_ok());
+
+        let r = catch_exception(|| {
+            let mut env = FpEnv::default();
+            env.set_handler(ExceptionKind::Invalid, Some(|_| Action::Abort));
+            let x = env.sqrt(-1.0f32);
+            unreachable!("{x}")
+        });
+        assert_eq!(r.unwrap_err().op(), Operation::Sqrt);
+    }
+}
@@ -0,0 +1,351 @@
+use core::fmt::Debug;
+use core::hash::Hash;
+use core::ops::Neg;
+
+use crate::{CielArithmetic, FloorArithmetic, RoundTiesEvenArithmetic, TruncArithmetic};
+use crate::{CielMath, FloorMath, RoundTiesEvenMath, TruncMath};
+use crate::{RoundingArithmetic, RoundingMath, RoundingMode};
+
+/// Floating-point number with every directed op, for width-generic code.
+///
+/// It bundles the arithmetic and math traits of every rounding mode,
+/// the constants and the bit conversions of the primitive,
+/// and the neighbors ([`next_up`], [`next_down`] and [`ulp`]).
+///
+/// ```
+/// use srmfpa::RoundingFloat;
+///
+/// /// Returns the lower and upper bounds of the sum.
+/// fn sum<T: RoundingFloat>(xs: &[T]) -> (T, T) {
+///     xs.iter().fold((T::ZERO, T::ZERO), |(lo, hi), &x| {
+///         (lo.floor_add(x), hi.ciel_add(x))
+///     })
+/// }
+///
+/// let (lo, hi) = sum(&[0.1f64, 0.2, 0.3]);
+/// assert!(lo < hi);
+/// assert_eq!(lo.next_up(), hi);
+///
+/// let (lo, hi) = sum(&[0.1f32, 0.2, 0.3]);
+/// assert!(lo <= hi);
+/// ```
+///
+/// [`next_up`]: RoundingFloat::next_up
+/// [`next_down`]: RoundingFloat::next_down
+/// [`ulp`]: RoundingFloat::ulp
+pub trait RoundingFloat:
+    Copy
+    + Default
+    + Debug
+    + PartialEq
+    + PartialOrd
+    + Neg<Output = Self>
+    + RoundingArithmetic<Output = Self>
+    + RoundTiesEvenArithmetic<Output = Self>
+    + CielArithmetic<Output = Self>
+    + FloorArithmetic<Output = Self>
+    + TruncArithmetic<Output = Self>
+    + RoundingMath<Output = Self>
+    + RoundTiesEvenMath<Output = Self>
+    + CielMath<Output = Self>
+    + FloorMath<Output = Self>
+    + TruncMath<Output = Self>
+    + crate::r#impl::format::BinaryFormat
+{
+    /// The unsigned integer type of the raw transmutation.
+    type Bits: Copy + Default + Debug + Eq + Ord + Hash;
+
+    /// The size of the type in bits.
+    const BITS: u32;
+    /// The number of significant digits in base 2, including the hidden bit.
+    const MANTISSA_DIGITS: u32;
+    /// The difference between `1.0` and the next larger representable number.
+    const EPSILON: Self;
+    /// The smallest positive normal number.
+    const MIN_POSITIVE: Self;
+    /// The smallest positive subnormal number.
+    const MIN_POSITIVE_SUBNORMAL: Self;
+    /// The smallest finite number.
+    const MIN: Self;
+    /// The largest finite number.
+    const MAX: Self;
+    /// `0.0`
+    const ZERO: Self;
+    /// `1.0`
+    const ONE: Self;
+    /// +∞
+    const INFINITY: Self;
+    /// -∞
+    const NEG_INFINITY: Self;
+    /// Not a Number (NaN).
+    const NAN: Self;
+
+    /// Returns the raw transmutation to the unsigned integer.
+    #[must_use]
+    fn to_bits(self) -> Self::Bits;
+    /// Returns the raw transmutation from the unsigned integer.
+    #[must_use]
+    fn from_bits(bits: Self::Bits) -> Self;
+
+    /// Returns the least number greater than `self`.
+    ///
+    /// It returns `self` on NaN and +∞,
+    /// and the smallest positive subnormal number on ±0.
+    #[must_use = "method returns a new number and does not mutate the original value"]
+    fn next_up(self) -> Self;
+    /// Returns the greatest number less than `self`.
+    ///
+    /// It returns `self` on NaN and -∞,
+    /// and the largest negative subnormal number on ±0.
+    #[must_use = "method returns a new number and does not mutate the original value"]
+    #[inline]
+    fn next_down(self) -> Self {
+        -(-self).next_up()
+    }
+    /// Returns the unit in the last place of `self`,
+    /// the distance from `|self|` to the next number away from 0.
+    ///
+    /// It returns the distance to the next number toward 0 on `MAX`,
+    /// and NaN on NaN and ±∞.
+    #[must_use = "method returns a new number and does not mutate the original value"]
+    fn ulp(self) -> Self;
+
+    /// Returns `x` converted with specified rounding mode.
+    ///
+    /// It is exact when `Self` is wider than `f64`.
+    #[must_use]
+    fn round_from_f64(x: f64, mode: &RoundingMode) -> Self;
+    /// Returns `self` converted to `f64` with specified rounding mode.
+    ///
+    /// It is exact when `Self` is narrower than `f64`.
+    #[must_use = "method returns a new number and does not mutate the original value"]
+    fn round_to_f64(self, mode: &RoundingMode) -> f64;
+}
+
+/// Returns `x as $narrow` with `mode`, corrects the nearest one by comparison in `$wide`.
+macro_rules! directed_cast {
+    ($x:expr, $wide:ty => $narrow:ty, $mode:expr) => {{
+        use $crate::{RoundingFloat, RoundingMode};
+
+        let x: $wide = $x;
+        let r = x as $narrow;
+        let back = r as $wide;
+        match $mode {
+            RoundingMode::NearestTiesEven => r,
+            RoundingMode::TowardPosInf if back < x => RoundingFloat::next_up(r),
+            RoundingMode::TowardNegInf if back > x => RoundingFloat::next_down(r),
+            RoundingMode::TowardZero if back > x && x >= 0.0 => RoundingFloat::next_down(r),
+            RoundingMode::TowardZero if back < x && x <= 0.0 => RoundingFloat::next_up(r),
+            _ => r,
+        }
+    }};
+}
+
+pub(crate) use directed_cast;
+
+macro_rules! impl_rounding_float {
+    (
+        $ty:ident, $bits:ty,
+        from_f64: |$x:ident, $from_mode:ident| $from:expr,
+        to_f64: |$y:ident, $to_mode:ident| $to:expr,
+    ) => {
+        impl RoundingFloat for $ty {
+            type Bits = $bits;
+
+            const BITS: u32 = <$bits>::BITS;
+            const MANTISSA_DIGITS: u32 = <$ty>::MANTISSA_DIGITS;
+            const EPSILON: Self = <$ty>::EPSILON;
+            const MIN_POSITIVE: Self = <$ty>::MIN_POSITIVE;
+            const MIN_POSITIVE_SUBNORMAL: Self = <$ty>::from_bits(1);
+            const MIN: Self = <$ty>::MIN;
+            const MAX: Self = <$ty>::MAX;
+            const ZERO: Self = 0.0;
+            const ONE: Self = 1.0;
+            const INFINITY: Self = <$ty>::INFINITY;
+            const NEG_INFINITY: Self = <$ty>::NEG_INFINITY;
+            const NAN: Self = <$ty>::NAN;
+
+            #[inline]
+            fn to_bits(self) -> $bits {
+                <$ty>::to_bits(self)
+            }
+
+            #[inline]
+            fn from_bits(bits: $bits) -> Self {
+                <$ty>::from_bits(bits)
+            }
+
+            #[inline]
+            fn next_up(self) -> Self {
+                let bits = <$ty>::to_bits(self);
+                let abs = bits & (<$bits>::MAX >> 1);
+
+                if self.is_nan() || self == <$ty>::INFINITY {
+                    self
+                } else if abs == 0 {
+                    <$ty>::from_bits(1)
+                } else if bits == abs {
+                    <$ty>::from_bits(bits + 1)
+                } else {
+                    <$ty>::from_bits(bits - 1)
+                }
+            }
+
+            #[inline]
+            fn ulp(self) -> Self {
+                let abs = <$ty>::from_bits(<$ty>::to_bits(self) & (<$bits>::MAX >> 1));
+
+                if self.is_nan() || abs == <$ty>::INFINITY {
+                    <$ty>::NAN
+                } else if abs == <$ty>::MAX {
+                    // adjacent numbers, the difference is exact
+                    abs - RoundingFloat::next_down(abs)
+                } else {
+                    RoundingFloat::next_up(abs) - abs
+                }
+            }
+
+            #[inline]
+            fn round_from_f64($x: f64, $from_mode: &RoundingMode) -> Self {
+                $from
+            }
+
+            #[inline]
+            fn round_to_f64(self, $to_mode: &RoundingMode) -> f64 {
+                let $y = self;
+                $to
+            }
+        }
+    };
+}
+
+#[cfg(any(feature = "f16", feature = "f16_softfloat", feature = "f16_widening"))]
+impl_rounding_float!(
+    f16, u16,
+    from_f64: |x, mode| directed_cast!(x, f64 => f16, mode),
+    to_f64: |x, _mode| x as f64,
+);
+impl_rounding_float!(
+    f32, u32,
+    from_f64: |x, mode| directed_cast!(x, f64 => f32, mode),
+    to_f64: |x, _mode| x as f64,
+);
+impl_rounding_float!(
+    f64, u64,
+    from_f64: |x, _mode| x,
+    to_f64: |x, _mode| x,
+);
+#[cfg(any(feature = "f128", feature = "f128_softfloat"))]
+impl_rounding_float!(
+    f128, u128,
+    from_f64: |x, _mode| x as f128,
+    to_f64: |x, mode| directed_cast!(x, f128 => f64, mode),
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sum<T: RoundingFloat>(xs: &[T]) -> (T, T) {
+        xs.iter().fold((T::ZERO, T::ZERO), |(lo, hi), &x| {
+            (lo.floor_add(x), hi.ciel_add(x))
+        })
+    }
+
+    fn neighbors<T: RoundingFloat>() {
+        assert_eq!(T::ZERO.next_up(), T::MIN_POSITIVE_SUBNORMAL);
+        assert_eq!((-T::ZERO).next_up(), T::MIN_POSITIVE_SUBNORMAL);
+        assert_eq!(T::ZERO.next_down(), -T::MIN_POSITIVE_SUBNORMAL);
+        assert_eq!(
+            T::ONE.next_up(),
+            T::ONE.round_add(T::EPSILON, &RoundingMode::TowardZero)
+        );
+        assert_eq!(T::MAX.next_up(), T::INFINITY);
+        assert_eq!(T::INFINITY.next_up(), T::INFINITY);
+        assert_eq!(T::NEG_INFINITY.next_up(), T::MIN);
+        assert_eq!(T::MIN.next_down(), T::NEG_INFINITY);
+        let nan = T::NAN.next_up();
+        assert!(nan.partial_cmp(&nan).is_none());
+
+        assert_eq!(T::ONE.ulp(), T::EPSILON);
+        assert_eq!((-T::ONE).ulp(), T::EPSILON);
+        assert_eq!(T::ZERO.ulp(), T::MIN_POSITIVE_SUBNORMAL);
+        assert_eq!(
+            T::MAX.ulp(),
+            T::MAX.round_sub(T::MAX.next_down(), &RoundingMode::TowardZero)
+        );
+        let nan = T::INFINITY.ulp();
+        assert!(nan.partial_cmp(&nan).is_none());
+
+        assert_eq!(T::from_bits(T::ONE.to_bits()), T::ONE);
+        assert_eq!(T::from_bits(T::Bits::default()), T::ZERO);
+    }
+
+    #[test]
+    fn test_sum() {
+        let (lo, hi) = sum(&[0.1f64, 0.2, 0.3]);
+        assert_eq!(lo, 0.6);
+        assert_eq!(hi, 0.6000000000000001);
+
+        let (lo, hi) = sum(&[0.1f32, 0.2, 0.3]);
+        assert_eq!(lo, 0.59999996);
+        assert_eq!(hi, 0.6);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        neighbors::<f32>();
+        neighbors::<f64>();
+
+        for x in [1.0f64, -1.0, 0.1, -0.0, 1e-310, f64::MAX, f64::MIN] {
+            assert_eq!(RoundingFloat::next_up(x).to_bits(), x.next_up().to_bits());
+            assert_eq!(
+                RoundingFloat::next_down(x).to_bits(),
+                x.next_down().to_bits()
+            );
+        }
+        for x in [1.0f32, -1.0, 0.1, 0.0, 1e-40, f32::MAX, f32::MIN] {
+            assert_eq!(RoundingFloat::next_up(x).to_bits(), x.next_up().to_bits());
+            assert_eq!(
+                RoundingFloat::next_down(x).to_bits(),
+                x.next_down().to_bits()
+            );
+        }
+    }
+
+    #[test]
+    fn test_convert() {
+        use RoundingMode::*;
+
+        let x = 0.1f64;
+        assert_eq!(f32::round_from_f64(x, &NearestTiesEven), 0.1f32);
+        assert_eq!(f32::round_from_f64(x, &TowardPosInf), 0.1f32);
+        assert_eq!(f32::round_from_f64(x, &TowardNegInf), 0.099999994);
+        assert_eq!(f32::round_from_f64(x, &TowardZero), 0.099999994);
+        assert_eq!(f32::round_from_f64(-x, &TowardPosInf), -0.099999994);
+        assert_eq!(f32::round_from_f64(-x, &TowardNegInf), -0.1f32);
+        assert_eq!(f32::round_from_f64(-x, &TowardZero), -0.099999994);
+
+        // exact
+        assert_eq!(f32::round_from_f64(0.5, &TowardPosInf), 0.5);
+        assert_eq!(f32::round_from_f64(0.5, &TowardNegInf), 0.5);
+
+        // overflow and underflow
+        assert_eq!(f32::round_from_f64(1e300, &TowardZero), f32::MAX);
+        assert_eq!(f32::round_from_f64(1e300, &TowardPosInf), f32::INFINITY);
+        assert_eq!(f32::round_from_f64(-1e300, &TowardPosInf), f32::MIN);
+        assert_eq!(
+            f32::round_from_f64(1e-300, &TowardPosInf),
+            f32::from_bits(1)
+        );
+        assert_eq!(
+            f32::round_from_f64(-1e-300, &TowardNegInf),
+            -f32::from_bits(1)
+        );
+        assert_eq!(f32::round_from_f64(1e-300, &TowardNegInf), 0.0);
+
+        assert!(f32::round_from_f64(f64::NAN, &TowardZero).is_nan());
+        assert_eq!(0.1f32.round_to_f64(&TowardNegInf), 0.1f32 as f64);
+        assert_eq!(f64::round_from_f64(x, &TowardNegInf), x);
+    }
+}
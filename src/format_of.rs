@@ -0,0 +1,354 @@
+use crate::float::directed_cast;
+use crate::RoundingMode;
+
+/// Provides IEEE 754 formatOf ops, whose operands are `T` and result is `Self`,
+/// see `round_add_to` etc. of each module (e.g. [`f64::round_add_to`][crate::f64::round_add_to]).
+///
+/// The result is rounded once with specified rounding mode, even if `Self` is narrower than `T`.
+pub trait FormatOf<T>: crate::sealed::Sealed {
+    /// Returns `a + b` rounded once into `Self` with specified rounding mode.
+    ///
+    /// # Safety
+    ///
+    /// Panics when fail to set/restore rounding mode.
+    fn round_add_from(a: T, b: T, mode: &RoundingMode) -> Self;
+    /// Returns `a - b` rounded once into `Self` with specified rounding mode.
+    ///
+    /// # Safety
+    ///
+    /// Panics when fail to set/restore rounding mode.
+    fn round_sub_from(a: T, b: T, mode: &RoundingMode) -> Self;
+    /// Returns `a * b` rounded once into `Self` with specified rounding mode.
+    ///
+    /// # Safety
+    ///
+    /// Panics when fail to set/restore rounding mode.
+    fn round_mul_from(a: T, b: T, mode: &RoundingMode) -> Self;
+    /// Returns `a / b` rounded once into `Self` with specified rounding mode.
+    ///
+    /// # Safety
+    ///
+    /// Panics when fail to set/restore rounding mode.
+    fn round_div_from(a: T, b: T, mode: &RoundingMode) -> Self;
+    /// Returns `a * b + c` rounded once into `Self` with specified rounding mode.
+    ///
+    /// # Safety
+    ///
+    /// Panics when fail to set/restore rounding mode.
+    fn round_mul_add_from(a: T, b: T, c: T, mode: &RoundingMode) -> Self;
+    /// Returns `a.sqrt()` rounded once into `Self` with specified rounding mode.
+    ///
+    /// # Safety
+    ///
+    /// Panics when fail to set/restore rounding mode.
+    fn round_sqrt_from(a: T, mode: &RoundingMode) -> Self;
+}
+
+macro_rules! impl_format_of {
+    // the same format, the ops of the type
+    ($(#[$attr:meta])* same: $ty:ident) => {
+        $(#[$attr])*
+        impl FormatOf<$ty> for $ty {
+            #[inline]
+            fn round_add_from(a: $ty, b: $ty, mode: &RoundingMode) -> Self {
+                crate::r#impl::$ty::round_add(a, b, mode)
+            }
+
+            #[inline]
+            fn round_sub_from(a: $ty, b: $ty, mode: &RoundingMode) -> Self {
+                crate::r#impl::$ty::round_sub(a, b, mode)
+            }
+
+            #[inline]
+            fn round_mul_from(a: $ty, b: $ty, mode: &RoundingMode) -> Self {
+                crate::r#impl::$ty::round_mul(a, b, mode)
+            }
+
+            #[inline]
+            fn round_div_from(a: $ty, b: $ty, mode: &RoundingMode) -> Self {
+                crate::r#impl::$ty::round_div(a, b, mode)
+            }
+
+            #[inline]
+            fn round_mul_add_from(a: $ty, b: $ty, c: $ty, mode: &RoundingMode) -> Self {
+                crate::r#impl::$ty::round_mul_add(a, b, c, mode)
+            }
+
+            #[inline]
+            fn round_sqrt_from(a: $ty, mode: &RoundingMode) -> Self {
+                crate::r#impl::$ty::round_sqrt(a, mode)
+            }
+        }
+    };
+    // to a wider format, the operands are converted exactly
+    ($(#[$attr:meta])* wide: $src:ident => $dst:ident) => {
+        $(#[$attr])*
+        impl FormatOf<$src> for $dst {
+            #[inline]
+            fn round_add_from(a: $src, b: $src, mode: &RoundingMode) -> Self {
+                crate::r#impl::$dst::round_add(a as $dst, b as $dst, mode)
+            }
+
+            #[inline]
+            fn round_sub_from(a: $src, b: $src, mode: &RoundingMode) -> Self {
+                crate::r#impl::$dst::round_sub(a as $dst, b as $dst, mode)
+            }
+
+            #[inline]
+            fn round_mul_from(a: $src, b: $src, mode: &RoundingMode) -> Self {
+                crate::r#impl::$dst::round_mul(a as $dst, b as $dst, mode)
+            }
+
+            #[inline]
+            fn round_div_from(a: $src, b: $src, mode: &RoundingMode) -> Self {
+                crate::r#impl::$dst::round_div(a as $dst, b as $dst, mode)
+            }
+
+            #[inline]
+            fn round_mul_add_from(a: $src, b: $src, c: $src, mode: &RoundingMode) -> Self {
+                crate::r#impl::$dst::round_mul_add(a as $dst, b as $dst, c as $dst, mode)
+            }
+
+            #[inline]
+            fn round_sqrt_from(a: $src, mode: &RoundingMode) -> Self {
+                crate::r#impl::$dst::round_sqrt(a as $dst, mode)
+            }
+        }
+    };
+    // to a narrower format, rounds to odd in `$src` and then rounds into `$dst`,
+    // it is single rounding since `$src` has at least 2 more bits of precision than `$dst`
+    ($(#[$attr:meta])* narrow: $src:ident => $dst:ident) => {
+        $(#[$attr])*
+        impl FormatOf<$src> for $dst {
+            #[inline]
+            fn round_add_from(a: $src, b: $src, mode: &RoundingMode) -> Self {
+                use crate::r#impl::$src::{round_add, round_add_bounds};
+                let x = impl_format_of!(@odd round_add_bounds(a, b), round_add(a, b, mode));
+                directed_cast!(x, $src => $dst, mode)
+            }
+
+            #[inline]
+            fn round_sub_from(a: $src, b: $src, mode: &RoundingMode) -> Self {
+                use crate::r#impl::$src::{round_sub, round_sub_bounds};
+                let x = impl_format_of!(@odd round_sub_bounds(a, b), round_sub(a, b, mode));
+                directed_cast!(x, $src => $dst, mode)
+            }
+
+            #[inline]
+            fn round_mul_from(a: $src, b: $src, mode: &RoundingMode) -> Self {
+                use crate::r#impl::$src::{round_mul, round_mul_bounds};
+                let x = impl_format_of!(@odd round_mul_bounds(a, b), round_mul(a, b, mode));
+                directed_cast!(x, $src => $dst, mode)
+            }
+
+            #[inline]
+            fn round_div_from(a: $src, b: $src, mode: &RoundingMode) -> Self {
+                use crate::r#impl::$src::{round_div, round_div_bounds};
+                let x = impl_format_of!(@odd round_div_bounds(a, b), round_div(a, b, mode));
+                directed_cast!(x, $src => $dst, mode)
+            }
+
+            #[inline]
+            fn round_mul_add_from(a: $src, b: $src, c: $src, mode: &RoundingMode) -> Self {
+                use crate::r#impl::$src::{round_mul_add, round_mul_add_bounds};
+                let x = impl_format_of!(
+                    @odd round_mul_add_bounds(a, b, c), round_mul_add(a, b, c, mode)
+                );
+                directed_cast!(x, $src => $dst, mode)
+            }
+
+            #[inline]
+            fn round_sqrt_from(a: $src, mode: &RoundingMode) -> Self {
+                use crate::r#impl::$src::{round_sqrt, round_sqrt_bounds};
+                let x = impl_format_of!(@odd round_sqrt_bounds(a), round_sqrt(a, mode));
+                directed_cast!(x, $src => $dst, mode)
+            }
+        }
+    };
+    // the result rounded to odd, from the bounds, or the exact result if they are identical
+    (@odd $bounds:expr, $exact:expr) => {{
+        let (lo, hi) = $bounds;
+        if lo == hi {
+            // takes the sign of exact zero from the op
+            if lo == 0.0 {
+                $exact
+            } else {
+                lo
+            }
+        } else if lo.to_bits() & 1 == 1 {
+            lo
+        } else {
+            hi
+        }
+    }};
+}
+
+impl_format_of!(same: f32);
+impl_format_of!(same: f64);
+impl_format_of!(wide: f32 => f64);
+impl_format_of!(narrow: f64 => f32);
+
+impl_format_of!(
+    #[cfg(any(feature = "f16", feature = "f16_softfloat", feature = "f16_widening"))]
+    same: f16
+);
+impl_format_of!(
+    #[cfg(any(feature = "f16", feature = "f16_softfloat", feature = "f16_widening"))]
+    wide: f16 => f32
+);
+impl_format_of!(
+    #[cfg(any(feature = "f16", feature = "f16_softfloat", feature = "f16_widening"))]
+    wide: f16 => f64
+);
+impl_format_of!(
+    #[cfg(any(feature = "f16", feature = "f16_softfloat", feature = "f16_widening"))]
+    narrow: f32 => f16
+);
+impl_format_of!(
+    #[cfg(any(feature = "f16", feature = "f16_softfloat", feature = "f16_widening"))]
+    narrow: f64 => f16
+);
+
+impl_format_of!(
+    #[cfg(any(feature = "f128", feature = "f128_softfloat"))]
+    same: f128
+);
+impl_format_of!(
+    #[cfg(any(feature = "f128", feature = "f128_softfloat"))]
+    wide: f32 => f128
+);
+impl_format_of!(
+    #[cfg(any(feature = "f128", feature = "f128_softfloat"))]
+    wide: f64 => f128
+);
+impl_format_of!(
+    #[cfg(any(feature = "f128", feature = "f128_softfloat"))]
+    narrow: f128 => f32
+);
+impl_format_of!(
+    #[cfg(any(feature = "f128", feature = "f128_softfloat"))]
+    narrow: f128 => f64
+);
+
+impl_format_of!(
+    #[cfg(all(
+        any(feature = "f16", feature = "f16_softfloat", feature = "f16_widening"),
+        any(feature = "f128", feature = "f128_softfloat")
+    ))]
+    wide: f16 => f128
+);
+impl_format_of!(
+    #[cfg(all(
+        any(feature = "f16", feature = "f16_softfloat", feature = "f16_widening"),
+        any(feature = "f128", feature = "f128_softfloat")
+    ))]
+    narrow: f128 => f16
+);
+
+#[cfg(test)]
+mod test {
+    use crate::f64::*;
+    use crate::RoundingMode::*;
+    use crate::{RoundingArithmetic, RoundingMath, RoundingMode};
+
+    const MODES: [RoundingMode; 4] = [NearestTiesEven, TowardPosInf, TowardNegInf, TowardZero];
+
+    #[test]
+    fn test_double_rounding() {
+        // 1 + 2^-24 + 2^-60, rounding to f64 and then to f32 is 1
+        let (a, b) = (1.0 + 2f64.powi(-24), 2f64.powi(-60));
+        assert_eq!((a + b) as f32, 1.0);
+
+        let up = 1.0 + f32::EPSILON;
+        assert_eq!(round_add_to::<f32>(a, b, &NearestTiesEven), up);
+        assert_eq!(round_add_to::<f32>(a, b, &TowardPosInf), up);
+        assert_eq!(round_add_to::<f32>(a, b, &TowardNegInf), 1.0);
+        assert_eq!(round_add_to::<f32>(a, b, &TowardZero), 1.0);
+        assert_eq!(round_sub_to::<f32>(-a, b, &NearestTiesEven), -up);
+        assert_eq!(round_sub_to::<f32>(-a, b, &TowardPosInf), -1.0);
+
+        // (1 + 2^-30)^2 = 1 + 2^-29 + 2^-60
+        let a = 1.0 + 2f64.powi(-30);
+        assert_eq!(round_mul_to::<f32>(a, a, &TowardPosInf), up);
+        assert_eq!(round_mul_to::<f32>(a, a, &TowardZero), 1.0);
+        assert_eq!(
+            round_mul_add_to::<f32>(a, a, -1.0, &TowardZero),
+            2f32.powi(-29)
+        );
+
+        // the same format
+        assert_eq!(round_add_to::<f64>(0.1, 0.2, &TowardNegInf), 0.3);
+        assert_eq!(
+            round_add_to::<f64>(0.1, 0.2, &TowardPosInf),
+            0.30000000000000004
+        );
+    }
+
+    #[test]
+    fn test_special() {
+        for mode in &MODES {
+            assert!(round_add_to::<f32>(f64::NAN, 1.0, mode).is_nan());
+            assert!(round_sub_to::<f32>(f64::INFINITY, f64::INFINITY, mode).is_nan());
+            assert!(round_sqrt_to::<f32>(-1.0, mode).is_nan());
+            assert_eq!(
+                round_mul_to::<f32>(f64::INFINITY, -1.0, mode),
+                f32::NEG_INFINITY
+            );
+            assert_eq!(round_div_to::<f32>(1.0, 0.0, mode), f32::INFINITY);
+        }
+
+        // the sign of exact zero
+        let zero = round_sub_to::<f32>(1.0, 1.0, &TowardNegInf);
+        assert!(zero == 0.0 && zero.is_sign_negative());
+        let zero = round_sub_to::<f32>(1.0, 1.0, &TowardPosInf);
+        assert!(zero == 0.0 && zero.is_sign_positive());
+
+        // overflow and underflow
+        assert_eq!(round_mul_to::<f32>(1e30, 1e30, &TowardZero), f32::MAX);
+        assert_eq!(
+            round_mul_to::<f32>(1e30, 1e30, &NearestTiesEven),
+            f32::INFINITY
+        );
+        assert_eq!(
+            round_mul_to::<f32>(1e-30, 1e-30, &TowardPosInf),
+            f32::from_bits(1)
+        );
+        assert_eq!(round_mul_to::<f32>(1e-30, -1e-30, &TowardPosInf), -0.0);
+    }
+
+    #[test]
+    fn test_f32_operands() {
+        // formatOf f64 ops on f32 operands are the f32 ops
+        let mut state = 0x2545f4914f6cdd1du64;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            f32::from_bits((state >> 32) as u32)
+        };
+
+        for _ in 0..10000 {
+            let (a, b, c) = (next(), next(), next());
+            let (x, y, z) = (a as f64, b as f64, c as f64);
+
+            for mode in &MODES {
+                let same = |r: f32, e: f32| r.to_bits() == e.to_bits() || r.is_nan() && e.is_nan();
+                assert!(same(round_add_to(x, y, mode), a.round_add(b, mode)));
+                assert!(same(round_sub_to(x, y, mode), a.round_sub(b, mode)));
+                assert!(same(round_mul_to(x, y, mode), a.round_mul(b, mode)));
+                assert!(same(round_div_to(x, y, mode), a.round_div(b, mode)));
+                assert!(same(
+                    round_mul_add_to(x, y, z, mode),
+                    a.round_mul_add(b, c, mode)
+                ));
+                assert!(same(round_sqrt_to(x, mode), a.round_sqrt(mode)));
+            }
+
+            // to a wider format
+            assert_eq!(
+                crate::f32::round_mul_to::<f64>(a, b, &TowardZero).to_bits(),
+                (x * y).to_bits()
+            );
+        }
+    }
+}
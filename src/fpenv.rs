@@ -0,0 +1,820 @@
+use core::fmt::{Display, Formatter};
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not};
+
+use core::panic::Location;
+
+use crate::exception::{self, Handlers};
+use crate::r#impl::arith;
+use crate::r#impl::format::BinaryFormat;
+use crate::{ExceptionHandler, ExceptionKind, Operation, RoundingFloat, RoundingMode};
+
+/// IEEE 754 exception (status) flags.
+///
+/// ```
+/// use srmfpa::ExceptionFlags;
+///
+/// let flags = ExceptionFlags::INEXACT | ExceptionFlags::UNDERFLOW;
+/// assert!(flags.contains(ExceptionFlags::INEXACT));
+/// assert!(!flags.contains(ExceptionFlags::INVALID));
+/// assert_eq!(flags.to_string(), "underflow | inexact");
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ExceptionFlags(u8);
+
+impl ExceptionFlags {
+    /// Invalid operation, e.g. `0 / 0`, `inf - inf` and `(-1).sqrt()`,
+    /// and an operation on signaling NaN.
+    pub const INVALID: Self = Self(1);
+    /// Division by zero of finite non-zero number.
+    pub const DIV_BY_ZERO: Self = Self(1 << 1);
+    /// The rounded result exceeds the largest finite number.
+    pub const OVERFLOW: Self = Self(1 << 2);
+    /// The result is tiny (less than the smallest normal number in magnitude) and inexact.
+    pub const UNDERFLOW: Self = Self(1 << 3);
+    /// The rounded result differs from the exact result.
+    pub const INEXACT: Self = Self(1 << 4);
+
+    /// Returns no flags.
+    #[must_use]
+    #[inline]
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Returns all flags.
+    #[must_use]
+    #[inline]
+    pub const fn all() -> Self {
+        Self(0b11111)
+    }
+
+    /// Returns `true` if no flag is raised.
+    #[must_use]
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns `true` if all flags of `other` are raised.
+    #[must_use]
+    #[inline]
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if some flag of `other` is raised.
+    #[must_use]
+    #[inline]
+    pub const fn intersects(&self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Returns the flags raised in `self` or `other`.
+    #[must_use]
+    #[inline]
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Raises the flags of `other`.
+    #[inline]
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    /// Lowers the flags of `other`.
+    #[inline]
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+}
+
+impl BitOr for ExceptionFlags {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self {
+        self.union(rhs)
+    }
+}
+
+impl BitOrAssign for ExceptionFlags {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.insert(rhs)
+    }
+}
+
+impl BitAnd for ExceptionFlags {
+    type Output = Self;
+
+    #[inline]
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl BitAndAssign for ExceptionFlags {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.0 &= rhs.0
+    }
+}
+
+impl Not for ExceptionFlags {
+    type Output = Self;
+
+    #[inline]
+    fn not(self) -> Self {
+        Self(!self.0 & Self::all().0)
+    }
+}
+
+impl Display for ExceptionFlags {
+    /// Writes the names of the raised flags separated by ` | `, or `none`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("none");
+        }
+
+        let mut first = true;
+        for (flag, name) in [
+            (Self::INVALID, "invalid"),
+            (Self::DIV_BY_ZERO, "division by zero"),
+            (Self::OVERFLOW, "overflow"),
+            (Self::UNDERFLOW, "underflow"),
+            (Self::INEXACT, "inexact"),
+        ] {
+            if self.contains(flag) {
+                if !first {
+                    f.write_str(" | ")?;
+                }
+                f.write_str(name)?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Tininess detection of underflow.
+///
+/// IEEE 754 allows to detect a nonzero result is tiny (less than `MIN_POSITIVE` in magnitude)
+/// either before or after rounding, which differs between platforms.
+/// It affects only whether underflow is signaled, the rounded result is the same.
+///
+/// The default is the one of the target architecture,
+/// before rounding on ARM, after rounding otherwise (e.g. x86 SSE and RISC-V).
+///
+/// ```
+/// use srmfpa::{ExceptionFlags, FpEnv, Tininess};
+///
+/// // 2^-1022 * (1 + 2^-52) * (1 - 2^-52) = 2^-1022 - 2^-1126
+/// let x = f64::from_bits(f64::MIN_POSITIVE.to_bits() + 1);
+/// let y = 1.0 - f64::EPSILON;
+///
+/// let mut env = FpEnv::default();
+///
+/// env.set_tininess(Tininess::AfterRounding);
+/// assert_eq!(env.mul(x, y), f64::MIN_POSITIVE);
+/// assert_eq!(env.take_flags(), ExceptionFlags::INEXACT);
+///
+/// env.set_tininess(Tininess::BeforeRounding);
+/// assert_eq!(env.mul(x, y), f64::MIN_POSITIVE);
+/// assert_eq!(env.take_flags(), ExceptionFlags::UNDERFLOW | ExceptionFlags::INEXACT);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Tininess {
+    /// Tiny if the exact result is less than `MIN_POSITIVE` in magnitude (e.g. ARM).
+    BeforeRounding,
+    /// Tiny if the result rounded to the precision with unbounded exponent
+    /// is less than `MIN_POSITIVE` in magnitude (e.g. x86 SSE and RISC-V).
+    AfterRounding,
+}
+
+impl Tininess {
+    /// The tininess detection of the target architecture.
+    pub const NATIVE: Self = if cfg!(any(target_arch = "arm", target_arch = "aarch64")) {
+        Self::BeforeRounding
+    } else {
+        Self::AfterRounding
+    };
+}
+
+impl Default for Tininess {
+    /// Returns [`Tininess::NATIVE`].
+    #[inline]
+    fn default() -> Self {
+        Self::NATIVE
+    }
+}
+
+/// NaN results of the ops, that is, the default NaN of invalid operations
+/// and which NaN operand propagates, which differ between platforms.
+///
+/// The sign and the payload of NaN are not specified by IEEE 754,
+/// choosing one gives identical bits on every platform.
+/// The default is the one of the target architecture.
+///
+/// ```
+/// use srmfpa::{FpEnv, NanSemantics};
+///
+/// let mut env = FpEnv::default();
+///
+/// env.set_nan_semantics(NanSemantics::X86);
+/// assert_eq!(env.sub(f64::INFINITY, f64::INFINITY).to_bits(), 0xFFF8_0000_0000_0000);
+///
+/// env.set_nan_semantics(NanSemantics::RiscVCanonical);
+/// assert_eq!(env.sub(f64::INFINITY, f64::INFINITY).to_bits(), 0x7FF8_0000_0000_0000);
+///
+/// let nan = f64::from_bits(0x7FF8_0000_0000_0001);
+///
+/// env.set_nan_semantics(NanSemantics::PreservePayload);
+/// assert_eq!(env.add(1.0, nan).to_bits(), 0x7FF8_0000_0000_0001);
+///
+/// env.set_nan_semantics(NanSemantics::ArmDefaultNan);
+/// assert_eq!(env.add(1.0, nan).to_bits(), 0x7FF8_0000_0000_0000);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum NanSemantics {
+    /// x86 SSE: the default NaN is negative quiet NaN without payload,
+    /// and the first NaN operand propagates quieted.
+    X86,
+    /// ARM with default NaN mode (`FPCR.DN`): every NaN result is positive quiet NaN without payload.
+    ArmDefaultNan,
+    /// RISC-V: every NaN result is the canonical NaN, positive quiet NaN without payload.
+    RiscVCanonical,
+    /// ARM without default NaN mode, as IEEE 754 recommends:
+    /// the first signaling NaN operand, or the first quiet NaN operand if none, propagates quieted,
+    /// and the default NaN is positive quiet NaN without payload.
+    PreservePayload,
+}
+
+impl NanSemantics {
+    /// All semantics.
+    pub const ALL: [Self; 4] = [
+        Self::X86,
+        Self::ArmDefaultNan,
+        Self::RiscVCanonical,
+        Self::PreservePayload,
+    ];
+
+    /// The NaN semantics of the target architecture.
+    pub const NATIVE: Self = if cfg!(any(target_arch = "x86", target_arch = "x86_64")) {
+        Self::X86
+    } else if cfg!(any(target_arch = "arm", target_arch = "aarch64")) {
+        Self::PreservePayload
+    } else {
+        Self::RiscVCanonical
+    };
+}
+
+impl Default for NanSemantics {
+    /// Returns [`NanSemantics::NATIVE`].
+    #[inline]
+    fn default() -> Self {
+        Self::NATIVE
+    }
+}
+
+/// Non-IEEE handling of subnormal numbers, flush-to-zero and denormals-are-zero,
+/// as DSPs, GPUs and SIMD units with the modes (e.g. MXCSR of x86) do.
+///
+/// The default is IEEE 754 gradual underflow, both are `false`.
+///
+/// ```
+/// use srmfpa::{DenormalMode, ExceptionFlags, FpEnv, RoundingMode};
+///
+/// let tiny = f64::MIN_POSITIVE / 4.0;
+///
+/// let mut env = FpEnv::new(RoundingMode::TowardPosInf);
+/// env.set_denormal_mode(DenormalMode::FTZ_DAZ);
+///
+/// // flush outputs
+/// assert_eq!(env.mul(f64::MIN_POSITIVE, 0.5), 0.0);
+/// assert_eq!(env.take_flags(), ExceptionFlags::UNDERFLOW | ExceptionFlags::INEXACT);
+/// // treat inputs as zero
+/// assert_eq!(env.add(tiny, 1.0), 1.0);
+/// // the others are rounded with the mode
+/// assert_eq!(env.add(0.1, 0.2), 0.30000000000000004);
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct DenormalMode {
+    /// Flush-to-zero (FTZ), replaces subnormal results with zero of the same sign
+    /// regardless of the rounding mode, and raises underflow and inexact.
+    pub flush_to_zero: bool,
+    /// Denormals-are-zero (DAZ), treats subnormal operands as zero of the same sign.
+    pub denormals_are_zero: bool,
+}
+
+impl DenormalMode {
+    /// IEEE 754 gradual underflow, neither FTZ nor DAZ.
+    pub const IEEE: Self = Self {
+        flush_to_zero: false,
+        denormals_are_zero: false,
+    };
+    /// Flush-to-zero only.
+    pub const FTZ: Self = Self {
+        flush_to_zero: true,
+        denormals_are_zero: false,
+    };
+    /// Denormals-are-zero only.
+    pub const DAZ: Self = Self {
+        flush_to_zero: false,
+        denormals_are_zero: true,
+    };
+    /// Both flush-to-zero and denormals-are-zero.
+    pub const FTZ_DAZ: Self = Self {
+        flush_to_zero: true,
+        denormals_are_zero: true,
+    };
+
+    /// Returns the operand treated by denormals-are-zero.
+    #[inline]
+    pub(crate) fn input<T: BinaryFormat>(&self, x: T) -> T {
+        let bits = x.to_raw();
+        if self.denormals_are_zero && T::FORMAT.is_subnormal(bits) {
+            T::from_raw(bits & T::FORMAT.sign_mask())
+        } else {
+            x
+        }
+    }
+
+    /// Returns the result flushed by flush-to-zero, and raises flags.
+    #[inline]
+    pub(crate) fn output<T: BinaryFormat>(&self, r: T, flags: &mut ExceptionFlags) -> T {
+        let bits = r.to_raw();
+        if self.flush_to_zero && T::FORMAT.is_subnormal(bits) {
+            flags.insert(ExceptionFlags::UNDERFLOW | ExceptionFlags::INEXACT);
+            T::from_raw(bits & T::FORMAT.sign_mask())
+        } else {
+            r
+        }
+    }
+}
+
+/// Floating-point number supported by the ops of [`FpEnv`], `f16`, `f32` and `f64`.
+///
+/// It is sealed, the ops are evaluated by integer ops for the precision up to 53 bits.
+///
+/// ```
+/// use srmfpa::{EnvFloat, FpEnv};
+///
+/// fn sum<T: EnvFloat>(env: &mut FpEnv, xs: &[T]) -> T {
+///     xs.iter().fold(T::ZERO, |acc, &x| env.add(acc, x))
+/// }
+///
+/// let mut env = FpEnv::default();
+/// assert_eq!(sum(&mut env, &[0.5f32, 0.25]), 0.75);
+/// assert!(env.flags().is_empty());
+/// ```
+pub trait EnvFloat: RoundingFloat {}
+
+#[cfg(any(feature = "f16", feature = "f16_softfloat", feature = "f16_widening"))]
+impl EnvFloat for f16 {}
+impl EnvFloat for f32 {}
+impl EnvFloat for f64 {}
+
+/// Floating-point environment, the rounding mode and the sticky exception flags,
+/// as the status-flag model of IEEE 754.
+///
+/// The ops (e.g. [`FpEnv::add`]) round with the mode of the environment,
+/// and raise the exception flags to it, which are kept until they are cleared.
+/// They are evaluated by integer ops (as `const_round_add` etc.),
+/// thus they do not depend on neither `<fenv.h>` nor the backend,
+/// and support `f16`, `f32` and `f64`.
+///
+/// Underflow is detected by the [`Tininess`] of the environment,
+/// NaN results follow the [`NanSemantics`] of it,
+/// and subnormal numbers are handled by the [`DenormalMode`] of it.
+///
+/// The exceptions are handled by the handlers of the environment or of the thread,
+/// see [`FpEnv::set_handler`] and [`set_exception_handler`][crate::set_exception_handler].
+///
+/// ```
+/// use srmfpa::{ExceptionFlags, FpEnv, RoundingMode};
+///
+/// let mut env = FpEnv::new(RoundingMode::TowardPosInf);
+///
+/// assert_eq!(env.add(0.5, 0.25), 0.75);
+/// assert!(env.flags().is_empty());
+///
+/// assert_eq!(env.add(0.1, 0.2), 0.30000000000000004);
+/// assert_eq!(env.mul(0.5, 0.5), 0.25);
+/// // inexact happened in the computation
+/// assert_eq!(env.flags(), ExceptionFlags::INEXACT);
+///
+/// env.clear_flags();
+/// assert!(env.div(1.0f32, 0.0).is_infinite());
+/// assert_eq!(env.flags(), ExceptionFlags::DIV_BY_ZERO);
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct FpEnv {
+    pub(crate) mode: RoundingMode,
+    pub(crate) flags: ExceptionFlags,
+    pub(crate) tininess: Tininess,
+    pub(crate) nan: NanSemantics,
+    denormal: DenormalMode,
+    handlers: Handlers,
+    /// Uniform random bits, rounds stochastically instead of the mode if it is `Some`.
+    pub(crate) random: Option<u64>,
+}
+
+impl Default for FpEnv {
+    /// Returns the environment rounding to nearest, ties to even, without flags.
+    #[inline]
+    fn default() -> Self {
+        Self::new(RoundingMode::NearestTiesEven)
+    }
+}
+
+macro_rules! impl_env_op {
+    ($(#[$attr:meta])* $name:ident, $func:ident, $op:ident, ($($arg:ident),*)) => {
+        $(#[$attr])*
+        #[must_use = "method returns a new number and does not mutate the original value"]
+        #[track_caller]
+        #[inline]
+        pub fn $name<T: EnvFloat>(&mut self, $($arg: T),*) -> T {
+            let mut env = Self {
+                flags: ExceptionFlags::empty(),
+                ..*self
+            };
+            $(let $arg = self.denormal.input($arg);)*
+            let r = T::from_raw(arith::$func(&T::FORMAT, $($arg.to_raw(),)* &mut env));
+            let r = self.denormal.output(r, &mut env.flags);
+            self.finish(r, env.flags, Operation::$op, Location::caller())
+        }
+    };
+}
+
+impl FpEnv {
+    /// Returns the environment with the rounding mode, [`Tininess::NATIVE`], [`NanSemantics::NATIVE`]
+    /// and [`DenormalMode::IEEE`], without flags.
+    #[must_use]
+    #[inline]
+    pub const fn new(mode: RoundingMode) -> Self {
+        Self {
+            mode,
+            flags: ExceptionFlags::empty(),
+            tininess: Tininess::NATIVE,
+            nan: NanSemantics::NATIVE,
+            denormal: DenormalMode::IEEE,
+            handlers: Handlers::new(),
+            random: None,
+        }
+    }
+
+    /// Returns the rounding mode.
+    #[must_use]
+    #[inline]
+    pub const fn mode(&self) -> RoundingMode {
+        self.mode
+    }
+
+    /// Sets the rounding mode, the flags are kept.
+    #[inline]
+    pub fn set_mode(&mut self, mode: RoundingMode) {
+        self.mode = mode;
+    }
+
+    /// Returns the tininess detection of underflow.
+    #[must_use]
+    #[inline]
+    pub const fn tininess(&self) -> Tininess {
+        self.tininess
+    }
+
+    /// Sets the tininess detection of underflow.
+    #[inline]
+    pub fn set_tininess(&mut self, tininess: Tininess) {
+        self.tininess = tininess;
+    }
+
+    /// Returns the NaN semantics.
+    #[must_use]
+    #[inline]
+    pub const fn nan_semantics(&self) -> NanSemantics {
+        self.nan
+    }
+
+    /// Sets the NaN semantics.
+    #[inline]
+    pub fn set_nan_semantics(&mut self, nan: NanSemantics) {
+        self.nan = nan;
+    }
+
+    /// Returns the handling of subnormal numbers.
+    #[must_use]
+    #[inline]
+    pub const fn denormal_mode(&self) -> DenormalMode {
+        self.denormal
+    }
+
+    /// Sets the handling of subnormal numbers.
+    #[inline]
+    pub fn set_denormal_mode(&mut self, denormal: DenormalMode) {
+        self.denormal = denormal;
+    }
+
+    /// Returns the raised flags.
+    #[must_use]
+    #[inline]
+    pub const fn flags(&self) -> ExceptionFlags {
+        self.flags
+    }
+
+    /// Returns `true` if some flag of `flags` is raised.
+    #[must_use]
+    #[inline]
+    pub const fn test_flags(&self, flags: ExceptionFlags) -> bool {
+        self.flags.intersects(flags)
+    }
+
+    /// Raises the flags.
+    #[inline]
+    pub const fn raise(&mut self, flags: ExceptionFlags) {
+        self.flags = self.flags.union(flags);
+    }
+
+    /// Lowers all flags.
+    #[inline]
+    pub fn clear_flags(&mut self) {
+        self.flags = ExceptionFlags::empty();
+    }
+
+    /// Lowers all flags and returns the flags before.
+    #[inline]
+    pub fn take_flags(&mut self) -> ExceptionFlags {
+        core::mem::take(&mut self.flags)
+    }
+
+    /// Sets the handler of the exception of the environment, returns the previous one.
+    /// `None` falls back to the handler of the thread, see [`set_exception_handler`][crate::set_exception_handler].
+    #[inline]
+    pub fn set_handler(
+        &mut self,
+        kind: ExceptionKind,
+        handler: Option<ExceptionHandler>,
+    ) -> Option<ExceptionHandler> {
+        self.handlers.replace(kind, handler)
+    }
+
+    /// Returns the handler of the exception of the environment.
+    #[must_use]
+    #[inline]
+    pub const fn handler(&self, kind: ExceptionKind) -> Option<ExceptionHandler> {
+        self.handlers.get(kind)
+    }
+
+    /// Handles the exceptions `raised` by the op and raises the flags.
+    #[inline]
+    fn finish<T: EnvFloat>(
+        &mut self,
+        r: T,
+        raised: ExceptionFlags,
+        op: Operation,
+        location: &'static Location<'static>,
+    ) -> T {
+        if !raised.intersects(!ExceptionFlags::INEXACT) {
+            self.flags.insert(raised);
+            return r;
+        }
+
+        let (r, raised) = exception::handle(&self.handlers, raised, r, op, &self.mode, location);
+        self.flags.insert(raised);
+        r
+    }
+
+    impl_env_op!(
+        /// Returns `a + b` with the rounding mode, and raises flags.
+        add, add_env, Add, (a, b)
+    );
+    impl_env_op!(
+        /// Returns `a - b` with the rounding mode, and raises flags.
+        sub, sub_env, Sub, (a, b)
+    );
+    impl_env_op!(
+        /// Returns `a * b` with the rounding mode, and raises flags.
+        mul, mul_env, Mul, (a, b)
+    );
+    impl_env_op!(
+        /// Returns `a / b` with the rounding mode, and raises flags.
+        div, div_env, Div, (a, b)
+    );
+    impl_env_op!(
+        /// Returns `a * b + c` with single rounding (fused multiply-add) with the rounding mode,
+        /// and raises flags.
+        mul_add, mul_add_env, MulAdd, (a, b, c)
+    );
+    impl_env_op!(
+        /// Returns `a.sqrt()` with the rounding mode, and raises flags.
+        sqrt, sqrt_env, Sqrt, (a)
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NEAREST: RoundingMode = RoundingMode::NearestTiesEven;
+
+    fn flags(f: impl FnOnce(&mut FpEnv) -> f64) -> ExceptionFlags {
+        let mut env = FpEnv::new(NEAREST);
+        let _ = f(&mut env);
+        env.flags()
+    }
+
+    #[test]
+    fn test_flags() {
+        use ExceptionFlags as F;
+
+        let snan = f64::from_bits(0x7FF0_0000_0000_0001);
+
+        // exact
+        assert_eq!(flags(|e| e.add(1.0, 2.0)), F::empty());
+        assert_eq!(flags(|e| e.sqrt(4.0)), F::empty());
+        assert_eq!(flags(|e| e.add(f64::INFINITY, 1.0)), F::empty());
+        assert_eq!(flags(|e| e.add(f64::NAN, 1.0)), F::empty());
+        // exact subnormal
+        assert_eq!(flags(|e| e.mul(f64::MIN_POSITIVE, 0.5)), F::empty());
+
+        // inexact
+        assert_eq!(flags(|e| e.add(0.1, 0.2)), F::INEXACT);
+        assert_eq!(flags(|e| e.div(1.0, 3.0)), F::INEXACT);
+        assert_eq!(flags(|e| e.sqrt(2.0)), F::INEXACT);
+
+        // invalid
+        assert_eq!(flags(|e| e.sub(f64::INFINITY, f64::INFINITY)), F::INVALID);
+        assert_eq!(flags(|e| e.mul(0.0, f64::INFINITY)), F::INVALID);
+        assert_eq!(flags(|e| e.div(0.0, 0.0)), F::INVALID);
+        assert_eq!(flags(|e| e.sqrt(-1.0)), F::INVALID);
+        assert_eq!(flags(|e| e.add(snan, 1.0)), F::INVALID);
+        assert_eq!(flags(|e| e.mul_add(1.0, 1.0, snan)), F::INVALID);
+        assert_eq!(
+            flags(|e| e.mul_add(0.0, f64::INFINITY, f64::NAN)),
+            F::INVALID
+        );
+
+        // division by zero
+        assert_eq!(flags(|e| e.div(1.0, 0.0)), F::DIV_BY_ZERO);
+        assert_eq!(flags(|e| e.div(f64::INFINITY, 0.0)), F::empty());
+
+        // overflow
+        assert_eq!(flags(|e| e.mul(f64::MAX, 2.0)), F::OVERFLOW | F::INEXACT);
+        assert_eq!(flags(|e| e.add(f64::MAX, 1.0)), F::INEXACT);
+
+        // underflow
+        assert_eq!(
+            flags(|e| e.mul(f64::MIN_POSITIVE, 0.1)),
+            F::UNDERFLOW | F::INEXACT
+        );
+        assert_eq!(
+            flags(|e| e.div(f64::from_bits(1), 3.0)),
+            F::UNDERFLOW | F::INEXACT
+        );
+    }
+
+    #[test]
+    fn test_tininess() {
+        use ExceptionFlags as F;
+
+        // 2^emin * (1 + 2^-52) * (1 - 2^-52) = 2^emin - 2^(emin - 104)
+        // rounds to 2^emin with unbounded exponent, it is not tiny
+        let x = f64::from_bits(f64::MIN_POSITIVE.to_bits() + 1);
+        let y = 1.0 - f64::EPSILON;
+        let mut env = FpEnv::new(NEAREST);
+        env.set_tininess(Tininess::AfterRounding);
+        assert_eq!(env.mul(x, y), f64::MIN_POSITIVE);
+        assert_eq!(env.flags(), F::INEXACT);
+
+        // but it is tiny toward zero
+        let mut env = FpEnv::new(RoundingMode::TowardZero);
+        assert_eq!(
+            env.mul(x, y),
+            f64::from_bits(f64::MIN_POSITIVE.to_bits() - 1)
+        );
+        assert_eq!(env.flags(), F::UNDERFLOW | F::INEXACT);
+
+        // tiny before rounding
+        let mut env = FpEnv::new(NEAREST);
+        env.set_tininess(Tininess::BeforeRounding);
+        assert_eq!(env.mul(x, y), f64::MIN_POSITIVE);
+        assert_eq!(env.take_flags(), F::UNDERFLOW | F::INEXACT);
+
+        // not underflow if exact, not tiny if the exact result is normal
+        assert_eq!(env.mul(f64::MIN_POSITIVE, 0.5), f64::MIN_POSITIVE / 2.0);
+        assert_eq!(
+            env.mul(x, 1.0 + f64::EPSILON),
+            f64::from_bits(x.to_bits() + 1)
+        );
+        assert_eq!(env.take_flags(), F::INEXACT);
+
+        let x = f32::from_bits(f32::MIN_POSITIVE.to_bits() + 1);
+        let y = 1.0 - f32::EPSILON;
+        for (tininess, flags) in [
+            (Tininess::BeforeRounding, F::UNDERFLOW | F::INEXACT),
+            (Tininess::AfterRounding, F::INEXACT),
+        ] {
+            let mut env = FpEnv::new(NEAREST);
+            env.set_tininess(tininess);
+            assert_eq!(env.mul(x, y), f32::MIN_POSITIVE);
+            assert_eq!(env.flags(), flags);
+        }
+    }
+
+    #[test]
+    fn test_denormal() {
+        use ExceptionFlags as F;
+
+        let sub = f64::MIN_POSITIVE / 4.0;
+
+        let mut env = FpEnv::new(RoundingMode::TowardPosInf);
+        env.set_denormal_mode(DenormalMode::FTZ);
+        // inexact and exact subnormal results
+        assert_eq!(env.mul(f64::MIN_POSITIVE, 0.1), 0.0);
+        assert_eq!(env.take_flags(), F::UNDERFLOW | F::INEXACT);
+        assert_eq!(
+            env.mul(-f64::MIN_POSITIVE, 0.5).to_bits(),
+            (-0.0f64).to_bits()
+        );
+        assert_eq!(env.take_flags(), F::UNDERFLOW | F::INEXACT);
+        // rounded up to the normal, and subnormal operands without DAZ
+        assert_eq!(
+            env.mul(f64::MIN_POSITIVE.next_up(), 1.0 - f64::EPSILON),
+            f64::MIN_POSITIVE
+        );
+        assert_eq!(env.add(sub, 1.0), 1.0f64.next_up());
+        assert_eq!(env.take_flags(), F::INEXACT);
+
+        env.set_denormal_mode(DenormalMode::DAZ);
+        assert_eq!(env.add(sub, 1.0), 1.0);
+        assert_eq!(env.mul(-sub, 2.0).to_bits(), (-0.0f64).to_bits());
+        assert_eq!(env.div(1.0, sub), f64::INFINITY);
+        assert_eq!(env.take_flags(), F::DIV_BY_ZERO);
+        // the results are not flushed
+        assert_eq!(env.mul(f64::MIN_POSITIVE, 0.5), f64::MIN_POSITIVE / 2.0);
+        assert_eq!(env.take_flags(), F::empty());
+
+        // f32
+        let mut env = FpEnv::new(RoundingMode::TowardNegInf);
+        env.set_denormal_mode(DenormalMode::FTZ_DAZ);
+        assert_eq!(
+            env.mul_add(f32::MIN_POSITIVE, 0.5, f32::MIN_POSITIVE / 2.0),
+            0.0
+        );
+        assert_eq!(env.sqrt(f32::MIN_POSITIVE / 2.0), 0.0);
+        assert_eq!(env.add(0.1f32, 0.2), crate::f32::floor_add(0.1, 0.2));
+    }
+
+    #[test]
+    fn test_sticky() {
+        let mut env = FpEnv::default();
+
+        let mut sum = 0.0f32;
+        for x in [1.0, 2.0, 3.0] {
+            sum = env.add(sum, x);
+        }
+        assert_eq!(sum, 6.0);
+        assert!(!env.test_flags(ExceptionFlags::INEXACT));
+
+        sum = env.div(sum, 7.0);
+        sum = env.mul(sum, 7.0);
+        let _ = env.sqrt(sum);
+        assert!(env.test_flags(ExceptionFlags::INEXACT));
+
+        assert_eq!(env.take_flags(), ExceptionFlags::INEXACT);
+        assert!(env.flags().is_empty());
+    }
+
+    #[test]
+    fn test_mode() {
+        let mut env = FpEnv::new(RoundingMode::TowardNegInf);
+        assert_eq!(env.add(0.1, 0.2), 0.3);
+        env.set_mode(RoundingMode::TowardPosInf);
+        assert_eq!(env.add(0.1, 0.2), 0.30000000000000004);
+        assert_eq!(env.flags(), ExceptionFlags::INEXACT);
+
+        // the same results as the ops
+        for mode in RoundingMode::ALL {
+            let mut env = FpEnv::new(mode);
+            assert_eq!(env.div(1.0, 3.0), crate::f64::round_div(1.0, 3.0, &mode));
+            assert_eq!(
+                env.mul_add(0.1f32, 0.2, 0.3),
+                crate::f32::round_mul_add(0.1, 0.2, 0.3, &mode)
+            );
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(ExceptionFlags::empty().to_string(), "none");
+        assert_eq!(
+            ExceptionFlags::all().to_string(),
+            "invalid | division by zero | overflow | underflow | inexact"
+        );
+        assert_eq!(
+            !ExceptionFlags::INEXACT & ExceptionFlags::all(),
+            !ExceptionFlags::INEXACT
+        );
+    }
+}
@@ -1,12 +1,33 @@
+pub(crate) mod arith;
+#[cfg(all(
+    any(feature = "f32_asm", feature = "f64_asm", feature = "simd"),
+    target_arch = "x86_64"
+))]
+pub(crate) mod asm;
+pub(crate) mod bigint;
 pub(crate) mod builtin;
+pub(crate) mod eft;
+pub(crate) mod format;
+#[cfg(feature = "rayon")]
+pub(crate) mod par;
+pub(crate) mod parse;
+pub(crate) mod print;
+#[cfg(any(feature = "f32_pure_rust", feature = "f64_pure_rust"))]
+pub(crate) mod pure_rust;
 pub(crate) mod softfloat;
+#[cfg(feature = "stochastic")]
+pub(crate) mod stochastic;
+#[cfg(any(feature = "f16_widening", feature = "f32_widening"))]
+pub(crate) mod widening;
 
 #[cfg(any(feature = "f128", feature = "f128_softfloat"))]
 pub mod f128;
-#[cfg(any(feature = "f16", feature = "f16_softfloat"))]
+#[cfg(any(feature = "f16", feature = "f16_softfloat", feature = "f16_widening"))]
 pub mod f16;
 pub mod f32;
 pub mod f64;
+#[cfg(feature = "simd")]
+pub mod simd;
 
 /// wrapper of panic!(..)
 #[cold]
@@ -0,0 +1,744 @@
+//! Correctly rounded arithmetic by integer bit manipulation.
+//!
+//! Every operation computes the exact result (or the exact result with a sticky bit)
+//! and rounds it by [`Format::round_pack_env`], thus it works in `const` context.
+//! The NaN results follow the [`NanSemantics`] of the [`FpEnv`] (of the target architecture by default),
+//! and the exception flags are raised to it.
+//!
+//! It supports formats whose precision is not greater than 53, that is, `f16`, `f32` and `f64`.
+
+use crate::r#impl::format::{Format, Unpacked};
+#[cfg(any(
+    test,
+    feature = "f16_softfloat",
+    feature = "f32_softfloat",
+    feature = "f64_softfloat",
+    feature = "f128_softfloat"
+))]
+use crate::Operation;
+use crate::{ExceptionFlags, FpEnv, NanSemantics, RoundingMode};
+
+/// The exact value `(-1)^sign * (sig + δ) * 2^exp`, see [`Format::round_pack`].
+#[derive(Debug, Copy, Clone)]
+struct Exact {
+    sign: bool,
+    sig: u128,
+    exp: i32,
+    sticky: bool,
+}
+
+const fn bit_len(x: u128) -> i32 {
+    128 - x.leading_zeros() as i32
+}
+
+impl Format {
+    const fn quiet_bit(&self) -> u128 {
+        1 << (self.precision - 2)
+    }
+
+    const fn is_nan(&self, bits: u128) -> bool {
+        bits & !self.sign_mask() > self.inf(false)
+    }
+
+    const fn is_signaling(&self, bits: u128) -> bool {
+        self.is_nan(bits) && bits & self.quiet_bit() == 0
+    }
+
+    /// Bits of the NaN generated by invalid operations.
+    pub(crate) const fn default_nan(&self, nan: NanSemantics) -> u128 {
+        match nan {
+            NanSemantics::X86 => self.sign_mask() | self.nan(),
+            NanSemantics::ArmDefaultNan
+            | NanSemantics::RiscVCanonical
+            | NanSemantics::PreservePayload => self.nan(),
+        }
+    }
+
+    /// Returns the NaN result of an operation taking `a` and `b`, one of them is NaN.
+    pub(crate) const fn propagate_nan(&self, a: u128, b: u128, nan: NanSemantics) -> u128 {
+        match nan {
+            NanSemantics::X86 => {
+                if self.is_nan(a) {
+                    a | self.quiet_bit()
+                } else {
+                    b | self.quiet_bit()
+                }
+            }
+            NanSemantics::PreservePayload => {
+                if self.is_signaling(a) {
+                    a | self.quiet_bit()
+                } else if self.is_signaling(b) || !self.is_nan(a) {
+                    b | self.quiet_bit()
+                } else {
+                    a
+                }
+            }
+            NanSemantics::ArmDefaultNan | NanSemantics::RiscVCanonical => self.default_nan(nan),
+        }
+    }
+
+    /// Returns the NaN result of `op` taking `xs` as the ops of this module,
+    /// provided the result is NaN.
+    ///
+    /// It is for backends whose NaN results differ (e.g. SoftFloat of another specialization).
+    #[cfg(any(
+        test,
+        feature = "f16_softfloat",
+        feature = "f32_softfloat",
+        feature = "f64_softfloat",
+        feature = "f128_softfloat"
+    ))]
+    pub(crate) const fn nan_result(&self, op: Operation, xs: &[u128], nan: NanSemantics) -> u128 {
+        match (op, xs) {
+            (Operation::Sqrt, &[a]) if self.is_nan(a) => self.propagate_nan(a, 0, nan),
+            (Operation::MulAdd, &[a, b, c]) => {
+                if self.is_nan(a) || self.is_nan(b) {
+                    self.propagate_nan(self.propagate_nan(a, b, nan), c, nan)
+                } else if self.is_nan(c) {
+                    let invalid = matches!(
+                        (self.unpack(a).1, self.unpack(b).1),
+                        (Unpacked::Inf, Unpacked::Zero) | (Unpacked::Zero, Unpacked::Inf)
+                    );
+                    let x = if invalid { self.default_nan(nan) } else { 0 };
+                    self.propagate_nan(x, c, nan)
+                } else {
+                    self.default_nan(nan)
+                }
+            }
+            (Operation::Add | Operation::Sub | Operation::Mul | Operation::Div, &[a, b])
+                if self.is_nan(a) || self.is_nan(b) =>
+            {
+                self.propagate_nan(a, b, nan)
+            }
+            _ => self.default_nan(nan),
+        }
+    }
+
+    /// Bits of the default NaN, raises invalid flag.
+    const fn invalid(&self, env: &mut FpEnv) -> u128 {
+        env.raise(ExceptionFlags::INVALID);
+        self.default_nan(env.nan)
+    }
+
+    /// Raises invalid flag if one of `xs` is a signaling NaN.
+    const fn check_signaling(&self, xs: &[u128], env: &mut FpEnv) {
+        let mut i = 0;
+        while i < xs.len() {
+            if self.is_signaling(xs[i]) {
+                env.raise(ExceptionFlags::INVALID);
+            }
+            i += 1;
+        }
+    }
+
+    /// Bits of the zero of exact `x - x`.
+    const fn exact_zero(&self, mode: &RoundingMode) -> u128 {
+        match mode {
+            RoundingMode::TowardNegInf => self.sign_mask(),
+            _ => 0,
+        }
+    }
+
+    const fn round_exact(&self, x: Exact, env: &mut FpEnv) -> u128 {
+        if x.sig == 0 && !x.sticky {
+            return self.exact_zero(&env.mode);
+        }
+        self.round_pack_env(x.sign, x.sig, x.exp, x.sticky, env)
+    }
+}
+
+/// Returns the exact sum of non-zero finite `x` and `y`, where the both `sticky` are `false`
+/// and `sig` have at most 106 bits.
+const fn add_exact(x: Exact, y: Exact) -> Exact {
+    let (x, y) = if x.exp < y.exp { (y, x) } else { (x, y) };
+    let d = x.exp - y.exp;
+
+    if bit_len(x.sig) + d <= 126 {
+        // exact, the result has at most 127 bits
+        let xs = x.sig << d;
+        return if x.sign == y.sign {
+            Exact {
+                sign: x.sign,
+                sig: xs + y.sig,
+                exp: y.exp,
+                sticky: false,
+            }
+        } else if xs >= y.sig {
+            Exact {
+                sign: x.sign,
+                sig: xs - y.sig,
+                exp: y.exp,
+                sticky: false,
+            }
+        } else {
+            Exact {
+                sign: y.sign,
+                sig: y.sig - xs,
+                exp: y.exp,
+                sticky: false,
+            }
+        };
+    }
+
+    // |x| >= 2^125 * 2^exp > |y|, y contributes as shifted bits and sticky
+    let s = 126 - bit_len(x.sig);
+    let xs = x.sig << s;
+    let exp = x.exp - s;
+    let d = (exp - y.exp) as u32;
+    let (ys, sticky) = if d >= 128 {
+        (0, true)
+    } else {
+        (y.sig >> d, y.sig & ((1 << d) - 1) != 0)
+    };
+
+    let sig = if x.sign == y.sign {
+        xs + ys
+    } else if sticky {
+        // xs - ys - δ = (xs - ys - 1) + (1 - δ)
+        xs - ys - 1
+    } else {
+        xs - ys
+    };
+    Exact {
+        sign: x.sign,
+        sig,
+        exp,
+        sticky,
+    }
+}
+
+/// Returns bits of `a + b` (or `a - b` if `neg` is `true`) rounded with the mode of `env`.
+const fn add_impl(fmt: &Format, a: u128, b: u128, neg: bool, env: &mut FpEnv) -> u128 {
+    if fmt.is_nan(a) || fmt.is_nan(b) {
+        fmt.check_signaling(&[a, b], env);
+        return fmt.propagate_nan(a, b, env.nan);
+    }
+    let (sa, va) = fmt.unpack(a);
+    let (sb, vb) = fmt.unpack(b);
+    let sb = sb != neg;
+
+    match (va, vb) {
+        (Unpacked::Inf, Unpacked::Inf) if sa != sb => fmt.invalid(env),
+        (Unpacked::Inf, _) => fmt.inf(sa),
+        (_, Unpacked::Inf) => fmt.inf(sb),
+        (Unpacked::Zero, Unpacked::Zero) if sa == sb => fmt.inf(sa) & fmt.sign_mask(),
+        (Unpacked::Zero, Unpacked::Zero) => fmt.exact_zero(&env.mode),
+        (Unpacked::Zero, _) => b ^ if neg { fmt.sign_mask() } else { 0 },
+        (_, Unpacked::Zero) => a,
+        (Unpacked::Finite { sig: ma, exp: ea }, Unpacked::Finite { sig: mb, exp: eb }) => {
+            let x = Exact {
+                sign: sa,
+                sig: ma,
+                exp: ea,
+                sticky: false,
+            };
+            let y = Exact {
+                sign: sb,
+                sig: mb,
+                exp: eb,
+                sticky: false,
+            };
+            fmt.round_exact(add_exact(x, y), env)
+        }
+        (Unpacked::Nan, _) | (_, Unpacked::Nan) => unreachable!(),
+    }
+}
+
+/// Returns bits of `a + b` rounded with `mode`.
+pub(crate) const fn add(fmt: &Format, a: u128, b: u128, mode: &RoundingMode) -> u128 {
+    add_env(fmt, a, b, &mut FpEnv::new(*mode))
+}
+
+/// Returns bits of `a + b` rounded with the mode of `env`, and raises flags to `env`.
+pub(crate) const fn add_env(fmt: &Format, a: u128, b: u128, env: &mut FpEnv) -> u128 {
+    add_impl(fmt, a, b, false, env)
+}
+
+/// Returns bits of `a - b` rounded with `mode`.
+pub(crate) const fn sub(fmt: &Format, a: u128, b: u128, mode: &RoundingMode) -> u128 {
+    sub_env(fmt, a, b, &mut FpEnv::new(*mode))
+}
+
+/// Returns bits of `a - b` rounded with the mode of `env`, and raises flags to `env`.
+pub(crate) const fn sub_env(fmt: &Format, a: u128, b: u128, env: &mut FpEnv) -> u128 {
+    add_impl(fmt, a, b, true, env)
+}
+
+/// Returns bits of `a * b` rounded with `mode`.
+pub(crate) const fn mul(fmt: &Format, a: u128, b: u128, mode: &RoundingMode) -> u128 {
+    mul_env(fmt, a, b, &mut FpEnv::new(*mode))
+}
+
+/// Returns bits of `a * b` rounded with the mode of `env`, and raises flags to `env`.
+pub(crate) const fn mul_env(fmt: &Format, a: u128, b: u128, env: &mut FpEnv) -> u128 {
+    if fmt.is_nan(a) || fmt.is_nan(b) {
+        fmt.check_signaling(&[a, b], env);
+        return fmt.propagate_nan(a, b, env.nan);
+    }
+    let (sa, va) = fmt.unpack(a);
+    let (sb, vb) = fmt.unpack(b);
+    let sign = sa != sb;
+
+    match (va, vb) {
+        (Unpacked::Inf, Unpacked::Zero) | (Unpacked::Zero, Unpacked::Inf) => fmt.invalid(env),
+        (Unpacked::Inf, _) | (_, Unpacked::Inf) => fmt.inf(sign),
+        (Unpacked::Zero, _) | (_, Unpacked::Zero) => fmt.inf(sign) & fmt.sign_mask(),
+        (Unpacked::Finite { sig: ma, exp: ea }, Unpacked::Finite { sig: mb, exp: eb }) => {
+            fmt.round_pack_env(sign, ma * mb, ea + eb, false, env)
+        }
+        (Unpacked::Nan, _) | (_, Unpacked::Nan) => unreachable!(),
+    }
+}
+
+/// Returns bits of `a / b` rounded with `mode`.
+pub(crate) const fn div(fmt: &Format, a: u128, b: u128, mode: &RoundingMode) -> u128 {
+    div_env(fmt, a, b, &mut FpEnv::new(*mode))
+}
+
+/// Returns bits of `a / b` rounded with the mode of `env`, and raises flags to `env`.
+pub(crate) const fn div_env(fmt: &Format, a: u128, b: u128, env: &mut FpEnv) -> u128 {
+    if fmt.is_nan(a) || fmt.is_nan(b) {
+        fmt.check_signaling(&[a, b], env);
+        return fmt.propagate_nan(a, b, env.nan);
+    }
+    let (sa, va) = fmt.unpack(a);
+    let (sb, vb) = fmt.unpack(b);
+    let sign = sa != sb;
+
+    match (va, vb) {
+        (Unpacked::Inf, Unpacked::Inf) | (Unpacked::Zero, Unpacked::Zero) => fmt.invalid(env),
+        (Unpacked::Inf, _) => fmt.inf(sign),
+        (_, Unpacked::Zero) => {
+            env.raise(ExceptionFlags::DIV_BY_ZERO);
+            fmt.inf(sign)
+        }
+        (Unpacked::Zero, _) | (_, Unpacked::Inf) => fmt.inf(sign) & fmt.sign_mask(),
+        (Unpacked::Finite { sig: ma, exp: ea }, Unpacked::Finite { sig: mb, exp: eb }) => {
+            // the quotient has at least 128 - 53 bits
+            let s = ma.leading_zeros();
+            let num = ma << s;
+            let exp = ea - s as i32 - eb;
+            fmt.round_pack_env(sign, num / mb, exp, num % mb != 0, env)
+        }
+        (Unpacked::Nan, _) | (_, Unpacked::Nan) => unreachable!(),
+    }
+}
+
+/// Returns bits of `a.sqrt()` rounded with `mode`.
+pub(crate) const fn sqrt(fmt: &Format, a: u128, mode: &RoundingMode) -> u128 {
+    sqrt_env(fmt, a, &mut FpEnv::new(*mode))
+}
+
+/// Returns bits of `a.sqrt()` rounded with the mode of `env`, and raises flags to `env`.
+pub(crate) const fn sqrt_env(fmt: &Format, a: u128, env: &mut FpEnv) -> u128 {
+    if fmt.is_nan(a) {
+        fmt.check_signaling(&[a], env);
+        return fmt.propagate_nan(a, 0, env.nan);
+    }
+    match fmt.unpack(a) {
+        (_, Unpacked::Zero) => a,
+        (false, Unpacked::Inf) => a,
+        (true, _) => fmt.invalid(env),
+        (false, Unpacked::Finite { sig, exp }) => {
+            // shifts sig to 125 or 126 bits with even exponent
+            let mut s = 126 - bit_len(sig);
+            if (exp - s) % 2 != 0 {
+                s -= 1;
+            }
+            let n = sig << s;
+
+            // bit-by-bit integer square root, the root has 63 bits
+            let mut root = 0u128;
+            let mut rem = n;
+            let mut bit = 1u128 << 126;
+            while bit > n {
+                bit >>= 2;
+            }
+            while bit != 0 {
+                if rem >= root + bit {
+                    rem -= root + bit;
+                    root = (root >> 1) + bit;
+                } else {
+                    root >>= 1;
+                }
+                bit >>= 2;
+            }
+
+            fmt.round_pack_env(false, root, (exp - s) / 2, rem != 0, env)
+        }
+        (false, Unpacked::Nan) => unreachable!(),
+    }
+}
+
+/// Returns bits of `a * b + c` rounded once with `mode`.
+pub(crate) const fn mul_add(fmt: &Format, a: u128, b: u128, c: u128, mode: &RoundingMode) -> u128 {
+    mul_add_env(fmt, a, b, c, &mut FpEnv::new(*mode))
+}
+
+/// Returns bits of `a * b + c` rounded once with the mode of `env`, and raises flags to `env`.
+pub(crate) const fn mul_add_env(fmt: &Format, a: u128, b: u128, c: u128, env: &mut FpEnv) -> u128 {
+    fmt.check_signaling(&[a, b, c], env);
+    if fmt.is_nan(a) || fmt.is_nan(b) {
+        return fmt.propagate_nan(fmt.propagate_nan(a, b, env.nan), c, env.nan);
+    }
+    let (sa, va) = fmt.unpack(a);
+    let (sb, vb) = fmt.unpack(b);
+    let (sc, vc) = fmt.unpack(c);
+    let sign = sa != sb;
+
+    let product = match (va, vb) {
+        (Unpacked::Inf, Unpacked::Zero) | (Unpacked::Zero, Unpacked::Inf) => {
+            let nan = fmt.invalid(env);
+            return fmt.propagate_nan(nan, c, env.nan);
+        }
+        (Unpacked::Inf, _) | (_, Unpacked::Inf) => {
+            return match vc {
+                Unpacked::Nan => fmt.propagate_nan(fmt.inf(sign), c, env.nan),
+                Unpacked::Inf if sign != sc => fmt.invalid(env),
+                _ => fmt.inf(sign),
+            };
+        }
+        (Unpacked::Zero, _) | (_, Unpacked::Zero) => None,
+        (Unpacked::Finite { sig: ma, exp: ea }, Unpacked::Finite { sig: mb, exp: eb }) => {
+            Some(Exact {
+                sign,
+                sig: ma * mb,
+                exp: ea + eb,
+                sticky: false,
+            })
+        }
+        (Unpacked::Nan, _) | (_, Unpacked::Nan) => unreachable!(),
+    };
+
+    match (product, vc) {
+        (_, Unpacked::Nan) => fmt.propagate_nan(0, c, env.nan),
+        (_, Unpacked::Inf) => c,
+        (None, Unpacked::Zero) if sign == sc => c,
+        (None, Unpacked::Zero) => fmt.exact_zero(&env.mode),
+        (None, Unpacked::Finite { .. }) => c,
+        (Some(x), Unpacked::Zero) => fmt.round_pack_env(x.sign, x.sig, x.exp, false, env),
+        (Some(x), Unpacked::Finite { sig, exp }) => {
+            let y = Exact {
+                sign: sc,
+                sig,
+                exp,
+                sticky: false,
+            };
+            fmt.round_exact(add_exact(x, y), env)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r#impl::format::{F32, F64};
+    use crate::RoundingMode as Mode;
+
+    const MODES: [Mode; 4] = [
+        Mode::NearestTiesEven,
+        Mode::TowardPosInf,
+        Mode::TowardNegInf,
+        Mode::TowardZero,
+    ];
+
+    /// xorshift, returns interesting bits frequently
+    struct Gen(u64);
+
+    impl Gen {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn f64(&mut self) -> f64 {
+            let r = self.next();
+            match r % 16 {
+                0 => [
+                    0.0,
+                    -0.0,
+                    f64::INFINITY,
+                    f64::NEG_INFINITY,
+                    f64::MAX,
+                    f64::MIN_POSITIVE,
+                ][(r >> 8) as usize % 6],
+                // subnormal
+                1 => f64::from_bits(r >> 12 | (r & 1 << 63)),
+                // close exponents
+                2..=7 => f64::from_bits(r & 0x800F_FFFF_FFFF_FFFF | 0x3FF0_0000_0000_0000),
+                // small integer
+                8 => (r % 64) as f64 - 32.0,
+                _ => match f64::from_bits(r) {
+                    x if x.is_nan() => 1.0,
+                    x => x,
+                },
+            }
+        }
+
+        fn f32(&mut self) -> f32 {
+            let r = self.next();
+            match r % 16 {
+                0 => [
+                    0.0,
+                    -0.0,
+                    f32::INFINITY,
+                    f32::NEG_INFINITY,
+                    f32::MAX,
+                    f32::MIN_POSITIVE,
+                ][(r >> 8) as usize % 6],
+                1 => f32::from_bits((r as u32) >> 9 | (r as u32 & 1 << 31)),
+                2..=7 => f32::from_bits(r as u32 & 0x807F_FFFF | 0x3F80_0000),
+                8 => (r % 64) as f32 - 32.0,
+                _ => match f32::from_bits(r as u32) {
+                    x if x.is_nan() => 1.0,
+                    x => x,
+                },
+            }
+        }
+    }
+
+    fn same(x: u128, e: u128, fmt: &Format) -> bool {
+        x == e || (fmt.is_nan(x) && fmt.is_nan(e))
+    }
+
+    #[test]
+    fn test_f64() {
+        let mut g = Gen(0x2545_F491_4F6C_DD1D);
+        for _ in 0..20000 {
+            let (a, b, c) = (g.f64(), g.f64(), g.f64());
+            let (ba, bb, bc) = (
+                a.to_bits() as u128,
+                b.to_bits() as u128,
+                c.to_bits() as u128,
+            );
+            for mode in &MODES {
+                let f = |x: f64| x.to_bits() as u128;
+                let e = f(crate::f64::round_add(a, b, mode));
+                assert!(
+                    same(add(&F64, ba, bb, mode), e, &F64),
+                    "{a:e} + {b:e} {mode:?}"
+                );
+                let e = f(crate::f64::round_sub(a, b, mode));
+                assert!(
+                    same(sub(&F64, ba, bb, mode), e, &F64),
+                    "{a:e} - {b:e} {mode:?}"
+                );
+                let e = f(crate::f64::round_mul(a, b, mode));
+                assert!(
+                    same(mul(&F64, ba, bb, mode), e, &F64),
+                    "{a:e} * {b:e} {mode:?}"
+                );
+                let e = f(crate::f64::round_div(a, b, mode));
+                assert!(
+                    same(div(&F64, ba, bb, mode), e, &F64),
+                    "{a:e} / {b:e} {mode:?}"
+                );
+                let e = f(crate::f64::round_sqrt(a, mode));
+                assert!(same(sqrt(&F64, ba, mode), e, &F64), "sqrt {a:e} {mode:?}");
+                let e = f(crate::f64::round_mul_add(a, b, c, mode));
+                let r = mul_add(&F64, ba, bb, bc, mode);
+                assert!(same(r, e, &F64), "{a:e} * {b:e} + {c:e} {mode:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_f32() {
+        let mut g = Gen(0x9E37_79B9_7F4A_7C15);
+        for _ in 0..20000 {
+            let (a, b, c) = (g.f32(), g.f32(), g.f32());
+            let (ba, bb, bc) = (
+                a.to_bits() as u128,
+                b.to_bits() as u128,
+                c.to_bits() as u128,
+            );
+            for mode in &MODES {
+                let f = |x: f32| x.to_bits() as u128;
+                let e = f(crate::f32::round_add(a, b, mode));
+                assert!(
+                    same(add(&F32, ba, bb, mode), e, &F32),
+                    "{a:e} + {b:e} {mode:?}"
+                );
+                let e = f(crate::f32::round_sub(a, b, mode));
+                assert!(
+                    same(sub(&F32, ba, bb, mode), e, &F32),
+                    "{a:e} - {b:e} {mode:?}"
+                );
+                let e = f(crate::f32::round_mul(a, b, mode));
+                assert!(
+                    same(mul(&F32, ba, bb, mode), e, &F32),
+                    "{a:e} * {b:e} {mode:?}"
+                );
+                let e = f(crate::f32::round_div(a, b, mode));
+                assert!(
+                    same(div(&F32, ba, bb, mode), e, &F32),
+                    "{a:e} / {b:e} {mode:?}"
+                );
+                let e = f(crate::f32::round_sqrt(a, mode));
+                assert!(same(sqrt(&F32, ba, mode), e, &F32), "sqrt {a:e} {mode:?}");
+                let e = f(crate::f32::round_mul_add(a, b, c, mode));
+                let r = mul_add(&F32, ba, bb, bc, mode);
+                assert!(same(r, e, &F32), "{a:e} * {b:e} + {c:e} {mode:?}");
+            }
+        }
+    }
+
+    /// Compares with the SoftFloat ops bit by bit, NaNs included.
+    #[test]
+    #[cfg(feature = "f64_softfloat")]
+    fn test_f64_softfloat() {
+        use crate::NanSemantics;
+
+        let mut g = Gen(0xD1B5_4A32_D192_ED03);
+        for nan in NanSemantics::ALL {
+            crate::set_softfloat_nan_semantics(Some(nan));
+            for _ in 0..5000 {
+                let mut x = || {
+                    let r = g.next();
+                    if r % 8 == 0 {
+                        // signaling or quiet NaN with a payload
+                        f64::from_bits(r & 0x800F_FFFF_FFFF_FFFF | 0x7FF0_0000_0000_0001)
+                    } else {
+                        g.f64()
+                    }
+                };
+                let (a, b, c) = (x(), x(), x());
+                let (ba, bb, bc) = (
+                    a.to_bits() as u128,
+                    b.to_bits() as u128,
+                    c.to_bits() as u128,
+                );
+                for mode in &MODES {
+                    let f = |x: f64| x.to_bits() as u128;
+                    let env = || {
+                        let mut env = FpEnv::new(*mode);
+                        env.nan = nan;
+                        env
+                    };
+                    let e = f(crate::f64::round_add(a, b, mode));
+                    let r = add_env(&F64, ba, bb, &mut env());
+                    assert_eq!(r, e, "{a:e} + {b:e} {mode:?} {nan:?}");
+                    let e = f(crate::f64::round_sub(a, b, mode));
+                    let r = sub_env(&F64, ba, bb, &mut env());
+                    assert_eq!(r, e, "{a:e} - {b:e} {mode:?} {nan:?}");
+                    let e = f(crate::f64::round_mul(a, b, mode));
+                    let r = mul_env(&F64, ba, bb, &mut env());
+                    assert_eq!(r, e, "{a:e} * {b:e} {mode:?} {nan:?}");
+                    let e = f(crate::f64::round_div(a, b, mode));
+                    let r = div_env(&F64, ba, bb, &mut env());
+                    assert_eq!(r, e, "{a:e} / {b:e} {mode:?} {nan:?}");
+                    let e = f(crate::f64::round_sqrt(a, mode));
+                    let r = sqrt_env(&F64, ba, &mut env());
+                    assert_eq!(r, e, "sqrt {a:e} {mode:?} {nan:?}");
+                    let e = f(crate::f64::round_mul_add(a, b, c, mode));
+                    let r = mul_add_env(&F64, ba, bb, bc, &mut env());
+                    assert_eq!(r, e, "{a:e} * {b:e} + {c:e} {mode:?} {nan:?}");
+                }
+            }
+        }
+        crate::set_softfloat_nan_semantics(None);
+    }
+
+    #[test]
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    fn test_nan() {
+        let qnan = 0x7FF8_0000_0000_0001;
+        let snan = 0x7FF0_0000_0000_0001;
+        let one = 1f64.to_bits() as u128;
+        let inf = f64::INFINITY.to_bits() as u128;
+        let mode = &Mode::NearestTiesEven;
+
+        assert_eq!(add(&F64, inf, inf | F64.sign_mask(), mode), 0xFFF8 << 48);
+        assert_eq!(add(&F64, snan, one, mode), qnan);
+        assert_eq!(
+            add(&F64, one, snan | F64.sign_mask(), mode),
+            qnan | F64.sign_mask()
+        );
+        assert_eq!(sub(&F64, one, qnan, mode), qnan);
+        assert_eq!(sqrt(&F64, snan, mode), qnan);
+        assert_eq!(mul_add(&F64, inf, 0, qnan, mode), 0xFFF8 << 48);
+        assert_eq!(mul_add(&F64, one, one, snan, mode), qnan);
+    }
+
+    #[test]
+    fn test_nan_semantics() {
+        use crate::{NanSemantics, Operation};
+
+        let qnan = 0x7FF8_0000_0000_0001;
+        let snan = 0xFFF0_0000_0000_0002;
+        let one = 1f64.to_bits() as u128;
+        let inf = f64::INFINITY.to_bits() as u128;
+        let values = [qnan, snan, one, 0, inf, inf | F64.sign_mask()];
+
+        let env = |nan| {
+            let mut env = FpEnv::default();
+            env.nan = nan;
+            env
+        };
+
+        for nan in NanSemantics::ALL {
+            let default = match nan {
+                NanSemantics::X86 => 0xFFF8 << 48,
+                _ => 0x7FF8 << 48,
+            };
+            assert_eq!(sub_env(&F64, inf, inf, &mut env(nan)), default);
+            assert_eq!(
+                sqrt_env(&F64, one | F64.sign_mask(), &mut env(nan)),
+                default
+            );
+
+            let e = match nan {
+                NanSemantics::X86 => qnan,
+                NanSemantics::PreservePayload => snan | F64.quiet_bit(),
+                _ => default,
+            };
+            assert_eq!(add_env(&F64, qnan, snan, &mut env(nan)), e);
+
+            // the same as the NaN results of the ops
+            for &a in &values {
+                let r = sqrt_env(&F64, a, &mut env(nan));
+                if F64.is_nan(r) {
+                    assert_eq!(r, F64.nan_result(Operation::Sqrt, &[a], nan));
+                }
+                for &b in &values {
+                    for (op, f) in [
+                        (
+                            Operation::Add,
+                            add_env as fn(&Format, u128, u128, &mut FpEnv) -> u128,
+                        ),
+                        (Operation::Sub, sub_env),
+                        (Operation::Mul, mul_env),
+                        (Operation::Div, div_env),
+                    ] {
+                        let r = f(&F64, a, b, &mut env(nan));
+                        if F64.is_nan(r) {
+                            assert_eq!(r, F64.nan_result(op, &[a, b], nan), "{op} {a:x} {b:x}");
+                        }
+                    }
+                    for &c in &values {
+                        let r = mul_add_env(&F64, a, b, c, &mut env(nan));
+                        if F64.is_nan(r) {
+                            assert_eq!(r, F64.nan_result(Operation::MulAdd, &[a, b, c], nan));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_zero_sign() {
+        let one = 1f64.to_bits() as u128;
+        for mode in &MODES {
+            let e = match mode {
+                Mode::TowardNegInf => F64.sign_mask(),
+                _ => 0,
+            };
+            assert_eq!(sub(&F64, one, one, mode), e);
+            assert_eq!(add(&F64, 0, F64.sign_mask(), mode), e);
+            assert_eq!(mul_add(&F64, one, one, one | F64.sign_mask(), mode), e);
+        }
+    }
+}
@@ -0,0 +1,281 @@
+//! Directed rounding by SSE instructions under MXCSR rounding control, on x86_64.
+//!
+//! Each op saves MXCSR, sets its rounding control, runs the instruction
+//! and restores the rounding control in a single `asm!` block,
+//! thus the compiler cannot fold or move the op out of the mode.
+//! Exception flags raised by the op are kept in MXCSR, as `<fenv.h>` does.
+
+#[cfg(feature = "f32_asm")]
+pub(crate) mod f32;
+#[cfg(feature = "f64_asm")]
+pub(crate) mod f64;
+
+use crate::RoundingMode;
+
+/// Returns the rounding control bits of MXCSR.
+#[inline(always)]
+pub(crate) fn rounding_control(mode: &RoundingMode) -> u32 {
+    match mode {
+        RoundingMode::NearestTiesEven => 0x0000,
+        RoundingMode::TowardNegInf => 0x2000,
+        RoundingMode::TowardPosInf => 0x4000,
+        RoundingMode::TowardZero => 0x6000,
+    }
+}
+
+/// Runs the instruction `$op` with the rounding control of `$mode`.
+macro_rules! with_mxcsr {
+    ($mode:expr, $op:expr, $($args:tt)*) => {{
+        let mut csr = [0u32; 2];
+        unsafe {
+            core::arch::asm!(
+                "stmxcsr [{csr}]",
+                "mov {tmp:e}, [{csr}]",
+                "and {tmp:e}, 0xFFFF9FFF",
+                "or {tmp:e}, {rc:e}",
+                "mov [{csr} + 4], {tmp:e}",
+                "ldmxcsr [{csr} + 4]",
+                $op,
+                // restores the rounding control, and keeps flags
+                "stmxcsr [{csr} + 4]",
+                "mov {tmp:e}, [{csr} + 4]",
+                "and {tmp:e}, 0xFFFF9FFF",
+                "mov {rc:e}, [{csr}]",
+                "and {rc:e}, 0x6000",
+                "or {tmp:e}, {rc:e}",
+                "mov [{csr} + 4], {tmp:e}",
+                "ldmxcsr [{csr} + 4]",
+                csr = in(reg) csr.as_mut_ptr(),
+                tmp = out(reg) _,
+                rc = inout(reg) crate::r#impl::asm::rounding_control($mode) => _,
+                $($args)*
+                options(nostack),
+            );
+        }
+    }};
+}
+
+#[cfg(any(feature = "f32_asm", feature = "f64_asm"))]
+macro_rules! impl_asm {
+    ($ty:ident, $fmt:ident, $suffix:literal) => {
+        use crate::r#impl::asm::with_mxcsr;
+        use crate::RoundingMode;
+
+        /// Returns `a + b` as specific rounding mode.
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_add(mut a: $ty, b: $ty, mode: &RoundingMode) -> $ty {
+            with_mxcsr!(
+                mode,
+                concat!("add", $suffix, " {a}, {b}"),
+                a = inout(xmm_reg) a,
+                b = in(xmm_reg) b,
+            );
+            a
+        }
+
+        /// Returns `a - b` as specific rounding mode.
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_sub(mut a: $ty, b: $ty, mode: &RoundingMode) -> $ty {
+            with_mxcsr!(
+                mode,
+                concat!("sub", $suffix, " {a}, {b}"),
+                a = inout(xmm_reg) a,
+                b = in(xmm_reg) b,
+            );
+            a
+        }
+
+        /// Returns `a * b` as specific rounding mode.
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_mul(mut a: $ty, b: $ty, mode: &RoundingMode) -> $ty {
+            with_mxcsr!(
+                mode,
+                concat!("mul", $suffix, " {a}, {b}"),
+                a = inout(xmm_reg) a,
+                b = in(xmm_reg) b,
+            );
+            a
+        }
+
+        /// Returns `a / b` as specific rounding mode.
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_div(mut a: $ty, b: $ty, mode: &RoundingMode) -> $ty {
+            with_mxcsr!(
+                mode,
+                concat!("div", $suffix, " {a}, {b}"),
+                a = inout(xmm_reg) a,
+                b = in(xmm_reg) b,
+            );
+            a
+        }
+
+        /// Returns `a * b + c` with single rounding (fused multiply-add) as specific rounding mode.
+        ///
+        /// It falls back to the integer implementation when the CPU does not support FMA.
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_mul_add(mut a: $ty, b: $ty, c: $ty, mode: &RoundingMode) -> $ty {
+            if std::arch::is_x86_feature_detected!("fma") {
+                with_mxcsr!(
+                    mode,
+                    concat!("vfmadd213", $suffix, " {a}, {b}, {c}"),
+                    a = inout(xmm_reg) a,
+                    b = in(xmm_reg) b,
+                    c = in(xmm_reg) c,
+                );
+                a
+            } else {
+                use crate::r#impl::format::$fmt;
+
+                let r = crate::r#impl::arith::mul_add(
+                    &$fmt,
+                    a.to_bits() as u128,
+                    b.to_bits() as u128,
+                    c.to_bits() as u128,
+                    mode,
+                );
+                <$ty>::from_bits(r as _)
+            }
+        }
+
+        /// Returns `a.sqrt()` as specific rounding mode.
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_sqrt(mut a: $ty, mode: &RoundingMode) -> $ty {
+            with_mxcsr!(
+                mode,
+                concat!("sqrt", $suffix, " {a}, {a}"),
+                a = inout(xmm_reg) a,
+            );
+            a
+        }
+    };
+}
+
+#[cfg(any(feature = "f32_asm", feature = "f64_asm"))]
+pub(crate) use impl_asm;
+pub(crate) use with_mxcsr;
+
+#[cfg(all(test, any(feature = "f32_asm", feature = "f64_asm")))]
+mod test {
+    use crate::r#impl::arith;
+    use crate::r#impl::format::Unpacked;
+    use crate::RoundingMode as Mode;
+
+    const MODES: [Mode; 4] = [
+        Mode::NearestTiesEven,
+        Mode::TowardPosInf,
+        Mode::TowardNegInf,
+        Mode::TowardZero,
+    ];
+
+    fn xorshift(x: &mut u64) -> u64 {
+        *x ^= *x << 13;
+        *x ^= *x >> 7;
+        *x ^= *x << 17;
+        *x
+    }
+
+    macro_rules! check {
+        ($ty:ident, $fmt:ident, $next:expr) => {{
+            use super::$ty::*;
+            use crate::r#impl::format::$fmt;
+
+            // NaN payloads follow the hardware
+            let f = |x: $ty| {
+                if x.is_nan() {
+                    $fmt.nan()
+                } else {
+                    x.to_bits() as u128
+                }
+            };
+            let g = |x: u128| {
+                if $fmt.unpack(x).1 == Unpacked::Nan {
+                    $fmt.nan()
+                } else {
+                    x
+                }
+            };
+            let mut next = $next;
+            for _ in 0..20000 {
+                let (a, b, c) = (next(), next(), next());
+                let (ba, bb, bc) = (
+                    a.to_bits() as u128,
+                    b.to_bits() as u128,
+                    c.to_bits() as u128,
+                );
+                for mode in &MODES {
+                    assert_eq!(f(round_add(a, b, mode)), g(arith::add(&$fmt, ba, bb, mode)));
+                    assert_eq!(f(round_sub(a, b, mode)), g(arith::sub(&$fmt, ba, bb, mode)));
+                    assert_eq!(f(round_mul(a, b, mode)), g(arith::mul(&$fmt, ba, bb, mode)));
+                    assert_eq!(f(round_div(a, b, mode)), g(arith::div(&$fmt, ba, bb, mode)));
+                    assert_eq!(f(round_sqrt(a, mode)), g(arith::sqrt(&$fmt, ba, mode)));
+                    assert_eq!(
+                        f(round_mul_add(a, b, c, mode)),
+                        g(arith::mul_add(&$fmt, ba, bb, bc, mode))
+                    );
+                }
+            }
+        }};
+    }
+
+    #[test]
+    #[cfg(feature = "f64_asm")]
+    fn test_f64() {
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        check!(f64, F64, || {
+            let r = xorshift(&mut x);
+            match r % 4 {
+                0 => f64::from_bits(r),
+                1 => (r % 1024) as f64 / 64.0,
+                _ => f64::from_bits(r & 0x800F_FFFF_FFFF_FFFF | 0x3FF0_0000_0000_0000),
+            }
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "f32_asm")]
+    fn test_f32() {
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        check!(f32, F32, || {
+            let r = xorshift(&mut x);
+            match r % 4 {
+                0 => f32::from_bits(r as u32),
+                1 => (r % 1024) as f32 / 64.0,
+                _ => f32::from_bits(r as u32 & 0x807F_FFFF | 0x3F80_0000),
+            }
+        });
+    }
+
+    #[test]
+    fn test_mode_restored() {
+        // the rounding control of MXCSR is to nearest after each op
+        let rc = || {
+            let mut csr = 0u32;
+            unsafe {
+                core::arch::asm!("stmxcsr [{csr}]", csr = in(reg) &mut csr, options(nostack));
+            }
+            csr & 0x6000
+        };
+        for mode in &MODES {
+            #[cfg(feature = "f64_asm")]
+            {
+                let _ = super::f64::round_add(0.1, 0.2, mode);
+                assert_eq!(rc(), 0, "f64 {mode:?}");
+                let _ = super::f64::round_sqrt(2.0, mode);
+                assert_eq!(rc(), 0, "f64 {mode:?}");
+            }
+            #[cfg(feature = "f32_asm")]
+            {
+                let _ = super::f32::round_add(0.1, 0.2, mode);
+                assert_eq!(rc(), 0, "f32 {mode:?}");
+                let _ = super::f32::round_mul_add(0.1, 0.2, 0.3, mode);
+                assert_eq!(rc(), 0, "f32 {mode:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,4 @@
+crate::r#impl::asm::impl_asm!(f32, F32, "ss");
+
+#[cfg(feature = "rayon")]
+crate::r#impl::par::impl_round_slice!(f32);
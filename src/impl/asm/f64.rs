@@ -0,0 +1,4 @@
+crate::r#impl::asm::impl_asm!(f64, F64, "sd");
+
+#[cfg(feature = "rayon")]
+crate::r#impl::par::impl_round_slice!(f64);
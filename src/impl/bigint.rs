@@ -0,0 +1,283 @@
+//! Fixed-capacity unsigned big integer.
+//!
+//! This is the minimum set of operations for exact decimal <-> binary conversion,
+//! it does not allocate and all operations are `const`, so that it is usable at compile time.
+//! The capacity `N` (the number of 32-bit limbs) is chosen by the caller.
+//! Operations overflowing the capacity panic.
+
+use core::cmp::Ordering;
+
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Big<const N: usize> {
+    /// Little-endian limbs, `limbs[len..]` are always zero.
+    limbs: [u32; N],
+    len: usize,
+}
+
+impl<const N: usize> Big<N> {
+    pub(crate) const fn zero() -> Self {
+        Self {
+            limbs: [0; N],
+            len: 0,
+        }
+    }
+
+    pub(crate) const fn from_u128(mut value: u128) -> Self {
+        let mut r = Self::zero();
+        while value != 0 {
+            r.limbs[r.len] = value as u32;
+            r.len += 1;
+            value >>= 32;
+        }
+        r
+    }
+
+    /// Returns `10^exp`.
+    pub(crate) const fn pow10(exp: u32) -> Self {
+        let mut r = Self::from_u128(1);
+        r.mul_pow10(exp);
+        r
+    }
+
+    pub(crate) const fn is_zero(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of significant bits.
+    pub(crate) const fn bit_len(&self) -> u32 {
+        match self.len {
+            0 => 0,
+            n => 32 * n as u32 - self.limbs[n - 1].leading_zeros(),
+        }
+    }
+
+    /// Returns the `i`-th bit.
+    pub(crate) const fn bit(&self, i: u32) -> bool {
+        let (q, r) = ((i / 32) as usize, i % 32);
+        q < self.len && (self.limbs[q] >> r) & 1 == 1
+    }
+
+    /// Returns `true` if any bit below the `i`-th bit is set.
+    pub(crate) const fn any_below(&self, i: u32) -> bool {
+        let (q, r) = ((i / 32) as usize, i % 32);
+        let mut k = 0;
+        while k < q && k < self.len {
+            if self.limbs[k] != 0 {
+                return true;
+            }
+            k += 1;
+        }
+        q < self.len && r != 0 && self.limbs[q] << (32 - r) != 0
+    }
+
+    /// Returns bits from `lo` (inclusive) to `lo + 128` (exclusive) as [`u128`].
+    pub(crate) const fn bits_u128(&self, lo: u32) -> u128 {
+        let mut r = 0;
+        let mut i = 0;
+        while i < 128 {
+            if self.bit(lo + i) {
+                r |= 1 << i;
+            }
+            i += 1;
+        }
+        r
+    }
+
+    const fn trim(&mut self) {
+        while self.len > 0 && self.limbs[self.len - 1] == 0 {
+            self.len -= 1;
+        }
+    }
+
+    /// `self = self * m + a`.
+    pub(crate) const fn mul_add_small(&mut self, m: u32, a: u32) {
+        let mut carry = a as u64;
+        let mut i = 0;
+        while i < self.len {
+            let t = self.limbs[i] as u64 * m as u64 + carry;
+            self.limbs[i] = t as u32;
+            carry = t >> 32;
+            i += 1;
+        }
+        if carry != 0 {
+            self.limbs[self.len] = carry as u32;
+            self.len += 1;
+        }
+        self.trim();
+    }
+
+    /// `self = self * 10^exp`.
+    pub(crate) const fn mul_pow10(&mut self, mut exp: u32) {
+        // 10^9 < 2^32
+        while exp >= 9 {
+            self.mul_add_small(1_000_000_000, 0);
+            exp -= 9;
+        }
+        let mut m = 1;
+        while exp > 0 {
+            m *= 10;
+            exp -= 1;
+        }
+        self.mul_add_small(m, 0);
+    }
+
+    /// `self = self * 5^exp`.
+    pub(crate) const fn mul_pow5(&mut self, mut exp: u32) {
+        // 5^13 < 2^32
+        while exp >= 13 {
+            self.mul_add_small(1_220_703_125, 0);
+            exp -= 13;
+        }
+        let mut m = 1;
+        while exp > 0 {
+            m *= 5;
+            exp -= 1;
+        }
+        self.mul_add_small(m, 0);
+    }
+
+    /// `self = self / d`, returns the remainder.
+    pub(crate) const fn div_rem_small(&mut self, d: u32) -> u32 {
+        let mut rem = 0u64;
+        let mut i = self.len;
+        while i > 0 {
+            i -= 1;
+            let t = (rem << 32) | self.limbs[i] as u64;
+            self.limbs[i] = (t / d as u64) as u32;
+            rem = t % d as u64;
+        }
+        self.trim();
+        rem as u32
+    }
+
+    /// `self = self << bits`.
+    pub(crate) const fn shl(&mut self, bits: u32) {
+        if self.len == 0 {
+            return;
+        }
+        let (q, r) = ((bits / 32) as usize, bits % 32);
+        let mut i = self.len + q + 1;
+        if i > N {
+            // the top limb must be empty
+            assert!(r == 0 || self.limbs[self.len - 1] >> (32 - r) == 0);
+            i = N;
+        }
+        let len = i;
+        while i > q {
+            i -= 1;
+            let src = i - q;
+            let hi = if src < self.len {
+                self.limbs[src] << r
+            } else {
+                0
+            };
+            let lo = if r != 0 && src > 0 {
+                self.limbs[src - 1] >> (32 - r)
+            } else {
+                0
+            };
+            self.limbs[i] = hi | lo;
+        }
+        while i > 0 {
+            i -= 1;
+            self.limbs[i] = 0;
+        }
+        self.len = len;
+        self.trim();
+    }
+
+    /// `self = self >> 1`.
+    pub(crate) const fn shr1(&mut self) {
+        let mut i = 0;
+        while i < self.len {
+            let hi = if i + 1 < self.len {
+                self.limbs[i + 1] << 31
+            } else {
+                0
+            };
+            self.limbs[i] = (self.limbs[i] >> 1) | hi;
+            i += 1;
+        }
+        self.trim();
+    }
+
+    pub(crate) const fn cmp(&self, other: &Self) -> Ordering {
+        if self.len != other.len {
+            return if self.len < other.len {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            };
+        }
+        let mut i = self.len;
+        while i > 0 {
+            i -= 1;
+            if self.limbs[i] != other.limbs[i] {
+                return if self.limbs[i] < other.limbs[i] {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                };
+            }
+        }
+        Ordering::Equal
+    }
+
+    /// `self = self - other`, requires `self >= other`.
+    pub(crate) const fn sub(&mut self, other: &Self) {
+        let mut borrow = 0u64;
+        let mut i = 0;
+        while i < self.len {
+            let b = if i < other.len { other.limbs[i] } else { 0 } as u64 + borrow;
+            let a = self.limbs[i] as u64;
+            self.limbs[i] = a.wrapping_sub(b) as u32;
+            borrow = (a < b) as u64;
+            i += 1;
+        }
+        self.trim();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    type B = Big<8>;
+
+    #[test]
+    fn test_mul_div() {
+        let mut a = B::pow10(30);
+        assert_eq!(a.bits_u128(0), 10u128.pow(30));
+        a.mul_add_small(7, 3);
+        assert_eq!(a.bits_u128(0), 7 * 10u128.pow(30) + 3);
+        assert_eq!(a.div_rem_small(11), ((7 * 10u128.pow(30) + 3) % 11) as u32);
+        assert_eq!(a.bits_u128(0), (7 * 10u128.pow(30) + 3) / 11);
+
+        let mut a = B::from_u128(3);
+        a.mul_pow5(40);
+        assert_eq!(a.bits_u128(0), 3 * 5u128.pow(40));
+    }
+
+    #[test]
+    fn test_shift() {
+        let mut a = B::from_u128(0xDEAD_BEEF_1234_5678);
+        a.shl(77);
+        assert_eq!(a.bit_len(), 64 + 77);
+        assert_eq!(a.bits_u128(77), 0xDEAD_BEEF_1234_5678);
+        assert!(!a.any_below(80));
+        assert!(a.any_below(81));
+
+        a.shr1();
+        assert_eq!(a.bits_u128(76), 0xDEAD_BEEF_1234_5678);
+    }
+
+    #[test]
+    fn test_cmp_sub() {
+        let mut a = B::pow10(20);
+        let b = B::from_u128(1);
+        assert_eq!(a.cmp(&b), Ordering::Greater);
+        a.sub(&b);
+        assert_eq!(a.bits_u128(0), 10u128.pow(20) - 1);
+        assert_eq!(b.cmp(&b), Ordering::Equal);
+    }
+}
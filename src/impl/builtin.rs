@@ -2,7 +2,271 @@
 pub(crate) mod f128;
 #[cfg(feature = "f16")]
 pub(crate) mod f16;
-#[cfg(not(feature = "f32_softfloat"))]
+#[cfg(not(any(
+    feature = "f32_softfloat",
+    feature = "f32_pure_rust",
+    feature = "f32_widening",
+    all(feature = "f32_asm", target_arch = "x86_64")
+)))]
 pub(crate) mod f32;
-#[cfg(not(feature = "f64_softfloat"))]
+#[cfg(not(any(
+    feature = "f64_softfloat",
+    feature = "f64_pure_rust",
+    all(feature = "f64_asm", target_arch = "x86_64")
+)))]
 pub(crate) mod f64;
+
+/// Generates `c_add`, `c_sub` and `c_mul` compatible functions
+/// which skip switching rounding mode when the result is exact,
+/// since an exact result does not depend on the rounding mode.
+///
+/// The exactness is tested by the residual of error-free transformations
+/// (TwoSum and fused multiply-add), which is valid only if the thread's rounding mode is to nearest,
+/// thus they are used only under the mode.
+/// They are used only if neither the operands nor the residual can be subnormal,
+/// which are flushed under flush-to-zero and denormals-are-zero modes.
+/// The one of a product requires the `fma` target feature,
+/// since fused multiply-add is a call of libm without it, which is slower than switching the mode.
+#[allow(unused_macros)] // when neither f32 nor f64 uses the builtin
+macro_rules! impl_exact_fast_path {
+    ($ty:ident, $add:ident => $c_add:ident, $sub:ident => $c_sub:ident, $mul:ident => $c_mul:ident,) => {
+        /// `2^(emin + precision)`, every partial result of TwoSum of operands not less than this
+        /// is a multiple of `2^(emin + 1)`, thus it is not subnormal.
+        const TINY: $ty = <$ty>::MIN_POSITIVE / <$ty>::EPSILON * 2.0;
+        /// `2^(emin + 2 * precision)`, the residual of a product not less than this
+        /// is a multiple of `2^emin`, thus it is not subnormal.
+        #[cfg(target_feature = "fma")]
+        const TINY_PRODUCT: $ty = TINY / <$ty>::EPSILON * 2.0;
+
+        /// Returns `a + b` if it is exact and non-zero.
+        #[inline(always)]
+        fn exact_add(a: $ty, b: $ty) -> Option<$ty> {
+            if !crate::env::is_nearest() {
+                return None;
+            }
+
+            let tiny = |x: $ty| x != 0.0 && x.abs() < TINY;
+            if tiny(a) || tiny(b) {
+                return None;
+            }
+
+            // TwoSum, the residual is NaN on overflow
+            let r = a + b;
+            let bb = r - a;
+            let e = (a - (r - bb)) + (b - bb);
+            // the sign of exact 0 depends on the rounding mode
+            (e == 0.0 && r != 0.0).then_some(r)
+        }
+
+        #[inline(always)]
+        unsafe fn $add(mode: c_int, a: $ty, b: $ty, dst: *mut $ty) -> c_int {
+            match exact_add(a, b) {
+                Some(r) => {
+                    *dst = r;
+                    0
+                }
+                None => $c_add(mode, a, b, dst),
+            }
+        }
+
+        #[inline(always)]
+        unsafe fn $sub(mode: c_int, a: $ty, b: $ty, dst: *mut $ty) -> c_int {
+            match exact_add(a, -b) {
+                Some(r) => {
+                    *dst = r;
+                    0
+                }
+                None => $c_sub(mode, a, b, dst),
+            }
+        }
+
+        #[cfg(target_feature = "fma")]
+        #[inline(always)]
+        unsafe fn $mul(mode: c_int, a: $ty, b: $ty, dst: *mut $ty) -> c_int {
+            let r = a * b;
+            // TINY_PRODUCT <= |r| < inf, and the residual is 0
+            if crate::env::is_nearest()
+                && (TINY_PRODUCT..=<$ty>::MAX).contains(&r.abs())
+                && a.mul_add(b, -r) == 0.0
+            {
+                *dst = r;
+                0
+            } else {
+                $c_mul(mode, a, b, dst)
+            }
+        }
+
+        #[cfg(not(target_feature = "fma"))]
+        #[inline(always)]
+        unsafe fn $mul(mode: c_int, a: $ty, b: $ty, dst: *mut $ty) -> c_int {
+            $c_mul(mode, a, b, dst)
+        }
+    };
+}
+
+#[allow(unused_imports)]
+pub(crate) use impl_exact_fast_path;
+
+#[cfg(all(test, not(any(feature = "f64_softfloat", feature = "f64_pure_rust"))))]
+#[cfg(not(all(feature = "f64_asm", target_arch = "x86_64")))]
+mod test {
+    use crate::f64::{round_add, round_mul, round_sub};
+    use crate::RoundingMode as Mode;
+
+    fn xorshift(x: &mut u64) -> u64 {
+        *x ^= *x << 13;
+        *x ^= *x >> 7;
+        *x ^= *x << 17;
+        *x
+    }
+
+    #[test]
+    fn test_exact_fast_path() {
+        for mode in [
+            Mode::NearestTiesEven,
+            Mode::TowardPosInf,
+            Mode::TowardNegInf,
+            Mode::TowardZero,
+        ] {
+            assert_eq!(round_add(1.0, 2.0, &mode), 3.0);
+            assert_eq!(round_sub(1.0, 0.25, &mode), 0.75);
+            assert_eq!(round_mul(3.0, 0.5, &mode), 1.5);
+            assert_eq!(round_mul(f64::MAX, 0.5, &mode), f64::MAX / 2.0);
+        }
+
+        // exact 0
+        assert!(round_sub(1.0, 1.0, &Mode::TowardNegInf).is_sign_negative());
+        assert!(round_sub(1.0, 1.0, &Mode::TowardPosInf).is_sign_positive());
+
+        // inexact
+        assert_eq!(
+            round_add(1.0, 1e-20, &Mode::TowardPosInf),
+            1.0000000000000002
+        );
+        assert_eq!(round_mul(f64::MAX, 2.0, &Mode::TowardZero), f64::MAX);
+        assert_eq!(
+            round_mul(1e-200, 1e-200, &Mode::TowardPosInf),
+            f64::from_bits(1)
+        );
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn test_exact_fast_path_denormal() {
+        use crate::f64::{const_round_add, const_round_mul};
+        use crate::{with_denormal_mode, DenormalMode};
+
+        // the residuals of ops close to underflow can be subnormal, which are flushed,
+        // then the ops must not take the fast path on them
+        let (a, b) = (1.0 + f64::EPSILON, 2f64.powi(-969) * (1.0 + f64::EPSILON));
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        for denormal in [DenormalMode::FTZ, DenormalMode::DAZ, DenormalMode::FTZ_DAZ] {
+            with_denormal_mode(&denormal, || {
+                assert_eq!(round_mul(a, b, &Mode::TowardPosInf), 2.004168360008974e-292);
+
+                for _ in 0..20000 {
+                    // normal operands and results close to underflow
+                    let r = xorshift(&mut x);
+                    let a = f64::from_bits(r & 0x800F_FFFF_FFFF_FFFF | 0x3FF0_0000_0000_0000);
+                    let exp = (r >> 52) % 128 + 1;
+                    let b = f64::from_bits(xorshift(&mut x) & 0x000F_FFFF_FFFF_FFFF | exp << 52);
+                    let c = f64::from_bits(xorshift(&mut x) & 0x000F_FFFF_FFFF_FFFF | exp << 52);
+                    for mode in &Mode::ALL {
+                        let f = |x: f64| x.to_bits();
+                        assert_eq!(
+                            f(round_mul(a, b, mode)),
+                            f(const_round_mul(a, b, mode)),
+                            "{a:e} * {b:e} {mode:?} under {denormal:?}"
+                        );
+                        assert_eq!(
+                            f(round_add(b, c, mode)),
+                            f(const_round_add(b, c, mode)),
+                            "{b:e} + {c:e} {mode:?} under {denormal:?}"
+                        );
+                    }
+                }
+            });
+        }
+    }
+
+    /// Compares the ops of `$ty` with `const_round_*` under every thread's mode other than to nearest.
+    macro_rules! check_thread_mode {
+        ($ty:ident, $next:expr) => {{
+            use crate::$ty::*;
+
+            let f = |x: $ty| x.to_bits();
+            let mut next = $next;
+            for thread in [Mode::TowardPosInf, Mode::TowardNegInf, Mode::TowardZero] {
+                crate::with_rounding_mode(&thread, || {
+                    for _ in 0..20000 {
+                        let (a, b) = (next(), next());
+                        for mode in &Mode::ALL {
+                            assert_eq!(
+                                f(round_add(a, b, mode)),
+                                f(const_round_add(a, b, mode)),
+                                "{a:e} + {b:e} {mode:?} under {thread:?}"
+                            );
+                            assert_eq!(
+                                f(round_sub(a, b, mode)),
+                                f(const_round_sub(a, b, mode)),
+                                "{a:e} - {b:e} {mode:?} under {thread:?}"
+                            );
+                            assert_eq!(
+                                f(round_mul(a, b, mode)),
+                                f(const_round_mul(a, b, mode)),
+                                "{a:e} * {b:e} {mode:?} under {thread:?}"
+                            );
+                        }
+                    }
+                });
+            }
+        }};
+    }
+
+    #[test]
+    fn test_exact_fast_path_thread_mode() {
+        // the residuals of Rust's float ops are not valid under the thread's mode
+        // other than to nearest, then the ops must not take the fast path
+        crate::with_rounding_mode(&Mode::TowardPosInf, || {
+            assert_eq!(
+                crate::f64::floor_add(-3.058781940549444e-300, 4.253831516829477e-133),
+                4.253831516829476e-133
+            );
+        });
+
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        check_thread_mode!(f64, || {
+            let r = xorshift(&mut x);
+            match r % 4 {
+                0 | 1 => match f64::from_bits(r) {
+                    x if x.is_nan() => 1.0,
+                    x => x,
+                },
+                2 => (r % 1024) as f64 / 64.0,
+                _ => f64::from_bits(r & 0x800F_FFFF_FFFF_FFFF | 0x3FF0_0000_0000_0000),
+            }
+        });
+    }
+
+    #[test]
+    #[cfg(not(any(
+        feature = "f32_softfloat",
+        feature = "f32_pure_rust",
+        feature = "f32_widening",
+        all(feature = "f32_asm", target_arch = "x86_64")
+    )))]
+    fn test_exact_fast_path_thread_mode_f32() {
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        check_thread_mode!(f32, || {
+            let r = xorshift(&mut x);
+            match r % 4 {
+                0 | 1 => match f32::from_bits(r as u32) {
+                    x if x.is_nan() => 1.0,
+                    x => x,
+                },
+                2 => (r % 1024) as f32 / 64.0,
+                _ => f32::from_bits(r as u32 & 0x807F_FFFF | 0x3F80_0000),
+            }
+        });
+    }
+}
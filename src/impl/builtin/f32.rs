@@ -1,6 +1,7 @@
 use std::ffi::c_int;
 
 use crate::internal::*;
+use crate::r#impl::builtin::impl_exact_fast_path;
 use crate::RoundingMode;
 
 extern "C" {
@@ -12,11 +13,29 @@ extern "C" {
     fn c_sqrt_f32(mode: c_int, a: f32, dst: *mut f32) -> c_int;
 }
 
+#[cfg(feature = "rayon")]
+crate::r#impl::par::impl_round_slice!(
+    f32,
+    c_add_f32_slice,
+    c_sub_f32_slice,
+    c_mul_f32_slice,
+    c_div_f32_slice,
+    c_fma_f32_slice,
+    c_sqrt_f32_slice
+);
+
+impl_exact_fast_path!(
+    f32,
+    add_f32 => c_add_f32,
+    sub_f32 => c_sub_f32,
+    mul_f32 => c_mul_f32,
+);
+
 impl_round_func_binary_all!(
     f32,
-    round_add => c_add_f32,
-    round_sub => c_sub_f32,
-    round_mul => c_mul_f32,
+    round_add => add_f32,
+    round_sub => sub_f32,
+    round_mul => mul_f32,
     round_div => c_div_f32,
     round_mul_add => c_fma_f32,
 );
@@ -1,6 +1,7 @@
 use std::ffi::c_int;
 
 use crate::internal::*;
+use crate::r#impl::builtin::impl_exact_fast_path;
 use crate::RoundingMode;
 
 extern "C" {
@@ -12,11 +13,29 @@ extern "C" {
     fn c_sqrt_f64(mode: c_int, a: f64, dst: *mut f64) -> c_int;
 }
 
+#[cfg(feature = "rayon")]
+crate::r#impl::par::impl_round_slice!(
+    f64,
+    c_add_f64_slice,
+    c_sub_f64_slice,
+    c_mul_f64_slice,
+    c_div_f64_slice,
+    c_fma_f64_slice,
+    c_sqrt_f64_slice
+);
+
+impl_exact_fast_path!(
+    f64,
+    add_f64 => c_add_f64,
+    sub_f64 => c_sub_f64,
+    mul_f64 => c_mul_f64,
+);
+
 impl_round_func_binary_all!(
     f64,
-    round_add => c_add_f64,
-    round_sub => c_sub_f64,
-    round_mul => c_mul_f64,
+    round_add => add_f64,
+    round_sub => sub_f64,
+    round_mul => mul_f64,
     round_div => c_div_f64,
     round_mul_add => c_fma_f64,
 );
@@ -0,0 +1,47 @@
+//! Helpers of error-free transformations.
+
+/// Generates helpers of error-free transformations for `f32` or `f64`.
+macro_rules! impl_eft {
+    ($ty:ident) => {
+        /// `2^(emin + precision)`, the residual of an op whose result is greater than this does not underflow.
+        const TINY: $ty = <$ty>::MIN_POSITIVE / <$ty>::EPSILON * 2.0;
+        /// The result of an op on operands less than this does not overflow, even if it steps an ulp.
+        const HUGE: $ty = <$ty>::MAX / 4.0;
+
+        /// Returns the next number toward +∞ of non-zero finite `x`.
+        #[inline(always)]
+        fn next_up(x: $ty) -> $ty {
+            if x > 0.0 {
+                <$ty>::from_bits(x.to_bits() + 1)
+            } else {
+                <$ty>::from_bits(x.to_bits() - 1)
+            }
+        }
+
+        /// Returns the next number toward -∞ of non-zero finite `x`.
+        #[inline(always)]
+        fn next_down(x: $ty) -> $ty {
+            -next_up(-x)
+        }
+
+        /// Returns `(r, e)` where `r` is `a + b` rounded to nearest and `r + e = a + b` exactly.
+        ///
+        /// It is valid only if the thread's rounding mode is to nearest.
+        #[inline(always)]
+        fn two_sum(a: $ty, b: $ty) -> ($ty, $ty) {
+            let r = a + b;
+            let bb = r - a;
+            let e = (a - (r - bb)) + (b - bb);
+            (r, e)
+        }
+
+        /// Returns `true` if `x` is a non-zero finite number and not greater than [`HUGE`].
+        #[inline(always)]
+        fn is_safe(x: $ty) -> bool {
+            // false on zero, inf and NaN
+            0.0 < x.abs() && x.abs() <= HUGE
+        }
+    };
+}
+
+pub(crate) use impl_eft;
@@ -88,6 +88,12 @@ impl_func_unary!(
     => f128, trunc_sqrt, round_sqrt, TowardZero
 );
 
+impl_round_parse!(f128, F128);
+impl_round_format!(f128);
+impl_hex!(f128, F128);
+impl_round_bounds!(f128);
+impl_round_to!(f128);
+
 impl RoundingArithmetic for f128 {
     type Output = Self;
 
@@ -189,3 +195,48 @@ impl TruncMath for f128 {
 }
 
 // TODO: add test when f128 support eq
+
+#[cfg(test)]
+mod test_fn_hex {
+    use crate::RoundingMode as Mode;
+
+    use super::*;
+
+    #[test]
+    fn test_to_hex_string() {
+        assert_eq!(to_hex_string(1.0), "0x1p+0");
+        assert_eq!(to_hex_string(-2.5), "-0x1.4p+1");
+        assert_eq!(
+            to_hex_string(f128::MAX),
+            "0x1.ffffffffffffffffffffffffffffp+16383"
+        );
+        assert_eq!(
+            to_hex_string(f128::from_bits(1)),
+            "0x0.0000000000000000000000000001p-16382"
+        );
+    }
+
+    #[test]
+    fn test_from_hex_str() {
+        for a in [
+            1.0,
+            -2.5,
+            0.1,
+            f128::MAX,
+            f128::MIN_POSITIVE,
+            f128::from_bits(1),
+        ] {
+            let r = from_hex_str(&to_hex_string(a), &Mode::NearestTiesEven).unwrap();
+            assert_eq!(r.to_bits(), a.to_bits());
+        }
+
+        // 1 + 2^-113
+        let s = "0x1.00000000000000000000000000008p+0";
+        let e = 1.0f128.to_bits();
+        let r = |mode| from_hex_str(s, mode).unwrap().to_bits();
+        assert_eq!(r(&Mode::NearestTiesEven), e);
+        assert_eq!(r(&Mode::TowardPosInf), e + 1);
+        assert_eq!(r(&Mode::TowardNegInf), e);
+        assert_eq!(r(&Mode::TowardZero), e);
+    }
+}
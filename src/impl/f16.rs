@@ -10,6 +10,8 @@ use crate::{RoundingArithmetic, RoundingMath};
 pub use crate::r#impl::builtin::f16::*;
 #[cfg(feature = "f16_softfloat")]
 pub use crate::r#impl::softfloat::f16::*;
+#[cfg(feature = "f16_widening")]
+pub use crate::r#impl::widening::f16::*;
 
 impl_non_round_func_binary_all!(
     f16, NearestTiesEven, "to nearest, ties to even",
@@ -88,6 +90,15 @@ impl_func_unary!(
     => f16, trunc_sqrt, round_sqrt, TowardZero
 );
 
+impl_round_parse!(f16, F16);
+impl_round_format!(f16);
+impl_hex!(f16, F16);
+impl_round_bounds!(f16);
+impl_round_to!(f16);
+
+#[cfg(feature = "stochastic")]
+crate::r#impl::stochastic::impl_stochastic!(f16);
+
 impl RoundingArithmetic for f16 {
     type Output = Self;
 
@@ -8,8 +8,16 @@ use crate::{CielArithmetic, FloorArithmetic, RoundTiesEvenArithmetic, TruncArith
 use crate::{CielMath, FloorMath, RoundTiesEvenMath, TruncMath};
 use crate::{RoundingArithmetic, RoundingMath};
 
-#[cfg(not(feature = "f64_softfloat"))]
+#[cfg(all(feature = "f64_asm", target_arch = "x86_64"))]
+pub use crate::r#impl::asm::f64::*;
+#[cfg(not(any(
+    feature = "f64_softfloat",
+    feature = "f64_pure_rust",
+    all(feature = "f64_asm", target_arch = "x86_64")
+)))]
 pub use crate::r#impl::builtin::f64::*;
+#[cfg(feature = "f64_pure_rust")]
+pub use crate::r#impl::pure_rust::f64::*;
 #[cfg(feature = "f64_softfloat")]
 pub use crate::r#impl::softfloat::f64::*;
 
@@ -90,6 +98,18 @@ impl_func_unary!(
     => f64, trunc_sqrt, round_sqrt, TowardZero
 );
 
+impl_round_parse!(f64, F64);
+impl_round_format!(f64);
+impl_hex!(f64, F64);
+impl_round_bounds!(f64, eft);
+impl_round_to!(f64);
+
+#[cfg(feature = "rayon")]
+crate::r#impl::par::impl_par_round_slice!(f64);
+impl_const_arith!(f64, F64);
+#[cfg(feature = "stochastic")]
+crate::r#impl::stochastic::impl_stochastic!(f64);
+
 impl RoundingArithmetic for f64 {
     type Output = Self;
 
@@ -413,6 +433,463 @@ mod test_fn_arith_mono {
     }
 }
 
+#[cfg(test)]
+mod test_fn_parse {
+    use crate::ParseErrorKind;
+    use crate::RoundingMode as Mode;
+
+    use super::*;
+
+    #[test]
+    fn test_round_parse() {
+        let e: f64 = 0.1;
+        assert_eq!(round_parse("0.1", &Mode::NearestTiesEven), Ok(e));
+        assert_eq!(round_parse("0.1", &Mode::TowardPosInf), Ok(e));
+        assert_eq!(round_parse("0.1", &Mode::TowardNegInf), Ok(e.next_down()));
+        assert_eq!(round_parse("0.1", &Mode::TowardZero), Ok(e.next_down()));
+
+        let e: f64 = -0.1;
+        assert_eq!(round_parse("-0.1", &Mode::NearestTiesEven), Ok(e));
+        assert_eq!(round_parse("-0.1", &Mode::TowardPosInf), Ok(e.next_up()));
+        assert_eq!(round_parse("-0.1", &Mode::TowardNegInf), Ok(e));
+        assert_eq!(round_parse("-0.1", &Mode::TowardZero), Ok(e.next_up()));
+    }
+
+    #[test]
+    fn test_round_parse_nearest() {
+        for s in [
+            "0",
+            "-0",
+            "1",
+            "1.5",
+            "3.14159265358979323846264338327950288",
+            "1e10",
+            "1.e-5",
+            ".5",
+            "+2.5E+3",
+            "123456789012345678901234567890",
+            "0.000000000000000000001",
+            "1e-40",
+            "1e-310",
+            "4.9406564584124654e-324",
+            "2.4703282292062327e-324",
+            "1.7976931348623157e308",
+            "3.4028235e38",
+            "1.401298464324817e-45",
+            "7.006492321624085354618647916449580656401309709382578858785341419448955413429303e-46",
+        ] {
+            let e: f64 = s.parse().unwrap();
+            let r = round_parse(s, &Mode::NearestTiesEven).unwrap();
+            assert_eq!(r.to_bits(), e.to_bits(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_round_parse_inexact() {
+        assert_eq!(
+            round_parse_inexact("0.5", &Mode::TowardPosInf),
+            Ok((0.5, false))
+        );
+        assert_eq!(
+            round_parse_inexact("1e3", &Mode::TowardPosInf),
+            Ok((1000.0, false))
+        );
+        assert_eq!(
+            round_parse_inexact("0.1", &Mode::TowardPosInf),
+            Ok((0.1, true))
+        );
+    }
+
+    #[test]
+    fn test_round_parse_overflow() {
+        let s = "1e400000";
+        assert_eq!(round_parse(s, &Mode::NearestTiesEven), Ok(f64::INFINITY));
+        assert_eq!(round_parse(s, &Mode::TowardPosInf), Ok(f64::INFINITY));
+        assert_eq!(round_parse(s, &Mode::TowardNegInf), Ok(f64::MAX));
+        assert_eq!(round_parse(s, &Mode::TowardZero), Ok(f64::MAX));
+
+        let s = "1e-400000";
+        let tiny = f64::from_bits(1);
+        assert_eq!(round_parse(s, &Mode::NearestTiesEven), Ok(0.0));
+        assert_eq!(round_parse(s, &Mode::TowardPosInf), Ok(tiny));
+        assert_eq!(round_parse(s, &Mode::TowardNegInf), Ok(0.0));
+        assert_eq!(round_parse(s, &Mode::TowardZero), Ok(0.0));
+        assert_eq!(round_parse("-1e-400000", &Mode::TowardNegInf), Ok(-tiny));
+    }
+
+    #[test]
+    fn test_round_parse_special() {
+        assert_eq!(round_parse("inf", &Mode::TowardZero), Ok(f64::INFINITY));
+        assert_eq!(
+            round_parse("-Infinity", &Mode::TowardZero),
+            Ok(f64::NEG_INFINITY)
+        );
+        assert!(round_parse("NaN", &Mode::TowardZero).unwrap().is_nan());
+        assert!(round_parse("-0", &Mode::TowardZero)
+            .unwrap()
+            .is_sign_negative());
+
+        for s in [
+            "", "-", ".", "e1", "1e", "1e+", "1.2.3", " 1", "1 ", "0x1p3", "infinite",
+        ] {
+            let e = round_parse(s, &Mode::NearestTiesEven).unwrap_err();
+            let kind = if s.is_empty() {
+                ParseErrorKind::Empty
+            } else {
+                ParseErrorKind::Invalid
+            };
+            assert_eq!(e.kind(), &kind, "{}", s);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_fn_format {
+    use crate::RoundingMode as Mode;
+
+    use super::*;
+
+    #[test]
+    fn test_round_format() {
+        let a: f64 = 2.0 / 3.0;
+        assert_eq!(round_format(a, 3, &Mode::NearestTiesEven), "0.667");
+        assert_eq!(round_format(a, 3, &Mode::TowardPosInf), "0.667");
+        assert_eq!(round_format(a, 3, &Mode::TowardNegInf), "0.666");
+        assert_eq!(round_format(a, 3, &Mode::TowardZero), "0.666");
+
+        let a: f64 = -2.0 / 3.0;
+        assert_eq!(round_format(a, 3, &Mode::NearestTiesEven), "-0.667");
+        assert_eq!(round_format(a, 3, &Mode::TowardPosInf), "-0.666");
+        assert_eq!(round_format(a, 3, &Mode::TowardNegInf), "-0.667");
+        assert_eq!(round_format(a, 3, &Mode::TowardZero), "-0.666");
+    }
+
+    #[test]
+    fn test_round_format_exact() {
+        let a: f64 = 1.25;
+        assert_eq!(round_format(a, 2, &Mode::TowardPosInf), "1.25");
+        assert_eq!(round_format(a, 2, &Mode::TowardNegInf), "1.25");
+        assert_eq!(round_format(a, 4, &Mode::TowardPosInf), "1.2500");
+        assert_eq!(round_format(a, 0, &Mode::TowardPosInf), "2");
+        assert_eq!(round_format(a, 0, &Mode::TowardNegInf), "1");
+    }
+
+    #[test]
+    fn test_round_format_nearest() {
+        for a in [
+            0.1,
+            0.5,
+            2.5,
+            1e-3,
+            1e10,
+            1.0 / 3.0,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+        ] {
+            for digits in [0, 1, 6, 20] {
+                let e = format!("{:.*}", digits, a);
+                assert_eq!(round_format(a, digits, &Mode::NearestTiesEven), e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_fn_hex {
+    use crate::RoundingMode as Mode;
+
+    use super::*;
+
+    #[test]
+    fn test_to_hex_string() {
+        assert_eq!(to_hex_string(0.1), "0x1.999999999999ap-4");
+        assert_eq!(to_hex_string(-1.0), "-0x1p+0");
+        assert_eq!(to_hex_string(0.0), "0x0p+0");
+    }
+
+    #[test]
+    fn test_from_hex_str() {
+        for a in [
+            0.1,
+            -1.0,
+            0.0,
+            -0.0,
+            3.0e-3,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            f64::from_bits(1),
+        ] {
+            let r = from_hex_str(&to_hex_string(a), &Mode::NearestTiesEven).unwrap();
+            assert_eq!(r.to_bits(), a.to_bits());
+        }
+
+        let s = "0x1.999999999999a8p-4";
+        let (e1, e2) = (
+            from_hex_str("0x1.999999999999ap-4", &Mode::NearestTiesEven).unwrap(),
+            from_hex_str("0x1.999999999999bp-4", &Mode::NearestTiesEven).unwrap(),
+        );
+        assert_eq!(from_hex_str(s, &Mode::NearestTiesEven), Ok(e1));
+        assert_eq!(from_hex_str(s, &Mode::TowardPosInf), Ok(e2));
+        assert_eq!(from_hex_str(s, &Mode::TowardNegInf), Ok(e1));
+        assert_eq!(from_hex_str(s, &Mode::TowardZero), Ok(e1));
+
+        assert_eq!(from_hex_str("0X1P-1", &Mode::TowardZero), Ok(0.5));
+        assert_eq!(from_hex_str("0x.8", &Mode::TowardZero), Ok(0.5));
+        assert_eq!(from_hex_str("-0x10", &Mode::TowardZero), Ok(-16.0));
+        assert_eq!(from_hex_str("0x1p99999", &Mode::TowardZero), Ok(f64::MAX));
+        assert_eq!(
+            from_hex_str("-inf", &Mode::TowardZero),
+            Ok(f64::NEG_INFINITY)
+        );
+        for s in ["", "0x", "1p3", "0x1p", "0x1.2.3", "0xg", "0x1q3"] {
+            assert!(from_hex_str(s, &Mode::TowardZero).is_err(), "{}", s);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_fn_const {
+    use crate::RoundingMode as Mode;
+
+    use super::*;
+
+    const BOUNDS: [f64; 4] = [
+        const_round_add(0.1, 0.2, &Mode::TowardNegInf),
+        const_round_add(0.1, 0.2, &Mode::TowardPosInf),
+        const_round_sqrt(2.0, &Mode::TowardNegInf),
+        const_round_sqrt(2.0, &Mode::TowardPosInf),
+    ];
+
+    #[test]
+    fn test_const() {
+        let (a, b) = (0.1, 0.2);
+        assert_eq!(BOUNDS[0], round_add(a, b, &Mode::TowardNegInf));
+        assert_eq!(BOUNDS[1], round_add(a, b, &Mode::TowardPosInf));
+        assert_eq!(BOUNDS[2], round_sqrt(2.0, &Mode::TowardNegInf));
+        assert_eq!(BOUNDS[3], round_sqrt(2.0, &Mode::TowardPosInf));
+    }
+
+    #[test]
+    fn test_identical() {
+        for (a, b, c) in [(0.1, 0.2, -0.3), (1.0, 3.0, 0.0), (-1e-30, f64::MAX, 1.0)] {
+            for mode in [
+                Mode::NearestTiesEven,
+                Mode::TowardPosInf,
+                Mode::TowardNegInf,
+                Mode::TowardZero,
+            ] {
+                assert_eq!(const_round_add(a, b, &mode), round_add(a, b, &mode));
+                assert_eq!(const_round_sub(a, b, &mode), round_sub(a, b, &mode));
+                assert_eq!(const_round_mul(a, b, &mode), round_mul(a, b, &mode));
+                assert_eq!(const_round_div(a, b, &mode), round_div(a, b, &mode));
+                assert_eq!(
+                    const_round_mul_add(a, b, c, &mode),
+                    round_mul_add(a, b, c, &mode)
+                );
+                assert_eq!(const_round_sqrt(b, &mode), round_sqrt(b, &mode));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_fn_bounds {
+    use super::*;
+
+    #[test]
+    fn test_bounds() {
+        let f = |(a, b): (f64, f64)| (a.to_bits(), b.to_bits());
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        for _ in 0..20000 {
+            let mut next = || {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                match x % 4 {
+                    0 => f64::from_bits(x),
+                    1 => (x % 1024) as f64 / 64.0,
+                    _ => f64::from_bits(x & 0x800F_FFFF_FFFF_FFFF | 0x3FF0_0000_0000_0000),
+                }
+            };
+            let (a, b, c) = (next(), next(), next());
+            assert_eq!(
+                f(round_add_bounds(a, b)),
+                f((floor_add(a, b), ciel_add(a, b)))
+            );
+            assert_eq!(
+                f(round_sub_bounds(a, b)),
+                f((floor_sub(a, b), ciel_sub(a, b)))
+            );
+            assert_eq!(
+                f(round_mul_bounds(a, b)),
+                f((floor_mul(a, b), ciel_mul(a, b)))
+            );
+            assert_eq!(
+                f(round_div_bounds(a, b)),
+                f((floor_div(a, b), ciel_div(a, b)))
+            );
+            assert_eq!(f(round_sqrt_bounds(a)), f((floor_sqrt(a), ciel_sqrt(a))));
+            assert_eq!(
+                f(round_mul_add_bounds(a, b, c)),
+                f((floor_mul_add(a, b, c), ciel_mul_add(a, b, c)))
+            );
+        }
+    }
+
+    #[test]
+    // the fallback of the backend depends on the thread's mode
+    #[cfg(not(feature = "f64_pure_rust"))]
+    fn test_bounds_thread_mode() {
+        // the residuals of Rust's float ops are not valid under the thread's mode
+        // other than to nearest, then the bounds must fall back to the directed ops
+        crate::with_rounding_mode(&RoundingMode::TowardPosInf, || {
+            assert_eq!(
+                round_add_bounds(-3.058781940549444e-300, 4.253831516829477e-133),
+                (4.253831516829476e-133, 4.253831516829477e-133)
+            );
+        });
+
+        let f = |(a, b): (f64, f64)| (a.to_bits(), b.to_bits());
+        let (lo, hi) = (&RoundingMode::TowardNegInf, &RoundingMode::TowardPosInf);
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        let mut next = || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            match x % 4 {
+                0 => match f64::from_bits(x) {
+                    x if x.is_nan() => 1.0,
+                    x => x,
+                },
+                1 => (x % 1024) as f64 / 64.0,
+                _ => f64::from_bits(x & 0x800F_FFFF_FFFF_FFFF | 0x3FF0_0000_0000_0000),
+            }
+        };
+        for thread in [
+            RoundingMode::TowardPosInf,
+            RoundingMode::TowardNegInf,
+            RoundingMode::TowardZero,
+        ] {
+            crate::with_rounding_mode(&thread, || {
+                for _ in 0..5000 {
+                    let (a, b, c) = (next(), next(), next());
+                    assert_eq!(
+                        f(round_add_bounds(a, b)),
+                        f((const_round_add(a, b, lo), const_round_add(a, b, hi))),
+                        "{a:e} + {b:e} under {thread:?}"
+                    );
+                    assert_eq!(
+                        f(round_sub_bounds(a, b)),
+                        f((const_round_sub(a, b, lo), const_round_sub(a, b, hi))),
+                        "{a:e} - {b:e} under {thread:?}"
+                    );
+                    assert_eq!(
+                        f(round_mul_bounds(a, b)),
+                        f((const_round_mul(a, b, lo), const_round_mul(a, b, hi))),
+                        "{a:e} * {b:e} under {thread:?}"
+                    );
+                    assert_eq!(
+                        f(round_div_bounds(a, b)),
+                        f((const_round_div(a, b, lo), const_round_div(a, b, hi))),
+                        "{a:e} / {b:e} under {thread:?}"
+                    );
+                    if a >= 0.0 {
+                        assert_eq!(
+                            f(round_sqrt_bounds(a)),
+                            f((const_round_sqrt(a, lo), const_round_sqrt(a, hi))),
+                            "sqrt {a:e} under {thread:?}"
+                        );
+                    }
+                    assert_eq!(
+                        f(round_mul_add_bounds(a, b, c)),
+                        f((
+                            const_round_mul_add(a, b, c, lo),
+                            const_round_mul_add(a, b, c, hi)
+                        )),
+                        "{a:e} * {b:e} + {c:e} under {thread:?}"
+                    );
+                }
+            });
+        }
+    }
+
+    #[test]
+    // pure_rust does not support flush-to-zero and denormals-are-zero modes
+    #[cfg(all(target_arch = "x86_64", not(feature = "f64_pure_rust")))]
+    fn test_bounds_denormal() {
+        use crate::{with_denormal_mode, DenormalMode};
+
+        // the residuals of ops close to underflow can be subnormal, which are flushed,
+        // then the bounds must fall back to the directed ops
+        let f = |(a, b): (f64, f64)| (a.to_bits(), b.to_bits());
+        let (lo, hi) = (&RoundingMode::TowardNegInf, &RoundingMode::TowardPosInf);
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        let mut next = || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        for denormal in [DenormalMode::FTZ, DenormalMode::DAZ, DenormalMode::FTZ_DAZ] {
+            with_denormal_mode(&denormal, || {
+                let (a, b) = (1.0 + f64::EPSILON, 2f64.powi(-969) * (1.0 + f64::EPSILON));
+                let (l, h) = round_mul_bounds(a, b);
+                assert!(l < h, "{l:e} {h:e}");
+
+                for _ in 0..5000 {
+                    // normal operands and results close to underflow
+                    let a = f64::from_bits(next() & 0x000F_FFFF_FFFF_FFFF | 0x3FF0_0000_0000_0000);
+                    let exp = next() % 128 + 2;
+                    let b = f64::from_bits(next() & 0x800F_FFFF_FFFF_FFFF | exp << 52);
+                    let c = f64::from_bits(next() & 0x000F_FFFF_FFFF_FFFF | exp << 52).copysign(b);
+                    assert_eq!(
+                        f(round_add_bounds(b, c)),
+                        f((const_round_add(b, c, lo), const_round_add(b, c, hi))),
+                        "{b:e} + {c:e} under {denormal:?}"
+                    );
+                    assert_eq!(
+                        f(round_mul_bounds(a, b)),
+                        f((const_round_mul(a, b, lo), const_round_mul(a, b, hi))),
+                        "{a:e} * {b:e} under {denormal:?}"
+                    );
+                    assert_eq!(
+                        f(round_div_bounds(b, a)),
+                        f((const_round_div(b, a, lo), const_round_div(b, a, hi))),
+                        "{b:e} / {a:e} under {denormal:?}"
+                    );
+                    assert_eq!(
+                        f(round_sqrt_bounds(b.abs())),
+                        f((const_round_sqrt(b.abs(), lo), const_round_sqrt(b.abs(), hi))),
+                        "sqrt {b:e} under {denormal:?}"
+                    );
+                    assert_eq!(
+                        f(round_mul_add_bounds(a, b, c)),
+                        f((
+                            const_round_mul_add(a, b, c, lo),
+                            const_round_mul_add(a, b, c, hi)
+                        )),
+                        "{a:e} * {b:e} + {c:e} under {denormal:?}"
+                    );
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn test_bounds_exact() {
+        assert_eq!(round_add_bounds(1.0, 2.0), (3.0, 3.0));
+        assert_eq!(round_mul_bounds(1.5, 4.0), (6.0, 6.0));
+        assert_eq!(round_sqrt_bounds(4.0), (2.0, 2.0));
+
+        let (lo, hi) = round_sub_bounds(1.0, 1.0);
+        assert!(lo.is_sign_negative() && hi.is_sign_positive());
+
+        let (lo, hi) = round_div_bounds(1.0, 3.0);
+        assert!(lo < hi);
+        assert_eq!(lo.next_up(), hi);
+    }
+}
+
 /// accrual test
 #[cfg(test)]
 mod test_fn_math {
@@ -0,0 +1,387 @@
+//! IEEE 754 binary interchange formats and rounding of exact values into them.
+
+use crate::{ExceptionFlags, FpEnv, RoundingMode, Tininess};
+
+/// Parameters of an IEEE 754 binary interchange format.
+#[derive(Debug, Copy, Clone)]
+pub struct Format {
+    /// Total bit width.
+    pub(crate) width: u32,
+    /// Precision in bits, including the hidden bit.
+    pub(crate) precision: u32,
+}
+
+#[cfg(any(feature = "f16", feature = "f16_softfloat", feature = "f16_widening"))]
+pub(crate) const F16: Format = Format {
+    width: 16,
+    precision: 11,
+};
+pub(crate) const F32: Format = Format {
+    width: 32,
+    precision: 24,
+};
+pub(crate) const F64: Format = Format {
+    width: 64,
+    precision: 53,
+};
+#[cfg(any(feature = "f128", feature = "f128_softfloat"))]
+pub(crate) const F128: Format = Format {
+    width: 128,
+    precision: 113,
+};
+
+/// Classified value, see [`Format::unpack`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Unpacked {
+    Nan,
+    Inf,
+    Zero,
+    /// `sig * 2^exp`, `sig` is not 0.
+    Finite {
+        sig: u128,
+        exp: i32,
+    },
+}
+
+impl Format {
+    #[inline]
+    pub(crate) const fn exp_width(&self) -> u32 {
+        self.width - self.precision
+    }
+
+    #[inline]
+    pub(crate) const fn bias(&self) -> i32 {
+        (1 << (self.exp_width() - 1)) - 1
+    }
+
+    /// The exponent of the minimum positive normal number.
+    #[inline]
+    pub(crate) const fn emin(&self) -> i32 {
+        1 - self.bias()
+    }
+
+    #[inline]
+    pub(crate) const fn sign_mask(&self) -> u128 {
+        1 << (self.width - 1)
+    }
+
+    #[inline]
+    pub(crate) const fn frac_mask(&self) -> u128 {
+        (1 << (self.precision - 1)) - 1
+    }
+
+    /// Bits of the infinity with sign.
+    #[inline]
+    pub(crate) const fn inf(&self, sign: bool) -> u128 {
+        let exp = ((1 << self.exp_width()) - 1) << (self.precision - 1);
+        if sign {
+            self.sign_mask() | exp
+        } else {
+            exp
+        }
+    }
+
+    /// Bits of the maximum finite number with sign.
+    #[inline]
+    pub(crate) const fn max(&self, sign: bool) -> u128 {
+        self.inf(sign) - 1
+    }
+
+    /// Bits of the (default) quiet NaN.
+    #[inline]
+    pub(crate) const fn nan(&self) -> u128 {
+        self.inf(false) | (1 << (self.precision - 2))
+    }
+
+    /// Returns `true` if `bits` is a (nonzero) subnormal number.
+    #[inline]
+    pub(crate) const fn is_subnormal(&self, bits: u128) -> bool {
+        bits & !self.sign_mask() != 0 && bits & self.inf(false) == 0
+    }
+
+    /// Returns the sign and the classified value of `bits`.
+    #[inline]
+    pub(crate) const fn unpack(&self, bits: u128) -> (bool, Unpacked) {
+        let sign = bits & self.sign_mask() != 0;
+        let frac = bits & self.frac_mask();
+        let biased = ((bits >> (self.precision - 1)) as i32) & ((1 << self.exp_width()) - 1);
+
+        let value = if biased == (1 << self.exp_width()) - 1 {
+            if frac == 0 {
+                Unpacked::Inf
+            } else {
+                Unpacked::Nan
+            }
+        } else if biased == 0 {
+            if frac == 0 {
+                Unpacked::Zero
+            } else {
+                Unpacked::Finite {
+                    sig: frac,
+                    exp: self.emin() - (self.precision as i32 - 1),
+                }
+            }
+        } else {
+            Unpacked::Finite {
+                sig: frac | (1 << (self.precision - 1)),
+                exp: biased - self.bias() - (self.precision as i32 - 1),
+            }
+        };
+        (sign, value)
+    }
+
+    /// Returns bits of the value rounded to nearest, ties to even, or with the `mode`,
+    /// and `true` if the result is inexact.
+    ///
+    /// The value is `(-1)^sign * (sig + δ) * 2^exp` where `δ` is `0` if `sticky` is `false`,
+    /// otherwise a (unknown) number in `(0, 1)`.
+    /// When `sticky` is `true`, `sig` must have (at least) `precision + 2` bits.
+    pub(crate) const fn round_pack(
+        &self,
+        sign: bool,
+        sig: u128,
+        exp: i32,
+        sticky: bool,
+        mode: &RoundingMode,
+    ) -> (u128, bool) {
+        let mut env = FpEnv::new(*mode);
+        let bits = self.round_pack_env(sign, sig, exp, sticky, &mut env);
+        (bits, env.flags.contains(ExceptionFlags::INEXACT))
+    }
+
+    /// [`Format::round_pack`] with the rounding mode of `env`,
+    /// raises inexact, overflow and underflow (tiny and inexact) flags to `env`.
+    pub(crate) const fn round_pack_env(
+        &self,
+        sign: bool,
+        sig: u128,
+        exp: i32,
+        sticky: bool,
+        env: &mut FpEnv,
+    ) -> u128 {
+        let sign_bit = if sign { self.sign_mask() } else { 0 };
+        if sig == 0 {
+            debug_assert!(!sticky);
+            return sign_bit;
+        }
+
+        let p = self.precision as i32;
+        let mode = env.mode;
+
+        // the exponent of the leading bit and of the ulp
+        let e = exp + (127 - sig.leading_zeros() as i32);
+        let q = if e < self.emin() { self.emin() } else { e } - (p - 1);
+
+        let (mut m, inexact) = round_at(sign, sig, exp, sticky, q, &mode, env.random);
+
+        let mut q = q;
+        if m == 1 << p {
+            m >>= 1;
+            q += 1;
+        }
+
+        let biased = if m >> (p - 1) != 0 {
+            q + (p - 1) + self.bias()
+        } else {
+            0
+        };
+
+        if biased >= (1 << self.exp_width()) - 1 {
+            let inf = match mode {
+                RoundingMode::NearestTiesEven => true,
+                RoundingMode::TowardZero => false,
+                RoundingMode::TowardPosInf => !sign,
+                RoundingMode::TowardNegInf => sign,
+            };
+            env.raise(ExceptionFlags::OVERFLOW.union(ExceptionFlags::INEXACT));
+            return if inf { self.inf(sign) } else { self.max(sign) };
+        }
+
+        if inexact {
+            env.raise(ExceptionFlags::INEXACT);
+
+            let tiny = match env.tininess {
+                // tiny if the exact result is less than 2^emin
+                Tininess::BeforeRounding => e < self.emin(),
+                // tiny if the result rounded with unbounded exponent is less than 2^emin
+                Tininess::AfterRounding => {
+                    e < self.emin() - 1
+                        || (e == self.emin() - 1
+                            && round_at(sign, sig, exp, sticky, e - (p - 1), &mode, env.random).0
+                                != 1 << p)
+                }
+            };
+            if tiny {
+                env.raise(ExceptionFlags::UNDERFLOW);
+            }
+        }
+
+        sign_bit | ((biased as u128) << (p - 1)) | (m & self.frac_mask())
+    }
+}
+
+/// Returns the significand of the value rounded at the ulp `2^q`,
+/// which may carry to `2^precision`, and `true` if it is inexact, see [`Format::round_pack`].
+///
+/// If `random` is `Some(u)`, the magnitude is rounded up stochastically,
+/// with the probability of the discarded fraction of the ulp (in 64 bits), instead of the `mode`.
+const fn round_at(
+    sign: bool,
+    sig: u128,
+    exp: i32,
+    sticky: bool,
+    q: i32,
+    mode: &RoundingMode,
+    random: Option<u64>,
+) -> (u128, bool) {
+    let shift = q - exp;
+    let (m, round, rest) = if shift <= 0 {
+        (sig << -shift, false, sticky)
+    } else if shift > 128 {
+        (0, false, true)
+    } else if shift == 128 {
+        (0, sig >> 127 == 1, sig << 1 != 0 || sticky)
+    } else {
+        let mask = (1 << (shift - 1)) - 1;
+        (
+            sig >> shift,
+            (sig >> (shift - 1)) & 1 == 1,
+            sig & mask != 0 || sticky,
+        )
+    };
+
+    let inexact = round || rest;
+    if let Some(u) = random {
+        // the upper 64 bits of the discarded fraction, the lower bits are jammed into the last bit
+        let frac = if shift <= 0 {
+            sticky as u64
+        } else if shift > 128 {
+            1
+        } else {
+            let discarded = if shift == 128 {
+                sig
+            } else {
+                sig & ((1 << shift) - 1)
+            };
+            let (top, lower) = if shift >= 64 {
+                (
+                    (discarded >> (shift - 64)) as u64,
+                    discarded & ((1 << (shift - 64)) - 1),
+                )
+            } else {
+                ((discarded << (64 - shift)) as u64, 0)
+            };
+            top | (lower != 0 || sticky) as u64
+        };
+        let inc = inexact && frac.overflowing_add(u).1;
+        return (if inc { m + 1 } else { m }, inexact);
+    }
+
+    let inc = match mode {
+        RoundingMode::NearestTiesEven => round && (rest || m & 1 == 1),
+        RoundingMode::TowardZero => false,
+        RoundingMode::TowardPosInf => inexact && !sign,
+        RoundingMode::TowardNegInf => inexact && sign,
+    };
+
+    (if inc { m + 1 } else { m }, inexact)
+}
+
+/// Floating-point types of the IEEE 754 binary interchange formats.
+///
+/// It is sealed, a supertrait of [`RoundingFloat`][crate::RoundingFloat]
+/// which generic public items are bounded by instead.
+pub trait BinaryFormat: Copy + crate::sealed::Sealed {
+    const FORMAT: Format;
+
+    fn to_raw(self) -> u128;
+
+    fn from_raw(bits: u128) -> Self;
+}
+
+macro_rules! impl_binary_format {
+    ($ty:ty, $fmt:ident) => {
+        impl BinaryFormat for $ty {
+            const FORMAT: Format = $fmt;
+
+            #[inline(always)]
+            fn to_raw(self) -> u128 {
+                self.to_bits() as u128
+            }
+
+            #[inline(always)]
+            fn from_raw(bits: u128) -> Self {
+                <$ty>::from_bits(bits as _)
+            }
+        }
+    };
+}
+
+#[cfg(any(feature = "f16", feature = "f16_softfloat", feature = "f16_widening"))]
+impl_binary_format!(f16, F16);
+impl_binary_format!(f32, F32);
+impl_binary_format!(f64, F64);
+#[cfg(any(feature = "f128", feature = "f128_softfloat"))]
+impl_binary_format!(f128, F128);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::RoundingMode as Mode;
+
+    #[test]
+    fn test_round_pack() {
+        // 1 + 2^-53
+        let (sig, exp) = ((1 << 53) + 1, -53);
+        let r = F64.round_pack(false, sig, exp, false, &Mode::NearestTiesEven);
+        assert_eq!(r, (1f64.to_bits() as u128, true));
+        let r = F64.round_pack(false, sig, exp, false, &Mode::TowardPosInf);
+        assert_eq!(r, (1f64.to_bits() as u128 + 1, true));
+        let r = F64.round_pack(true, sig, exp, false, &Mode::TowardPosInf);
+        assert_eq!(r, ((-1f64).to_bits() as u128, true));
+        let r = F64.round_pack(true, sig, exp, false, &Mode::TowardNegInf);
+        assert_eq!(r, ((-1f64).to_bits() as u128 + 1, true));
+
+        // exact
+        let r = F32.round_pack(false, 3, -1, false, &Mode::TowardZero);
+        assert_eq!(r, (1.5f32.to_bits() as u128, false));
+
+        // subnormal
+        let r = F64.round_pack(false, 3, -1076, false, &Mode::TowardPosInf);
+        assert_eq!(r, (1, true));
+        let r = F64.round_pack(false, 3, -1076, false, &Mode::TowardZero);
+        assert_eq!(r, (0, true));
+        let r = F64.round_pack(false, 1, -1076, false, &Mode::NearestTiesEven);
+        assert_eq!(r, (0, true));
+
+        // overflow
+        let r = F32.round_pack(false, 1, 128, false, &Mode::NearestTiesEven);
+        assert_eq!(r, (f32::INFINITY.to_bits() as u128, true));
+        let r = F32.round_pack(false, 1, 128, false, &Mode::TowardZero);
+        assert_eq!(r, (f32::MAX.to_bits() as u128, true));
+        let r = F32.round_pack(true, 1, 128, false, &Mode::TowardPosInf);
+        assert_eq!(r, (f32::MIN.to_bits() as u128, true));
+    }
+
+    #[test]
+    fn test_unpack() {
+        let f = |x: f64| F64.unpack(x.to_bits() as u128);
+        assert_eq!(
+            f(1.5),
+            (
+                false,
+                Unpacked::Finite {
+                    sig: 3 << 51,
+                    exp: -52
+                }
+            )
+        );
+        assert_eq!(f(-0.0), (true, Unpacked::Zero));
+        assert_eq!(
+            f(f64::from_bits(1)),
+            (false, Unpacked::Finite { sig: 1, exp: -1074 })
+        );
+        assert_eq!(f(f64::NEG_INFINITY), (true, Unpacked::Inf));
+        assert_eq!(f(f64::NAN).1, Unpacked::Nan);
+    }
+}
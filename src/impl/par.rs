@@ -0,0 +1,324 @@
+//! Parallel directed ops on slices by [`rayon`].
+//!
+//! The slices are split into chunks of [`CHUNK`] elements,
+//! and each worker computes a whole chunk by a slice kernel of the backend.
+//! On the default backend, the kernel switches the rounding mode of the worker thread once per chunk,
+//! and restores it before it returns to Rust, thus no mode leaks on the worker threads
+//! even when another task on them panics.
+//! The other backends do not touch the thread's rounding mode at all.
+
+/// The number of elements computed under a single rounding mode switch.
+pub(crate) const CHUNK: usize = 4096;
+
+/// Generates slice kernels `round_*_slice(a, .., dst, mode)` of a backend.
+///
+/// `$ty, $c_add, ...` calls the C kernels switching rounding mode once,
+/// and `$ty` loops over the scalar functions.
+macro_rules! impl_round_slice {
+    ($ty:ident, $c_add:ident, $c_sub:ident, $c_mul:ident, $c_div:ident, $c_fma:ident, $c_sqrt:ident) => {
+        extern "C" {
+            fn $c_add(mode: c_int, n: usize, a: *const $ty, b: *const $ty, dst: *mut $ty) -> c_int;
+            fn $c_sub(mode: c_int, n: usize, a: *const $ty, b: *const $ty, dst: *mut $ty) -> c_int;
+            fn $c_mul(mode: c_int, n: usize, a: *const $ty, b: *const $ty, dst: *mut $ty) -> c_int;
+            fn $c_div(mode: c_int, n: usize, a: *const $ty, b: *const $ty, dst: *mut $ty) -> c_int;
+            fn $c_fma(
+                mode: c_int,
+                n: usize,
+                a: *const $ty,
+                b: *const $ty,
+                c: *const $ty,
+                dst: *mut $ty,
+            ) -> c_int;
+            fn $c_sqrt(mode: c_int, n: usize, a: *const $ty, dst: *mut $ty) -> c_int;
+        }
+
+        crate::r#impl::par::impl_round_slice!(@binary $ty, round_add_slice, $c_add);
+        crate::r#impl::par::impl_round_slice!(@binary $ty, round_sub_slice, $c_sub);
+        crate::r#impl::par::impl_round_slice!(@binary $ty, round_mul_slice, $c_mul);
+        crate::r#impl::par::impl_round_slice!(@binary $ty, round_div_slice, $c_div);
+
+        #[inline]
+        pub(crate) fn round_mul_add_slice(
+            a: &[$ty],
+            b: &[$ty],
+            c: &[$ty],
+            dst: &mut [$ty],
+            mode: &RoundingMode,
+        ) {
+            assert!(a.len() == dst.len() && b.len() == dst.len() && c.len() == dst.len());
+            let n = dst.len();
+            match unsafe {
+                $c_fma(mode.as_c_int(), n, a.as_ptr(), b.as_ptr(), c.as_ptr(), dst.as_mut_ptr())
+            } {
+                0 => (),
+                _ => crate::r#impl::error(),
+            }
+        }
+
+        #[inline]
+        pub(crate) fn round_sqrt_slice(a: &[$ty], dst: &mut [$ty], mode: &RoundingMode) {
+            assert!(a.len() == dst.len());
+            match unsafe { $c_sqrt(mode.as_c_int(), dst.len(), a.as_ptr(), dst.as_mut_ptr()) } {
+                0 => (),
+                _ => crate::r#impl::error(),
+            }
+        }
+    };
+    (@binary $ty:ident, $name:ident, $c_name:ident) => {
+        #[inline]
+        pub(crate) fn $name(a: &[$ty], b: &[$ty], dst: &mut [$ty], mode: &RoundingMode) {
+            assert!(a.len() == dst.len() && b.len() == dst.len());
+            let n = dst.len();
+            match unsafe { $c_name(mode.as_c_int(), n, a.as_ptr(), b.as_ptr(), dst.as_mut_ptr()) } {
+                0 => (),
+                _ => crate::r#impl::error(),
+            }
+        }
+    };
+    ($ty:ident) => {
+        crate::r#impl::par::impl_round_slice!(@loop $ty, round_add_slice, round_add);
+        crate::r#impl::par::impl_round_slice!(@loop $ty, round_sub_slice, round_sub);
+        crate::r#impl::par::impl_round_slice!(@loop $ty, round_mul_slice, round_mul);
+        crate::r#impl::par::impl_round_slice!(@loop $ty, round_div_slice, round_div);
+
+        #[inline]
+        pub(crate) fn round_mul_add_slice(
+            a: &[$ty],
+            b: &[$ty],
+            c: &[$ty],
+            dst: &mut [$ty],
+            mode: &RoundingMode,
+        ) {
+            for (i, x) in dst.iter_mut().enumerate() {
+                *x = round_mul_add(a[i], b[i], c[i], mode);
+            }
+        }
+
+        #[inline]
+        pub(crate) fn round_sqrt_slice(a: &[$ty], dst: &mut [$ty], mode: &RoundingMode) {
+            for (x, a) in dst.iter_mut().zip(a) {
+                *x = round_sqrt(*a, mode);
+            }
+        }
+    };
+    (@loop $ty:ident, $name:ident, $scalar:ident) => {
+        #[inline]
+        pub(crate) fn $name(a: &[$ty], b: &[$ty], dst: &mut [$ty], mode: &RoundingMode) {
+            for (i, x) in dst.iter_mut().enumerate() {
+                *x = $scalar(a[i], b[i], mode);
+            }
+        }
+    };
+}
+
+/// Generates `par_round_*_slice` on the slice kernels `round_*_slice`.
+macro_rules! impl_par_round_slice {
+    ($ty:ident) => {
+        crate::r#impl::par::impl_par_round_slice!(
+            @binary $ty, par_round_add_slice, round_add_slice, "a[i] + b[i]"
+        );
+        crate::r#impl::par::impl_par_round_slice!(
+            @binary $ty, par_round_sub_slice, round_sub_slice, "a[i] - b[i]"
+        );
+        crate::r#impl::par::impl_par_round_slice!(
+            @binary $ty, par_round_mul_slice, round_mul_slice, "a[i] * b[i]"
+        );
+        crate::r#impl::par::impl_par_round_slice!(
+            @binary $ty, par_round_div_slice, round_div_slice, "a[i] / b[i]"
+        );
+
+        /// Stores `a[i] * b[i] + c[i]` with single rounding (fused multiply-add)
+        /// into `dst[i]` in parallel as specific rounding mode.
+        ///
+        /// # Safety
+        ///
+        /// Panics when the lengths of the slices differ,
+        /// or when fail to set/restore rounding mode.
+        #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+        pub fn par_round_mul_add_slice(
+            a: &[$ty],
+            b: &[$ty],
+            c: &[$ty],
+            dst: &mut [$ty],
+            mode: &RoundingMode,
+        ) {
+            use rayon::prelude::*;
+
+            use crate::r#impl::par::CHUNK;
+
+            assert_eq!(a.len(), dst.len(), "lengths of slices differ");
+            assert_eq!(b.len(), dst.len(), "lengths of slices differ");
+            assert_eq!(c.len(), dst.len(), "lengths of slices differ");
+            dst.par_chunks_mut(CHUNK)
+                .enumerate()
+                .for_each(|(i, dst)| {
+                    let r = i * CHUNK..i * CHUNK + dst.len();
+                    round_mul_add_slice(&a[r.clone()], &b[r.clone()], &c[r], dst, mode)
+                });
+        }
+
+        /// Stores `a[i].sqrt()` into `dst[i]` in parallel as specific rounding mode.
+        ///
+        /// # Safety
+        ///
+        /// Panics when the lengths of the slices differ,
+        /// or when fail to set/restore rounding mode.
+        #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+        pub fn par_round_sqrt_slice(a: &[$ty], dst: &mut [$ty], mode: &RoundingMode) {
+            use rayon::prelude::*;
+
+            use crate::r#impl::par::CHUNK;
+
+            assert_eq!(a.len(), dst.len(), "lengths of slices differ");
+            dst.par_chunks_mut(CHUNK)
+                .zip(a.par_chunks(CHUNK))
+                .for_each(|(dst, a)| round_sqrt_slice(a, dst, mode));
+        }
+    };
+    (@binary $ty:ident, $name:ident, $kernel:ident, $op:literal) => {
+        #[doc = concat!("Stores `", $op, "` into `dst[i]` in parallel as specific rounding mode.")]
+        ///
+        /// # Safety
+        ///
+        /// Panics when the lengths of the slices differ,
+        /// or when fail to set/restore rounding mode.
+        #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+        pub fn $name(a: &[$ty], b: &[$ty], dst: &mut [$ty], mode: &RoundingMode) {
+            use rayon::prelude::*;
+
+            use crate::r#impl::par::CHUNK;
+
+            assert_eq!(a.len(), dst.len(), "lengths of slices differ");
+            assert_eq!(b.len(), dst.len(), "lengths of slices differ");
+            dst.par_chunks_mut(CHUNK)
+                .zip(a.par_chunks(CHUNK))
+                .zip(b.par_chunks(CHUNK))
+                .for_each(|((dst, a), b)| $kernel(a, b, dst, mode));
+        }
+    };
+}
+
+pub(crate) use impl_par_round_slice;
+pub(crate) use impl_round_slice;
+
+#[cfg(test)]
+mod test {
+    use crate::RoundingMode as Mode;
+
+    const MODES: [Mode; 4] = [
+        Mode::NearestTiesEven,
+        Mode::TowardPosInf,
+        Mode::TowardNegInf,
+        Mode::TowardZero,
+    ];
+
+    fn xorshift(x: &mut u64) -> u64 {
+        *x ^= *x << 13;
+        *x ^= *x >> 7;
+        *x ^= *x << 17;
+        *x
+    }
+
+    /// Returns `true` if the rounding mode of every worker is the default.
+    fn default_mode_on_workers() -> bool {
+        // 1 + 2^-60 rounds to 1 only in the default mode
+        rayon::broadcast(|_| {
+            let (a, b) = (
+                core::hint::black_box(1.0f64),
+                core::hint::black_box(2f64.powi(-60)),
+            );
+            a + b == 1.0 && -a - b == -1.0
+        })
+        .into_iter()
+        .all(|x| x)
+    }
+
+    macro_rules! check {
+        ($ty:ident, $next:expr) => {{
+            use crate::$ty::*;
+
+            let f = |x: &[$ty]| x.iter().map(|x| x.to_bits()).collect::<Vec<_>>();
+            let mut next = $next;
+            // not multiple of CHUNK
+            let n = 3 * super::CHUNK + 123;
+            let a: Vec<$ty> = (0..n).map(|_| next()).collect();
+            let b: Vec<$ty> = (0..n).map(|_| next()).collect();
+            let c: Vec<$ty> = (0..n).map(|_| next()).collect();
+            let mut dst = vec![0.0; n];
+            for mode in &MODES {
+                macro_rules! e {
+                    ($e:expr) => {
+                        (0..n).map(|i| $e(i)).collect::<Vec<$ty>>()
+                    };
+                }
+                par_round_add_slice(&a, &b, &mut dst, mode);
+                assert_eq!(f(&dst), f(&e!(|i: usize| round_add(a[i], b[i], mode))));
+                par_round_sub_slice(&a, &b, &mut dst, mode);
+                assert_eq!(f(&dst), f(&e!(|i: usize| round_sub(a[i], b[i], mode))));
+                par_round_mul_slice(&a, &b, &mut dst, mode);
+                assert_eq!(f(&dst), f(&e!(|i: usize| round_mul(a[i], b[i], mode))));
+                par_round_div_slice(&a, &b, &mut dst, mode);
+                assert_eq!(f(&dst), f(&e!(|i: usize| round_div(a[i], b[i], mode))));
+                par_round_mul_add_slice(&a, &b, &c, &mut dst, mode);
+                assert_eq!(
+                    f(&dst),
+                    f(&e!(|i: usize| round_mul_add(a[i], b[i], c[i], mode)))
+                );
+                par_round_sqrt_slice(&a, &mut dst, mode);
+                assert_eq!(f(&dst), f(&e!(|i: usize| round_sqrt(a[i], mode))));
+            }
+            assert!(default_mode_on_workers());
+        }};
+    }
+
+    #[test]
+    fn test_f64() {
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        check!(f64, || {
+            let r = xorshift(&mut x);
+            match r % 4 {
+                0 => f64::from_bits(r),
+                1 => (r % 1024) as f64 / 64.0,
+                _ => f64::from_bits(r & 0x800F_FFFF_FFFF_FFFF | 0x3FF0_0000_0000_0000),
+            }
+        });
+    }
+
+    #[test]
+    fn test_f32() {
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        check!(f32, || {
+            let r = xorshift(&mut x);
+            match r % 4 {
+                0 => f32::from_bits(r as u32),
+                1 => (r % 1024) as f32 / 64.0,
+                _ => f32::from_bits(r as u32 & 0x807F_FFFF | 0x3F80_0000),
+            }
+        });
+    }
+
+    #[test]
+    fn test_panic() {
+        let (a, b) = (vec![0.1f64; 10000], vec![0.2f64; 9999]);
+        let mut dst = vec![0.0; 10000];
+        let r = std::panic::catch_unwind(move || {
+            crate::f64::par_round_add_slice(&a, &b, &mut dst, &Mode::TowardPosInf)
+        });
+        assert!(r.is_err());
+
+        let r = std::panic::catch_unwind(|| {
+            rayon::broadcast(|_| {
+                let mut dst = vec![0.0f64; 10];
+                crate::f64::par_round_mul_slice(
+                    &[0.1; 10],
+                    &[0.2; 10],
+                    &mut dst,
+                    &Mode::TowardNegInf,
+                );
+                panic!("panic in a closure");
+            })
+        });
+        assert!(r.is_err());
+        assert!(default_mode_on_workers());
+    }
+}
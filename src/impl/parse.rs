@@ -0,0 +1,341 @@
+//! Correctly rounded decimal-to-binary conversion.
+
+use crate::r#impl::bigint::Big;
+use crate::r#impl::format::Format;
+use crate::{ParseError, ParseErrorKind, RoundingMode};
+
+/// The number of significant digits kept exactly, the rest contributes as sticky.
+///
+/// This is greater than the number of significant digits of any midpoint of the format,
+/// thus truncation does not change the rounding result.
+const fn max_digits(fmt: &Format) -> i64 {
+    (fmt.bias() + fmt.precision as i32 + 10) as i64
+}
+
+/// A value less than `10^min10` rounds to 0 or the minimum subnormal.
+const fn min10(fmt: &Format) -> i64 {
+    -(((fmt.bias() + fmt.precision as i32 - 2) as i64 * 30103 / 100000) + 2)
+}
+
+/// A value not less than `10^(max10 - 1)` overflows.
+const fn max10(fmt: &Format) -> i64 {
+    ((fmt.bias() + 1) as i64 * 30103 / 100000) + 2
+}
+
+/// The capacity of [`Big`] required by [`parse_decimal`].
+pub(crate) const fn limbs(fmt: &Format) -> usize {
+    // log2(10) < 3.322
+    let bits = (max_digits(fmt) - min10(fmt)) * 3322 / 1000 + fmt.precision as i64 + 64;
+    bits as usize / 32 + 2
+}
+
+enum Prefix<'a> {
+    /// Bits of infinity or NaN.
+    Special(u128),
+    /// The sign and the rest.
+    Number(bool, &'a [u8]),
+}
+
+/// Returns `true` if `s` equals to the lowercase `lower` ignoring ASCII case.
+const fn eq_ignore_case(s: &[u8], lower: &[u8]) -> bool {
+    if s.len() != lower.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < s.len() {
+        if s[i].to_ascii_lowercase() != lower[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Parses the sign, infinity and NaN.
+const fn parse_prefix<'a>(s: &'a [u8], fmt: &Format) -> Result<Prefix<'a>, ParseError> {
+    let (sign, s) = match s.split_first() {
+        None => return Err(ParseError::new(ParseErrorKind::Empty)),
+        Some((b'-', rest)) => (true, rest),
+        Some((b'+', rest)) => (false, rest),
+        Some(_) => (false, s),
+    };
+
+    if eq_ignore_case(s, b"inf") || eq_ignore_case(s, b"infinity") {
+        Ok(Prefix::Special(fmt.inf(sign)))
+    } else if eq_ignore_case(s, b"nan") {
+        let sign_bit = if sign { fmt.sign_mask() } else { 0 };
+        Ok(Prefix::Special(sign_bit | fmt.nan()))
+    } else {
+        Ok(Prefix::Number(sign, s))
+    }
+}
+
+/// Parses an exponent (after `e` or `p`), saturates on overflow.
+const fn parse_exp(s: &[u8]) -> Result<i64, ParseError> {
+    let (sign, s) = match s.split_first() {
+        Some((b'-', rest)) => (-1, rest),
+        Some((b'+', rest)) => (1, rest),
+        _ => (1, s),
+    };
+    if s.is_empty() {
+        return Err(ParseError::new(ParseErrorKind::Invalid));
+    }
+
+    let mut exp = 0i64;
+    let mut i = 0;
+    while i < s.len() {
+        match s[i] {
+            b'0'..=b'9' => {
+                exp = exp * 10 + (s[i] - b'0') as i64;
+                // it is enough to overflow/underflow any format
+                if exp > 1_000_000_000 {
+                    exp = 1_000_000_000;
+                }
+            }
+            _ => return Err(ParseError::new(ParseErrorKind::Invalid)),
+        }
+        i += 1;
+    }
+    Ok(sign * exp)
+}
+
+/// Parses a decimal string (e.g. `-1.5e-3`, `inf` and `NaN`),
+/// returns bits of the correctly rounded value and `true` if the result is inexact.
+///
+/// `N` must be [`limbs(fmt)`][limbs].
+pub(crate) const fn parse_decimal<const N: usize>(
+    s: &[u8],
+    fmt: &Format,
+    mode: &RoundingMode,
+) -> Result<(u128, bool), ParseError> {
+    let (sign, s) = match parse_prefix(s, fmt) {
+        Ok(Prefix::Special(bits)) => return Ok((bits, false)),
+        Ok(Prefix::Number(sign, s)) => (sign, s),
+        Err(e) => return Err(e),
+    };
+    let sign_bit = if sign { fmt.sign_mask() } else { 0 };
+
+    let max_digits = max_digits(fmt);
+
+    // significant digits as integer, the power of 10 and the sticky digit
+    let mut digits = Big::<N>::zero();
+    let mut n_digits = 0i64;
+    let mut exp10 = 0i64;
+    let mut sticky = false;
+
+    // accumulates up to 9 digits
+    let mut chunk = 0u32;
+    let mut chunk_len = 0u32;
+
+    let mut seen_digit = false;
+    let mut seen_point = false;
+    let mut i = 0;
+    while i < s.len() {
+        match s[i] {
+            c @ b'0'..=b'9' => {
+                seen_digit = true;
+                let d = (c - b'0') as u32;
+                if n_digits == 0 && d == 0 {
+                    // leading zero
+                } else if n_digits < max_digits {
+                    chunk = chunk * 10 + d;
+                    chunk_len += 1;
+                    if chunk_len == 9 {
+                        digits.mul_add_small(1_000_000_000, chunk);
+                        chunk = 0;
+                        chunk_len = 0;
+                    }
+                    n_digits += 1;
+                } else {
+                    sticky |= d != 0;
+                    exp10 += 1;
+                }
+                if seen_point {
+                    exp10 -= 1;
+                }
+            }
+            b'.' if !seen_point => seen_point = true,
+            _ => break,
+        }
+        i += 1;
+    }
+    if 0 < chunk_len {
+        digits.mul_add_small(10u32.pow(chunk_len), chunk);
+    }
+    if !seen_digit {
+        return Err(ParseError::new(ParseErrorKind::Invalid));
+    }
+
+    if i < s.len() {
+        if s[i] != b'e' && s[i] != b'E' {
+            return Err(ParseError::new(ParseErrorKind::Invalid));
+        }
+        match parse_exp(s.split_at(i + 1).1) {
+            Ok(e) => exp10 += e,
+            Err(e) => return Err(e),
+        }
+    }
+
+    if digits.is_zero() {
+        return Ok((sign_bit, false));
+    }
+
+    // the value is in [10^(e10 - 1), 10^e10)
+    let e10 = exp10 + n_digits;
+    if max10(fmt) < e10 {
+        return Ok(fmt.round_pack(sign, 1, 1 << 20, false, mode));
+    }
+    if e10 < min10(fmt) {
+        return Ok(fmt.round_pack(sign, 1, -(1 << 20), false, mode));
+    }
+
+    let r = if 0 <= exp10 {
+        digits.mul_pow10(exp10 as u32);
+
+        match digits.bit_len().checked_sub(128) {
+            None => fmt.round_pack(sign, digits.bits_u128(0), 0, sticky, mode),
+            Some(lo) => {
+                let sticky = sticky || digits.any_below(lo);
+                fmt.round_pack(sign, digits.bits_u128(lo), lo as i32, sticky, mode)
+            }
+        }
+    } else {
+        // the quotient of num / den has (precision + 3) or (precision + 4) bits
+        let bits = fmt.precision + 3;
+
+        let mut num = digits;
+        let mut den = Big::<N>::pow10(-exp10 as u32);
+
+        let shift = bits as i64 - (num.bit_len() as i64 - den.bit_len() as i64);
+        if 0 < shift {
+            num.shl(shift as u32);
+        } else {
+            den.shl(-shift as u32);
+        }
+
+        den.shl(bits);
+        let mut q = 0u128;
+        let mut k = bits + 1;
+        while 0 < k {
+            k -= 1;
+            if !num.cmp(&den).is_lt() {
+                num.sub(&den);
+                q |= 1 << k;
+            }
+            den.shr1();
+        }
+
+        let sticky = sticky || !num.is_zero();
+        fmt.round_pack(sign, q, -shift as i32, sticky, mode)
+    };
+
+    Ok(r)
+}
+
+/// Parses a hexadecimal string (e.g. `0x1.8p-3`, `inf` and `NaN`),
+/// returns bits of the correctly rounded value and `true` if the result is inexact.
+pub(crate) const fn parse_hex(
+    s: &[u8],
+    fmt: &Format,
+    mode: &RoundingMode,
+) -> Result<(u128, bool), ParseError> {
+    let (sign, s) = match parse_prefix(s, fmt) {
+        Ok(Prefix::Special(bits)) => return Ok((bits, false)),
+        Ok(Prefix::Number(sign, s)) => (sign, s),
+        Err(e) => return Err(e),
+    };
+
+    let s = match s {
+        [b'0', b'x' | b'X', s @ ..] => s,
+        _ => return Err(ParseError::new(ParseErrorKind::Invalid)),
+    };
+
+    // the value is (sig + sticky) * 2^exp
+    let mut sig = 0u128;
+    let mut exp = 0i64;
+    let mut sticky = false;
+
+    let mut seen_digit = false;
+    let mut seen_point = false;
+    let mut i = 0;
+    while i < s.len() {
+        match (s[i] as char).to_digit(16) {
+            Some(d) => {
+                seen_digit = true;
+                if sig >> 124 == 0 {
+                    sig = (sig << 4) | d as u128;
+                    if seen_point {
+                        exp -= 4;
+                    }
+                } else {
+                    sticky |= d != 0;
+                    if !seen_point {
+                        exp += 4;
+                    }
+                }
+            }
+            None if s[i] == b'.' && !seen_point => seen_point = true,
+            None => break,
+        }
+        i += 1;
+    }
+    if !seen_digit {
+        return Err(ParseError::new(ParseErrorKind::Invalid));
+    }
+
+    if i < s.len() {
+        if s[i] != b'p' && s[i] != b'P' {
+            return Err(ParseError::new(ParseErrorKind::Invalid));
+        }
+        match parse_exp(s.split_at(i + 1).1) {
+            Ok(e) => exp += e,
+            Err(e) => return Err(e),
+        }
+    }
+
+    if sig == 0 {
+        let sign_bit = if sign { fmt.sign_mask() } else { 0 };
+        return Ok((sign_bit, false));
+    }
+
+    // any exponent out of this range overflows/underflows
+    let exp = if exp < -(1 << 20) {
+        -(1 << 20)
+    } else if exp > 1 << 20 {
+        1 << 20
+    } else {
+        exp
+    };
+    Ok(fmt.round_pack(sign, sig, exp as i32, sticky, mode))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(any(feature = "f128", feature = "f128_softfloat"))]
+    use crate::r#impl::format::F128;
+    #[cfg(any(feature = "f16", feature = "f16_softfloat", feature = "f16_widening"))]
+    use crate::r#impl::format::F16;
+    use crate::r#impl::format::{F32, F64};
+
+    #[test]
+    fn test_limbs() {
+        // keep stack usage reasonable
+        #[cfg(any(feature = "f16", feature = "f16_softfloat", feature = "f16_widening"))]
+        assert!(limbs(&F16) < 16);
+        assert!(limbs(&F32) < 32);
+        assert!(limbs(&F64) < 256);
+        #[cfg(any(feature = "f128", feature = "f128_softfloat"))]
+        assert!(limbs(&F128) < 4096);
+    }
+
+    #[test]
+    fn test_bounds() {
+        // 10^-46 < 2^-150, 10^39 > f32::MAX
+        assert_eq!(min10(&F32), -46);
+        assert_eq!(max10(&F32), 40);
+        // 10^-325 < 2^-1075, 10^309 > f64::MAX
+        assert_eq!(min10(&F64), -325);
+        assert_eq!(max10(&F64), 310);
+    }
+}
@@ -0,0 +1,253 @@
+//! Binary-to-decimal conversion with rounding mode.
+
+use crate::r#impl::bigint::Big;
+#[cfg(any(feature = "f128", feature = "f128_softfloat"))]
+use crate::r#impl::format::F128;
+use crate::r#impl::format::{BinaryFormat, Format, Unpacked, F64};
+use crate::RoundingMode;
+
+/// The capacity of [`Big`] holding the exact decimal value of the format.
+const fn limbs(fmt: &Format) -> usize {
+    // the minimum exponent is -(bias + precision - 2), and log2(5) < 2.322
+    let bits = (fmt.bias() + fmt.precision as i32 - 2) as usize * 2322 / 1000
+        + fmt.precision as usize
+        + 64;
+    bits / 32 + 2
+}
+
+/// Returns decimal digits of `n`.
+fn to_decimal<const N: usize>(mut n: Big<N>) -> String {
+    let mut chunks = Vec::new();
+    while !n.is_zero() {
+        chunks.push(n.div_rem_small(1_000_000_000));
+    }
+    match chunks.pop() {
+        None => "0".to_string(),
+        Some(head) => {
+            let mut s = head.to_string();
+            for c in chunks.iter().rev() {
+                s.push_str(&format!("{:09}", c));
+            }
+            s
+        }
+    }
+}
+
+/// Returns the sign and the decimal string of the absolute value of `x`
+/// rounded to `digits` fractional digits, or the exact value if `digits` is `None`.
+pub(crate) fn format_abs<T: BinaryFormat>(
+    x: T,
+    digits: Option<usize>,
+    mode: &RoundingMode,
+) -> (bool, String) {
+    match T::FORMAT.width {
+        // f16 and f32 fit in the f64's capacity
+        16 | 32 | 64 => format_abs_impl::<{ limbs(&F64) }>(x.to_raw(), &T::FORMAT, digits, mode),
+        #[cfg(any(feature = "f128", feature = "f128_softfloat"))]
+        128 => format_abs_impl::<{ limbs(&F128) }>(x.to_raw(), &T::FORMAT, digits, mode),
+        _ => unreachable!(),
+    }
+}
+
+fn format_abs_impl<const N: usize>(
+    bits: u128,
+    fmt: &Format,
+    digits: Option<usize>,
+    mode: &RoundingMode,
+) -> (bool, String) {
+    let (sign, value) = match fmt.unpack(bits) {
+        // removes trailing zeros
+        (sign, Unpacked::Finite { sig, exp }) => {
+            let tz = sig.trailing_zeros();
+            let value = Unpacked::Finite {
+                sig: sig >> tz,
+                exp: exp + tz as i32,
+            };
+            (sign, value)
+        }
+        r => r,
+    };
+
+    // the value is n / 10^frac_len
+    let (mut n, frac_len) = match value {
+        Unpacked::Nan => return (false, "NaN".to_string()),
+        Unpacked::Inf => return (sign, "inf".to_string()),
+        Unpacked::Zero => (Big::<N>::zero(), 0),
+        Unpacked::Finite { sig, exp } if 0 <= exp => {
+            let mut n = Big::<N>::from_u128(sig);
+            n.shl(exp as u32);
+            (n, 0)
+        }
+        Unpacked::Finite { sig, exp } => {
+            let mut n = Big::<N>::from_u128(sig);
+            n.mul_pow5(-exp as u32);
+            (n, -exp as usize)
+        }
+    };
+
+    let digits = digits.unwrap_or(frac_len);
+
+    if digits < frac_len {
+        let mut drop = frac_len - digits;
+
+        // the most significant dropped digit and the rest
+        let mut round = 0;
+        let mut rest = false;
+        while 0 < drop {
+            let k = drop.min(9);
+            let p = 10u32.pow(k as u32);
+            let r = n.div_rem_small(p);
+
+            rest |= round != 0 || r % (p / 10) != 0;
+            round = r / (p / 10);
+            drop -= k;
+        }
+
+        let inexact = round != 0 || rest;
+        let inc = match mode {
+            RoundingMode::NearestTiesEven => {
+                5 < round || (round == 5 && (rest || n.bits_u128(0) & 1 == 1))
+            }
+            RoundingMode::TowardZero => false,
+            RoundingMode::TowardPosInf => inexact && !sign,
+            RoundingMode::TowardNegInf => inexact && sign,
+        };
+        if inc {
+            n.mul_add_small(1, 1);
+        }
+    }
+
+    let mut s = to_decimal(n);
+    let frac_len = frac_len.min(digits);
+
+    // pads leading zeros, e.g. 0.001
+    if s.len() <= frac_len {
+        s.insert_str(0, &"0".repeat(frac_len - s.len() + 1));
+    }
+    if 0 < frac_len {
+        s.insert(s.len() - frac_len, '.');
+    } else if 0 < digits {
+        s.push('.');
+    }
+    // pads trailing zeros
+    s.push_str(&"0".repeat(digits - frac_len));
+
+    (sign, s)
+}
+
+/// Returns the hexadecimal string of `x` (e.g. `0x1.8p-3`),
+/// as C's `printf("%a", x)` does.
+pub(crate) fn format_hex<T: BinaryFormat>(x: T) -> String {
+    let fmt = &T::FORMAT;
+    let (sign, value) = fmt.unpack(x.to_raw());
+    let sign = if sign { "-" } else { "" };
+
+    let (lead, frac, exp) = match value {
+        Unpacked::Nan => return "nan".to_string(),
+        Unpacked::Inf => return format!("{}inf", sign),
+        Unpacked::Zero => return format!("{}0x0p+0", sign),
+        Unpacked::Finite { sig, exp } => {
+            let frac = sig & fmt.frac_mask();
+            match sig >> (fmt.precision - 1) {
+                // subnormal
+                0 => (0, frac, fmt.emin()),
+                _ => (1, frac, exp + fmt.precision as i32 - 1),
+            }
+        }
+    };
+
+    // aligns fraction bits to hex digits
+    let frac_width = fmt.precision - 1;
+    let digits = frac_width.div_ceil(4);
+    let frac = frac << (4 * digits - frac_width);
+
+    let mut s = format!("{}0x{}", sign, lead);
+    if frac != 0 {
+        let hex = format!("{:0width$x}", frac, width = digits as usize);
+        s.push('.');
+        s.push_str(hex.trim_end_matches('0'));
+    }
+    s.push_str(&format!("p{:+}", exp));
+    s
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::RoundingMode as Mode;
+
+    fn f(x: f64, digits: usize, mode: &RoundingMode) -> String {
+        match format_abs(x, Some(digits), mode) {
+            (true, s) => format!("-{}", s),
+            (false, s) => s,
+        }
+    }
+
+    #[test]
+    fn test_format_abs() {
+        assert_eq!(f(0.1, 3, &Mode::TowardPosInf), "0.101");
+        assert_eq!(f(0.1, 3, &Mode::TowardNegInf), "0.100");
+        assert_eq!(f(-0.1, 3, &Mode::TowardPosInf), "-0.100");
+        assert_eq!(f(-0.1, 3, &Mode::TowardNegInf), "-0.101");
+        assert_eq!(f(0.125, 2, &Mode::NearestTiesEven), "0.12");
+        assert_eq!(f(0.375, 2, &Mode::NearestTiesEven), "0.38");
+        assert_eq!(f(0.125, 2, &Mode::TowardZero), "0.12");
+        assert_eq!(f(1.0, 0, &Mode::TowardPosInf), "1");
+        assert_eq!(f(9.5, 0, &Mode::TowardPosInf), "10");
+        assert_eq!(f(123.0, 2, &Mode::TowardPosInf), "123.00");
+        assert_eq!(f(0.0, 2, &Mode::TowardPosInf), "0.00");
+        assert_eq!(f(-0.0, 2, &Mode::TowardPosInf), "-0.00");
+        assert_eq!(f(1e-10, 3, &Mode::TowardPosInf), "0.001");
+        assert_eq!(f(1e-10, 3, &Mode::TowardNegInf), "0.000");
+        assert_eq!(f(f64::from_bits(1), 2, &Mode::TowardPosInf), "0.01");
+        assert_eq!(f(f64::INFINITY, 2, &Mode::TowardPosInf), "inf");
+        assert_eq!(f(f64::NAN, 2, &Mode::TowardPosInf), "NaN");
+    }
+
+    #[test]
+    fn test_format_abs_nearest() {
+        for x in [
+            0.1,
+            0.3,
+            1.5,
+            2.5,
+            1e-7,
+            1e22,
+            123456.789,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+        ] {
+            for digits in [0, 1, 5, 17, 30] {
+                let e = format!("{:.*}", digits, x);
+                assert_eq!(f(x, digits, &Mode::NearestTiesEven), e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_format_hex() {
+        assert_eq!(format_hex(0.1f64), "0x1.999999999999ap-4");
+        assert_eq!(format_hex(0.1f32), "0x1.99999ap-4");
+        assert_eq!(format_hex(1.0f64), "0x1p+0");
+        assert_eq!(format_hex(-2.5f64), "-0x1.4p+1");
+        assert_eq!(format_hex(-0.0f64), "-0x0p+0");
+        assert_eq!(format_hex(f64::MAX), "0x1.fffffffffffffp+1023");
+        assert_eq!(format_hex(f64::from_bits(1)), "0x0.0000000000001p-1022");
+        assert_eq!(format_hex(f32::from_bits(1)), "0x0.000002p-126");
+        assert_eq!(format_hex(f32::NEG_INFINITY), "-inf");
+        assert_eq!(format_hex(f32::NAN), "nan");
+    }
+
+    #[test]
+    fn test_format_abs_exact() {
+        let (_, s) = format_abs(0.1f64, None, &Mode::TowardZero);
+        assert_eq!(
+            s,
+            "0.1000000000000000055511151231257827021181583404541015625"
+        );
+        let (_, s) = format_abs(0.5f32, None, &Mode::TowardZero);
+        assert_eq!(s, "0.5");
+        let (_, s) = format_abs(1e3f32, None, &Mode::TowardZero);
+        assert_eq!(s, "1000");
+    }
+}
@@ -0,0 +1,289 @@
+//! Directed rounding by error-free transformations, without `<fenv.h>` and C.
+//!
+//! Each op computes the result rounded to nearest natively,
+//! and steps it by an ulp according to the sign of the exact residual
+//! (by TwoSum, fused multiply-add, or ErrFma for fused multiply-add itself).
+//! The residual is not exact around overflow and underflow,
+//! there the ops fall back to the integer implementation of [`arith`][crate::r#impl::arith].
+//!
+//! As Rust's float ops, they require the thread's rounding mode to be to nearest.
+
+#[cfg(feature = "f32_pure_rust")]
+pub(crate) mod f32;
+#[cfg(feature = "f64_pure_rust")]
+pub(crate) mod f64;
+
+macro_rules! impl_pure_rust {
+    ($ty:ident, $fmt:ident) => {
+        use crate::r#impl::arith;
+        use crate::r#impl::format::$fmt;
+        use crate::RoundingMode;
+
+        crate::r#impl::eft::impl_eft!($ty);
+
+        /// Returns the rounded result from the nearest `r` of the exact value `r + residual`.
+        #[inline(always)]
+        fn adjust(r: $ty, residual: $ty, mode: &RoundingMode) -> $ty {
+            match mode {
+                RoundingMode::TowardPosInf if residual > 0.0 => next_up(r),
+                RoundingMode::TowardNegInf if residual < 0.0 => next_down(r),
+                RoundingMode::TowardZero if r > 0.0 && residual < 0.0 => next_down(r),
+                RoundingMode::TowardZero if r < 0.0 && residual > 0.0 => next_up(r),
+                _ => r,
+            }
+        }
+
+        #[inline(always)]
+        fn add_impl(a: $ty, b: $ty, mode: &RoundingMode) -> $ty {
+            let (r, e) = two_sum(a, b);
+            if r == 0.0 {
+                // exact zero of non-zero operands
+                return match mode {
+                    RoundingMode::TowardNegInf => -0.0,
+                    _ => 0.0,
+                };
+            }
+            adjust(r, e, mode)
+        }
+
+        /// Returns `a + b` as specific rounding mode.
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_add(a: $ty, b: $ty, mode: &RoundingMode) -> $ty {
+            if is_safe(a) && is_safe(b) {
+                add_impl(a, b, mode)
+            } else {
+                let r = arith::add(&$fmt, a.to_bits() as u128, b.to_bits() as u128, mode);
+                <$ty>::from_bits(r as _)
+            }
+        }
+
+        /// Returns `a - b` as specific rounding mode.
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_sub(a: $ty, b: $ty, mode: &RoundingMode) -> $ty {
+            if is_safe(a) && is_safe(b) {
+                add_impl(a, -b, mode)
+            } else {
+                let r = arith::sub(&$fmt, a.to_bits() as u128, b.to_bits() as u128, mode);
+                <$ty>::from_bits(r as _)
+            }
+        }
+
+        /// Returns `a * b` as specific rounding mode.
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_mul(a: $ty, b: $ty, mode: &RoundingMode) -> $ty {
+            let r = a * b;
+            if TINY <= r.abs() && r.abs() <= HUGE {
+                // TwoProd
+                adjust(r, a.mul_add(b, -r), mode)
+            } else {
+                let r = arith::mul(&$fmt, a.to_bits() as u128, b.to_bits() as u128, mode);
+                <$ty>::from_bits(r as _)
+            }
+        }
+
+        /// Returns `a / b` as specific rounding mode.
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_div(a: $ty, b: $ty, mode: &RoundingMode) -> $ty {
+            let r = a / b;
+            if 2.0 * TINY <= a.abs() && TINY <= r.abs() && r.abs() <= HUGE {
+                // a - r * b is exact, and a / b - r has the same sign as (a - r * b) / b
+                let rem = (-r).mul_add(b, a);
+                adjust(r, if b < 0.0 { -rem } else { rem }, mode)
+            } else {
+                let r = arith::div(&$fmt, a.to_bits() as u128, b.to_bits() as u128, mode);
+                <$ty>::from_bits(r as _)
+            }
+        }
+
+        /// Returns `a * b + c` with single rounding (fused multiply-add) as specific rounding mode.
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_mul_add(a: $ty, b: $ty, c: $ty, mode: &RoundingMode) -> $ty {
+            let p = a * b;
+            let r = a.mul_add(b, c);
+            if TINY <= p.abs()
+                && p.abs() <= HUGE
+                && is_safe(c)
+                && TINY <= r.abs()
+                && r.abs() <= HUGE
+            {
+                // ErrFma (Boldo and Muller), a * b + c = r + g + z exactly
+                let (s, z) = two_sum(c, a.mul_add(b, -p));
+                let (t, e) = two_sum(p, s);
+                let g = (t - r) + e;
+                // g + z keeps the sign of the residual, which is a multiple of the least subnormal
+                adjust(r, g + z, mode)
+            } else {
+                let r = arith::mul_add(
+                    &$fmt,
+                    a.to_bits() as u128,
+                    b.to_bits() as u128,
+                    c.to_bits() as u128,
+                    mode,
+                );
+                <$ty>::from_bits(r as _)
+            }
+        }
+
+        /// Returns `a.sqrt()` as specific rounding mode.
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_sqrt(a: $ty, mode: &RoundingMode) -> $ty {
+            if (TINY / <$ty>::EPSILON..=<$ty>::MAX).contains(&a) {
+                let r = a.sqrt();
+                adjust(r, (-r).mul_add(r, a), mode)
+            } else {
+                let r = arith::sqrt(&$fmt, a.to_bits() as u128, mode);
+                <$ty>::from_bits(r as _)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_pure_rust;
+
+#[cfg(test)]
+mod test {
+    use crate::r#impl::arith;
+    use crate::RoundingMode as Mode;
+
+    const MODES: [Mode; 4] = [
+        Mode::NearestTiesEven,
+        Mode::TowardPosInf,
+        Mode::TowardNegInf,
+        Mode::TowardZero,
+    ];
+
+    #[test]
+    #[cfg(feature = "f64_pure_rust")]
+    fn test_f64() {
+        use super::f64::*;
+        use crate::r#impl::format::F64;
+
+        let f = |x: f64| x.to_bits() as u128;
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        for _ in 0..20000 {
+            let mut next = || {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                match x % 4 {
+                    0 => f64::from_bits(x),
+                    1 => (x % 1024) as f64 / 64.0,
+                    _ => f64::from_bits(x & 0x800F_FFFF_FFFF_FFFF | 0x3FF0_0000_0000_0000),
+                }
+            };
+            let (a, b, c) = (next(), next(), next());
+            let (ba, bb, bc) = (f(a), f(b), f(c));
+            for mode in &MODES {
+                assert_eq!(f(round_add(a, b, mode)), arith::add(&F64, ba, bb, mode));
+                assert_eq!(f(round_sub(a, b, mode)), arith::sub(&F64, ba, bb, mode));
+                assert_eq!(f(round_mul(a, b, mode)), arith::mul(&F64, ba, bb, mode));
+                assert_eq!(f(round_div(a, b, mode)), arith::div(&F64, ba, bb, mode));
+                assert_eq!(f(round_sqrt(a, mode)), arith::sqrt(&F64, ba, mode));
+                assert_eq!(
+                    f(round_mul_add(a, b, c, mode)),
+                    arith::mul_add(&F64, ba, bb, bc, mode)
+                );
+            }
+        }
+    }
+
+    /// `c` close to `-a * b`, where the residual of the fused multiply-add is tiny.
+    #[test]
+    #[cfg(feature = "f64_pure_rust")]
+    fn test_mul_add_cancel_f64() {
+        use super::f64::*;
+        use crate::r#impl::format::F64;
+
+        let f = |x: f64| x.to_bits() as u128;
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        for _ in 0..20000 {
+            let mut next = || {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x
+            };
+            let a = f64::from_bits(next() & 0x800F_FFFF_FFFF_FFFF | 0x3FF0_0000_0000_0000);
+            let b = f64::from_bits(next() & 0x000F_FFFF_FFFF_FFFF | 0x3FF0_0000_0000_0000);
+            let p = -(a * b);
+            let c = f64::from_bits((p.to_bits() as i64 + (next() % 5) as i64 - 2) as u64);
+            for mode in &MODES {
+                assert_eq!(
+                    f(round_mul_add(a, b, c, mode)),
+                    arith::mul_add(&F64, f(a), f(b), f(c), mode),
+                    "{a:e} * {b:e} + {c:e} {mode:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "f32_pure_rust")]
+    fn test_f32() {
+        use super::f32::*;
+        use crate::r#impl::format::F32;
+
+        let f = |x: f32| x.to_bits() as u128;
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        for _ in 0..20000 {
+            let mut next = || {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                match x % 4 {
+                    0 => f32::from_bits(x as u32),
+                    1 => (x % 1024) as f32 / 64.0,
+                    _ => f32::from_bits(x as u32 & 0x807F_FFFF | 0x3F80_0000),
+                }
+            };
+            let (a, b, c) = (next(), next(), next());
+            let (ba, bb, bc) = (f(a), f(b), f(c));
+            for mode in &MODES {
+                assert_eq!(f(round_add(a, b, mode)), arith::add(&F32, ba, bb, mode));
+                assert_eq!(f(round_sub(a, b, mode)), arith::sub(&F32, ba, bb, mode));
+                assert_eq!(f(round_mul(a, b, mode)), arith::mul(&F32, ba, bb, mode));
+                assert_eq!(f(round_div(a, b, mode)), arith::div(&F32, ba, bb, mode));
+                assert_eq!(f(round_sqrt(a, mode)), arith::sqrt(&F32, ba, mode));
+                assert_eq!(
+                    f(round_mul_add(a, b, c, mode)),
+                    arith::mul_add(&F32, ba, bb, bc, mode)
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "f32_pure_rust")]
+    fn test_mul_add_cancel_f32() {
+        use super::f32::*;
+        use crate::r#impl::format::F32;
+
+        let f = |x: f32| x.to_bits() as u128;
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        for _ in 0..20000 {
+            let mut next = || {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x as u32
+            };
+            let a = f32::from_bits(next() & 0x807F_FFFF | 0x3F80_0000);
+            let b = f32::from_bits(next() & 0x007F_FFFF | 0x3F80_0000);
+            let p = -(a * b);
+            let c = f32::from_bits((p.to_bits() as i32 + (next() % 5) as i32 - 2) as u32);
+            for mode in &MODES {
+                assert_eq!(
+                    f(round_mul_add(a, b, c, mode)),
+                    arith::mul_add(&F32, f(a), f(b), f(c), mode),
+                    "{a:e} * {b:e} + {c:e} {mode:?}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,4 @@
+crate::r#impl::pure_rust::impl_pure_rust!(f32, F32);
+
+#[cfg(feature = "rayon")]
+crate::r#impl::par::impl_round_slice!(f32);
@@ -0,0 +1,591 @@
+//! Functions for packed vectors of [`f32`] and [`f64`].
+//!
+//! Each op computes all lanes under a single rounding mode switch,
+//! by SSE (and AVX if the CPU supports it) instructions and MXCSR on x86_64.
+//! Otherwise, it falls back to a scalar loop over the functions of [`f32`][mod@crate::f32]
+//! and [`f64`][mod@crate::f64].
+//!
+//! Vectors are represented as arrays, e.g. `f64x4` is `[f64; 4]`.
+//!
+//! Notes, NaN payloads follow the hardware on x86_64,
+//! thus they may differ from the ones of the scalar functions (e.g., on softfloat).
+//!
+//! ```
+//! use srmfpa::simd::{round_add_f64x2, round_div_f32x4};
+//! use srmfpa::RoundingMode;
+//!
+//! let a = round_add_f64x2([0.1, -0.1], [0.2, -0.2], &RoundingMode::TowardPosInf);
+//! assert_eq!(a, [0.30000000000000004, -0.3]);
+//!
+//! let a = round_div_f32x4([1.0; 4], [3.0; 4], &RoundingMode::TowardNegInf);
+//! assert_eq!(a, [srmfpa::f32::floor_div(1.0, 3.0); 4]);
+//! ```
+
+use crate::RoundingMode;
+
+macro_rules! impl_simd {
+    (
+        $ty:ident, $lanes:ident, $n:literal,
+        $add:ident, $sub:ident, $mul:ident, $div:ident, $mul_add:ident, $sqrt:ident,
+    ) => {
+        #[doc = concat!("Returns `a + b` of `", stringify!($lanes), "` lane-wise as specific rounding mode.")]
+        ///
+        /// # Safety
+        ///
+        /// Panics when fail to set/restore rounding mode.
+        #[must_use = "function returns a new vector and does not mutate the original value"]
+        #[inline]
+        pub fn $add(a: [$ty; $n], b: [$ty; $n], mode: &RoundingMode) -> [$ty; $n] {
+            #[cfg(target_arch = "x86_64")]
+            return x86_64::$lanes::add(a, b, mode);
+            #[cfg(not(target_arch = "x86_64"))]
+            return core::array::from_fn(|i| crate::$ty::round_add(a[i], b[i], mode));
+        }
+
+        #[doc = concat!("Returns `a - b` of `", stringify!($lanes), "` lane-wise as specific rounding mode.")]
+        ///
+        /// # Safety
+        ///
+        /// Panics when fail to set/restore rounding mode.
+        #[must_use = "function returns a new vector and does not mutate the original value"]
+        #[inline]
+        pub fn $sub(a: [$ty; $n], b: [$ty; $n], mode: &RoundingMode) -> [$ty; $n] {
+            #[cfg(target_arch = "x86_64")]
+            return x86_64::$lanes::sub(a, b, mode);
+            #[cfg(not(target_arch = "x86_64"))]
+            return core::array::from_fn(|i| crate::$ty::round_sub(a[i], b[i], mode));
+        }
+
+        #[doc = concat!("Returns `a * b` of `", stringify!($lanes), "` lane-wise as specific rounding mode.")]
+        ///
+        /// # Safety
+        ///
+        /// Panics when fail to set/restore rounding mode.
+        #[must_use = "function returns a new vector and does not mutate the original value"]
+        #[inline]
+        pub fn $mul(a: [$ty; $n], b: [$ty; $n], mode: &RoundingMode) -> [$ty; $n] {
+            #[cfg(target_arch = "x86_64")]
+            return x86_64::$lanes::mul(a, b, mode);
+            #[cfg(not(target_arch = "x86_64"))]
+            return core::array::from_fn(|i| crate::$ty::round_mul(a[i], b[i], mode));
+        }
+
+        #[doc = concat!("Returns `a / b` of `", stringify!($lanes), "` lane-wise as specific rounding mode.")]
+        ///
+        /// # Safety
+        ///
+        /// Panics when fail to set/restore rounding mode.
+        #[must_use = "function returns a new vector and does not mutate the original value"]
+        #[inline]
+        pub fn $div(a: [$ty; $n], b: [$ty; $n], mode: &RoundingMode) -> [$ty; $n] {
+            #[cfg(target_arch = "x86_64")]
+            return x86_64::$lanes::div(a, b, mode);
+            #[cfg(not(target_arch = "x86_64"))]
+            return core::array::from_fn(|i| crate::$ty::round_div(a[i], b[i], mode));
+        }
+
+        #[doc = concat!("Returns `a * b + c` of `", stringify!($lanes), "` lane-wise with single rounding (fused multiply-add) as specific rounding mode.")]
+        ///
+        /// It falls back to the scalar loop when the CPU does not support FMA.
+        ///
+        /// # Safety
+        ///
+        /// Panics when fail to set/restore rounding mode.
+        #[must_use = "function returns a new vector and does not mutate the original value"]
+        #[inline]
+        pub fn $mul_add(
+            a: [$ty; $n],
+            b: [$ty; $n],
+            c: [$ty; $n],
+            mode: &RoundingMode,
+        ) -> [$ty; $n] {
+            #[cfg(target_arch = "x86_64")]
+            if std::arch::is_x86_feature_detected!("fma") {
+                return unsafe { x86_64::$lanes::mul_add(a, b, c, mode) };
+            }
+            core::array::from_fn(|i| crate::$ty::round_mul_add(a[i], b[i], c[i], mode))
+        }
+
+        #[doc = concat!("Returns `a.sqrt()` of `", stringify!($lanes), "` lane-wise as specific rounding mode.")]
+        ///
+        /// # Safety
+        ///
+        /// Panics when fail to set/restore rounding mode.
+        #[must_use = "function returns a new vector and does not mutate the original value"]
+        #[inline]
+        pub fn $sqrt(a: [$ty; $n], mode: &RoundingMode) -> [$ty; $n] {
+            #[cfg(target_arch = "x86_64")]
+            return x86_64::$lanes::sqrt(a, mode);
+            #[cfg(not(target_arch = "x86_64"))]
+            return core::array::from_fn(|i| crate::$ty::round_sqrt(a[i], mode));
+        }
+    };
+}
+
+impl_simd!(
+    f64,
+    f64x2,
+    2,
+    round_add_f64x2,
+    round_sub_f64x2,
+    round_mul_f64x2,
+    round_div_f64x2,
+    round_mul_add_f64x2,
+    round_sqrt_f64x2,
+);
+impl_simd!(
+    f64,
+    f64x4,
+    4,
+    round_add_f64x4,
+    round_sub_f64x4,
+    round_mul_f64x4,
+    round_div_f64x4,
+    round_mul_add_f64x4,
+    round_sqrt_f64x4,
+);
+impl_simd!(
+    f32,
+    f32x4,
+    4,
+    round_add_f32x4,
+    round_sub_f32x4,
+    round_mul_f32x4,
+    round_div_f32x4,
+    round_mul_add_f32x4,
+    round_sqrt_f32x4,
+);
+impl_simd!(
+    f32,
+    f32x8,
+    8,
+    round_add_f32x8,
+    round_sub_f32x8,
+    round_mul_f32x8,
+    round_div_f32x8,
+    round_mul_add_f32x8,
+    round_sqrt_f32x8,
+);
+
+/// Kernels by packed instructions under MXCSR rounding control.
+#[cfg(target_arch = "x86_64")]
+mod x86_64 {
+    /// Kernels of 128-bit vectors by SSE.
+    macro_rules! impl_sse {
+        ($ty:ident, $n:literal, $vec:ident, $suffix:literal) => {
+            use core::arch::x86_64::$vec;
+            use core::mem::transmute;
+
+            use crate::r#impl::asm::with_mxcsr;
+            use crate::RoundingMode;
+
+            #[inline(always)]
+            fn pack(x: [$ty; $n]) -> $vec {
+                unsafe { transmute::<[$ty; $n], $vec>(x) }
+            }
+
+            #[inline(always)]
+            fn unpack(x: $vec) -> [$ty; $n] {
+                unsafe { transmute::<$vec, [$ty; $n]>(x) }
+            }
+
+            #[inline(always)]
+            fn binary<const OP: u8>(a: [$ty; $n], b: [$ty; $n], mode: &RoundingMode) -> [$ty; $n] {
+                let (mut a, b) = (pack(a), pack(b));
+                macro_rules! op {
+                    ($op:literal) => {
+                        with_mxcsr!(
+                            mode,
+                            concat!($op, $suffix, " {a}, {b}"),
+                            a = inout(xmm_reg) a,
+                            b = in(xmm_reg) b,
+                        )
+                    };
+                }
+                match OP {
+                    b'+' => op!("add"),
+                    b'-' => op!("sub"),
+                    b'*' => op!("mul"),
+                    _ => op!("div"),
+                }
+                unpack(a)
+            }
+
+            #[inline]
+            pub(crate) fn add(a: [$ty; $n], b: [$ty; $n], mode: &RoundingMode) -> [$ty; $n] {
+                binary::<b'+'>(a, b, mode)
+            }
+
+            #[inline]
+            pub(crate) fn sub(a: [$ty; $n], b: [$ty; $n], mode: &RoundingMode) -> [$ty; $n] {
+                binary::<b'-'>(a, b, mode)
+            }
+
+            #[inline]
+            pub(crate) fn mul(a: [$ty; $n], b: [$ty; $n], mode: &RoundingMode) -> [$ty; $n] {
+                binary::<b'*'>(a, b, mode)
+            }
+
+            #[inline]
+            pub(crate) fn div(a: [$ty; $n], b: [$ty; $n], mode: &RoundingMode) -> [$ty; $n] {
+                binary::<b'/'>(a, b, mode)
+            }
+
+            /// Requires FMA.
+            #[inline]
+            pub(crate) unsafe fn mul_add(
+                a: [$ty; $n],
+                b: [$ty; $n],
+                c: [$ty; $n],
+                mode: &RoundingMode,
+            ) -> [$ty; $n] {
+                let (mut a, b, c) = (pack(a), pack(b), pack(c));
+                with_mxcsr!(
+                    mode,
+                    concat!("vfmadd213", $suffix, " {a}, {b}, {c}"),
+                    a = inout(xmm_reg) a,
+                    b = in(xmm_reg) b,
+                    c = in(xmm_reg) c,
+                );
+                unpack(a)
+            }
+
+            #[inline]
+            pub(crate) fn sqrt(a: [$ty; $n], mode: &RoundingMode) -> [$ty; $n] {
+                let mut a = pack(a);
+                with_mxcsr!(
+                    mode,
+                    concat!("sqrt", $suffix, " {a}, {a}"),
+                    a = inout(xmm_reg) a,
+                );
+                unpack(a)
+            }
+        };
+    }
+
+    /// Kernels of 256-bit vectors by AVX,
+    /// or by a pair of SSE instructions when the CPU does not support AVX.
+    macro_rules! impl_avx {
+        ($ty:ident, $n:literal, $vec:ident, $half:ident, $suffix:literal) => {
+            use core::arch::x86_64::{$half, $vec};
+            use core::mem::transmute;
+
+            use crate::r#impl::asm::with_mxcsr;
+            use crate::RoundingMode;
+
+            #[inline(always)]
+            fn pack(x: [$ty; $n]) -> $vec {
+                unsafe { transmute::<[$ty; $n], $vec>(x) }
+            }
+
+            #[inline(always)]
+            fn unpack(x: $vec) -> [$ty; $n] {
+                unsafe { transmute::<$vec, [$ty; $n]>(x) }
+            }
+
+            #[inline(always)]
+            fn split(x: [$ty; $n]) -> [$half; 2] {
+                unsafe { transmute::<[$ty; $n], [$half; 2]>(x) }
+            }
+
+            #[inline(always)]
+            fn join(x: [$half; 2]) -> [$ty; $n] {
+                unsafe { transmute::<[$half; 2], [$ty; $n]>(x) }
+            }
+
+            #[target_feature(enable = "avx")]
+            unsafe fn binary_avx<const OP: u8>(
+                a: [$ty; $n],
+                b: [$ty; $n],
+                mode: &RoundingMode,
+            ) -> [$ty; $n] {
+                let (mut a, b) = (pack(a), pack(b));
+                macro_rules! op {
+                    ($op:literal) => {
+                        with_mxcsr!(
+                            mode,
+                            concat!("v", $op, $suffix, " {a}, {a}, {b}"),
+                            a = inout(ymm_reg) a,
+                            b = in(ymm_reg) b,
+                        )
+                    };
+                }
+                match OP {
+                    b'+' => op!("add"),
+                    b'-' => op!("sub"),
+                    b'*' => op!("mul"),
+                    _ => op!("div"),
+                }
+                unpack(a)
+            }
+
+            #[inline(always)]
+            fn binary_sse<const OP: u8>(a: [$ty; $n], b: [$ty; $n], mode: &RoundingMode) -> [$ty; $n] {
+                let ([mut a0, mut a1], [b0, b1]): ([$half; 2], [$half; 2]) =
+                    (split(a), split(b));
+                macro_rules! op {
+                    ($op:literal) => {
+                        with_mxcsr!(
+                            mode,
+                            concat!($op, $suffix, " {a0}, {b0}\n", $op, $suffix, " {a1}, {b1}"),
+                            a0 = inout(xmm_reg) a0,
+                            a1 = inout(xmm_reg) a1,
+                            b0 = in(xmm_reg) b0,
+                            b1 = in(xmm_reg) b1,
+                        )
+                    };
+                }
+                match OP {
+                    b'+' => op!("add"),
+                    b'-' => op!("sub"),
+                    b'*' => op!("mul"),
+                    _ => op!("div"),
+                }
+                join([a0, a1])
+            }
+
+            #[inline(always)]
+            fn binary<const OP: u8>(a: [$ty; $n], b: [$ty; $n], mode: &RoundingMode) -> [$ty; $n] {
+                if std::arch::is_x86_feature_detected!("avx") {
+                    unsafe { binary_avx::<OP>(a, b, mode) }
+                } else {
+                    binary_sse::<OP>(a, b, mode)
+                }
+            }
+
+            #[inline]
+            pub(crate) fn add(a: [$ty; $n], b: [$ty; $n], mode: &RoundingMode) -> [$ty; $n] {
+                binary::<b'+'>(a, b, mode)
+            }
+
+            #[inline]
+            pub(crate) fn sub(a: [$ty; $n], b: [$ty; $n], mode: &RoundingMode) -> [$ty; $n] {
+                binary::<b'-'>(a, b, mode)
+            }
+
+            #[inline]
+            pub(crate) fn mul(a: [$ty; $n], b: [$ty; $n], mode: &RoundingMode) -> [$ty; $n] {
+                binary::<b'*'>(a, b, mode)
+            }
+
+            #[inline]
+            pub(crate) fn div(a: [$ty; $n], b: [$ty; $n], mode: &RoundingMode) -> [$ty; $n] {
+                binary::<b'/'>(a, b, mode)
+            }
+
+            /// Requires FMA (thus AVX).
+            #[target_feature(enable = "avx,fma")]
+            pub(crate) unsafe fn mul_add(
+                a: [$ty; $n],
+                b: [$ty; $n],
+                c: [$ty; $n],
+                mode: &RoundingMode,
+            ) -> [$ty; $n] {
+                let (mut a, b, c) = (pack(a), pack(b), pack(c));
+                with_mxcsr!(
+                    mode,
+                    concat!("vfmadd213", $suffix, " {a}, {b}, {c}"),
+                    a = inout(ymm_reg) a,
+                    b = in(ymm_reg) b,
+                    c = in(ymm_reg) c,
+                );
+                unpack(a)
+            }
+
+            #[target_feature(enable = "avx")]
+            unsafe fn sqrt_avx(a: [$ty; $n], mode: &RoundingMode) -> [$ty; $n] {
+                let mut a = pack(a);
+                with_mxcsr!(
+                    mode,
+                    concat!("vsqrt", $suffix, " {a}, {a}"),
+                    a = inout(ymm_reg) a,
+                );
+                unpack(a)
+            }
+
+            #[inline]
+            pub(crate) fn sqrt(a: [$ty; $n], mode: &RoundingMode) -> [$ty; $n] {
+                if std::arch::is_x86_feature_detected!("avx") {
+                    return unsafe { sqrt_avx(a, mode) };
+                }
+                let [mut a0, mut a1] = split(a);
+                with_mxcsr!(
+                    mode,
+                    concat!("sqrt", $suffix, " {a0}, {a0}\n", "sqrt", $suffix, " {a1}, {a1}"),
+                    a0 = inout(xmm_reg) a0,
+                    a1 = inout(xmm_reg) a1,
+                );
+                join([a0, a1])
+            }
+        };
+    }
+
+    pub(super) mod f64x2 {
+        impl_sse!(f64, 2, __m128d, "pd");
+    }
+
+    pub(super) mod f32x4 {
+        impl_sse!(f32, 4, __m128, "ps");
+    }
+
+    pub(super) mod f64x4 {
+        impl_avx!(f64, 4, __m256d, __m128d, "pd");
+    }
+
+    pub(super) mod f32x8 {
+        impl_avx!(f32, 8, __m256, __m128, "ps");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::RoundingMode as Mode;
+
+    const MODES: [Mode; 4] = [
+        Mode::NearestTiesEven,
+        Mode::TowardPosInf,
+        Mode::TowardNegInf,
+        Mode::TowardZero,
+    ];
+
+    fn xorshift(x: &mut u64) -> u64 {
+        *x ^= *x << 13;
+        *x ^= *x >> 7;
+        *x ^= *x << 17;
+        *x
+    }
+
+    macro_rules! check {
+        (
+            $ty:ident, $n:literal, $next:expr,
+            $add:ident, $sub:ident, $mul:ident, $div:ident, $mul_add:ident, $sqrt:ident,
+        ) => {{
+            use crate::$ty::{
+                round_add, round_div, round_mul, round_mul_add, round_sqrt, round_sub,
+            };
+
+            // NaN payloads follow the hardware
+            let f = |x: [$ty; $n]| {
+                x.map(|x| {
+                    if x.is_nan() {
+                        <$ty>::NAN.to_bits()
+                    } else {
+                        x.to_bits()
+                    }
+                })
+            };
+            let next = $next;
+            for _ in 0..2000 {
+                let a: [$ty; $n] = core::array::from_fn(|_| next());
+                let b: [$ty; $n] = core::array::from_fn(|_| next());
+                let c: [$ty; $n] = core::array::from_fn(|_| next());
+                for mode in &MODES {
+                    let e = |g: &dyn Fn(usize) -> $ty| f(core::array::from_fn(g));
+                    assert_eq!(f($add(a, b, mode)), e(&|i| round_add(a[i], b[i], mode)));
+                    assert_eq!(f($sub(a, b, mode)), e(&|i| round_sub(a[i], b[i], mode)));
+                    assert_eq!(f($mul(a, b, mode)), e(&|i| round_mul(a[i], b[i], mode)));
+                    assert_eq!(f($div(a, b, mode)), e(&|i| round_div(a[i], b[i], mode)));
+                    assert_eq!(f($sqrt(a, mode)), e(&|i| round_sqrt(a[i], mode)));
+                    assert_eq!(
+                        f($mul_add(a, b, c, mode)),
+                        e(&|i| round_mul_add(a[i], b[i], c[i], mode))
+                    );
+                }
+            }
+        }};
+    }
+
+    #[test]
+    fn test_f64() {
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        let mut next = || {
+            let r = xorshift(&mut x);
+            match r % 4 {
+                0 => f64::from_bits(r),
+                1 => (r % 1024) as f64 / 64.0,
+                _ => f64::from_bits(r & 0x800F_FFFF_FFFF_FFFF | 0x3FF0_0000_0000_0000),
+            }
+        };
+        check!(
+            f64,
+            2,
+            &mut next,
+            round_add_f64x2,
+            round_sub_f64x2,
+            round_mul_f64x2,
+            round_div_f64x2,
+            round_mul_add_f64x2,
+            round_sqrt_f64x2,
+        );
+        check!(
+            f64,
+            4,
+            &mut next,
+            round_add_f64x4,
+            round_sub_f64x4,
+            round_mul_f64x4,
+            round_div_f64x4,
+            round_mul_add_f64x4,
+            round_sqrt_f64x4,
+        );
+    }
+
+    #[test]
+    fn test_f32() {
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        let mut next = || {
+            let r = xorshift(&mut x);
+            match r % 4 {
+                0 => f32::from_bits(r as u32),
+                1 => (r % 1024) as f32 / 64.0,
+                _ => f32::from_bits(r as u32 & 0x807F_FFFF | 0x3F80_0000),
+            }
+        };
+        check!(
+            f32,
+            4,
+            &mut next,
+            round_add_f32x4,
+            round_sub_f32x4,
+            round_mul_f32x4,
+            round_div_f32x4,
+            round_mul_add_f32x4,
+            round_sqrt_f32x4,
+        );
+        check!(
+            f32,
+            8,
+            &mut next,
+            round_add_f32x8,
+            round_sub_f32x8,
+            round_mul_f32x8,
+            round_div_f32x8,
+            round_mul_add_f32x8,
+            round_sqrt_f32x8,
+        );
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn test_mode_restored() {
+        // the rounding control of MXCSR is to nearest after each op
+        let rc = || {
+            let mut csr = 0u32;
+            unsafe {
+                core::arch::asm!("stmxcsr [{csr}]", csr = in(reg) &mut csr, options(nostack));
+            }
+            csr & 0x6000
+        };
+        for mode in &MODES {
+            let _ = round_add_f64x4([0.1; 4], [0.2; 4], mode);
+            assert_eq!(rc(), 0, "f64x4 {mode:?}");
+            let _ = round_mul_add_f64x4([0.1; 4], [0.2; 4], [0.3; 4], mode);
+            assert_eq!(rc(), 0, "f64x4 {mode:?}");
+            let _ = round_sqrt_f32x8([2.0; 8], mode);
+            assert_eq!(rc(), 0, "f32x8 {mode:?}");
+            let _ = round_div_f32x8([1.0; 8], [3.0; 8], mode);
+            assert_eq!(rc(), 0, "f32x8 {mode:?}");
+        }
+    }
+}
@@ -13,3 +13,53 @@ pub(crate) mod f64;
     feature = "f64_softfloat"
 ))]
 pub(crate) mod rounding_mode;
+#[cfg(any(
+    feature = "f128_softfloat",
+    feature = "f16_softfloat",
+    feature = "f32_softfloat",
+    feature = "f64_softfloat"
+))]
+pub(crate) mod tininess;
+
+/// Calls the SoftFloat op `f` taking `xs` under the thread's settings,
+/// that is, treats subnormal operands and flushes the result by the denormal mode,
+/// replaces the NaN result by the NaN semantics and handles the exceptions by the handlers.
+#[cfg(any(
+    feature = "f128_softfloat",
+    feature = "f16_softfloat",
+    feature = "f32_softfloat",
+    feature = "f64_softfloat"
+))]
+#[track_caller]
+#[inline]
+pub(crate) fn call<T: crate::RoundingFloat, const N: usize>(
+    op: crate::Operation,
+    mode: &crate::RoundingMode,
+    xs: [T; N],
+    f: impl FnOnce([T; N]) -> T,
+) -> T {
+    use srmfpa_berkeley_softfloat_3::{c_get_exception_flags, c_set_exception_flags};
+
+    crate::exception::handle_softfloat(op, mode, || {
+        let denormal = crate::env::current_softfloat_denormal_mode();
+        let xs = xs.map(|x| denormal.input(x));
+
+        let mut r = f(xs);
+        // NaN
+        if r.partial_cmp(&r).is_none() {
+            if let Some(nan) = crate::current_softfloat_nan_semantics() {
+                let bits = xs.map(|x| x.to_raw());
+                r = T::from_raw(T::FORMAT.nan_result(op, &bits, nan));
+            }
+        }
+
+        let mut flags = crate::ExceptionFlags::empty();
+        let r = denormal.output(r, &mut flags);
+        if !flags.is_empty() {
+            unsafe {
+                c_set_exception_flags(c_get_exception_flags() | flags.as_berkeley_softfloat())
+            };
+        }
+        r
+    })
+}
@@ -1,6 +1,7 @@
 use srmfpa_berkeley_softfloat_3::*;
 
-use crate::RoundingMode;
+use crate::r#impl::softfloat::call;
+use crate::{Operation, RoundingMode};
 
 #[inline(always)]
 fn from_f128(value: f128) -> float128_t {
@@ -19,55 +20,73 @@ fn to_f128(value: float128_t) -> f128 {
 
 /// Returns `a + b` as specific rounding mode.
 #[must_use = "function returns a new number and does not mutate the original value"]
+#[track_caller]
 #[inline]
 pub fn round_add(a: f128, b: f128, mode: &RoundingMode) -> f128 {
-    let r = unsafe { c_add_f128(mode.as_berkeley_softfloat(), from_f128(a), from_f128(b)) };
-    to_f128(r)
+    call(Operation::Add, mode, [a, b], |[a, b]| {
+        let r = unsafe { c_add_f128(mode.as_berkeley_softfloat(), from_f128(a), from_f128(b)) };
+        to_f128(r)
+    })
 }
 
 /// Returns `a - b` as specific rounding mode.
 #[must_use = "function returns a new number and does not mutate the original value"]
+#[track_caller]
 #[inline]
 pub fn round_sub(a: f128, b: f128, mode: &RoundingMode) -> f128 {
-    let r = unsafe { c_sub_f128(mode.as_berkeley_softfloat(), from_f128(a), from_f128(b)) };
-    to_f128(r)
+    call(Operation::Sub, mode, [a, b], |[a, b]| {
+        let r = unsafe { c_sub_f128(mode.as_berkeley_softfloat(), from_f128(a), from_f128(b)) };
+        to_f128(r)
+    })
 }
 
 /// Returns `a * b` as specific rounding mode.
 #[must_use = "function returns a new number and does not mutate the original value"]
+#[track_caller]
 #[inline]
 pub fn round_mul(a: f128, b: f128, mode: &RoundingMode) -> f128 {
-    let r = unsafe { c_mul_f128(mode.as_berkeley_softfloat(), from_f128(a), from_f128(b)) };
-    to_f128(r)
+    call(Operation::Mul, mode, [a, b], |[a, b]| {
+        let r = unsafe { c_mul_f128(mode.as_berkeley_softfloat(), from_f128(a), from_f128(b)) };
+        to_f128(r)
+    })
 }
 
 /// Returns `a / b` as specific rounding mode.
 #[must_use = "function returns a new number and does not mutate the original value"]
+#[track_caller]
 #[inline]
 pub fn round_div(a: f128, b: f128, mode: &RoundingMode) -> f128 {
-    let r = unsafe { c_div_f128(mode.as_berkeley_softfloat(), from_f128(a), from_f128(b)) };
-    to_f128(r)
+    call(Operation::Div, mode, [a, b], |[a, b]| {
+        let r = unsafe { c_div_f128(mode.as_berkeley_softfloat(), from_f128(a), from_f128(b)) };
+        to_f128(r)
+    })
 }
 
 /// Returns `a * b + c` with single rounding (fused multiply-add) as specific rounding mode.
 #[must_use = "function returns a new number and does not mutate the original value"]
+#[track_caller]
 #[inline]
 pub fn round_mul_add(a: f128, b: f128, c: f128, mode: &RoundingMode) -> f128 {
-    let r = unsafe {
-        c_fma_f128(
-            mode.as_berkeley_softfloat(),
-            from_f128(a),
-            from_f128(b),
-            from_f128(c),
-        )
-    };
-    to_f128(r)
+    call(Operation::MulAdd, mode, [a, b, c], |[a, b, c]| {
+        let r = unsafe {
+            c_fma_f128(
+                mode.as_berkeley_softfloat(),
+                from_f128(a),
+                from_f128(b),
+                from_f128(c),
+            )
+        };
+        to_f128(r)
+    })
 }
 
 /// Returns `a.sqrt()` as specific rounding mode.
 #[must_use = "function returns a new number and does not mutate the original value"]
+#[track_caller]
 #[inline]
 pub fn round_sqrt(a: f128, mode: &RoundingMode) -> f128 {
-    let r = unsafe { c_sqrt_f128(mode.as_berkeley_softfloat(), from_f128(a)) };
-    to_f128(r)
+    call(Operation::Sqrt, mode, [a], |[a]| {
+        let r = unsafe { c_sqrt_f128(mode.as_berkeley_softfloat(), from_f128(a)) };
+        to_f128(r)
+    })
 }
@@ -1,6 +1,7 @@
 use srmfpa_berkeley_softfloat_3::*;
 
-use crate::RoundingMode;
+use crate::r#impl::softfloat::call;
+use crate::{Operation, RoundingMode};
 
 #[inline(always)]
 fn from_f16(value: f16) -> float16_t {
@@ -14,55 +15,73 @@ fn to_f16(value: float16_t) -> f16 {
 
 /// Returns `a + b` as specific rounding mode.
 #[must_use = "function returns a new number and does not mutate the original value"]
+#[track_caller]
 #[inline]
 pub fn round_add(a: f16, b: f16, mode: &RoundingMode) -> f16 {
-    let r = unsafe { c_add_f16(mode.as_berkeley_softfloat(), from_f16(a), from_f16(b)) };
-    to_f16(r)
+    call(Operation::Add, mode, [a, b], |[a, b]| {
+        let r = unsafe { c_add_f16(mode.as_berkeley_softfloat(), from_f16(a), from_f16(b)) };
+        to_f16(r)
+    })
 }
 
 /// Returns `a - b` as specific rounding mode.
 #[must_use = "function returns a new number and does not mutate the original value"]
+#[track_caller]
 #[inline]
 pub fn round_sub(a: f16, b: f16, mode: &RoundingMode) -> f16 {
-    let r = unsafe { c_sub_f16(mode.as_berkeley_softfloat(), from_f16(a), from_f16(b)) };
-    to_f16(r)
+    call(Operation::Sub, mode, [a, b], |[a, b]| {
+        let r = unsafe { c_sub_f16(mode.as_berkeley_softfloat(), from_f16(a), from_f16(b)) };
+        to_f16(r)
+    })
 }
 
 /// Returns `a * b` as specific rounding mode.
 #[must_use = "function returns a new number and does not mutate the original value"]
+#[track_caller]
 #[inline]
 pub fn round_mul(a: f16, b: f16, mode: &RoundingMode) -> f16 {
-    let r = unsafe { c_mul_f16(mode.as_berkeley_softfloat(), from_f16(a), from_f16(b)) };
-    to_f16(r)
+    call(Operation::Mul, mode, [a, b], |[a, b]| {
+        let r = unsafe { c_mul_f16(mode.as_berkeley_softfloat(), from_f16(a), from_f16(b)) };
+        to_f16(r)
+    })
 }
 
 /// Returns `a * b` as specific rounding mode.
 #[must_use = "function returns a new number and does not mutate the original value"]
+#[track_caller]
 #[inline]
 pub fn round_div(a: f16, b: f16, mode: &RoundingMode) -> f16 {
-    let r = unsafe { c_div_f16(mode.as_berkeley_softfloat(), from_f16(a), from_f16(b)) };
-    to_f16(r)
+    call(Operation::Div, mode, [a, b], |[a, b]| {
+        let r = unsafe { c_div_f16(mode.as_berkeley_softfloat(), from_f16(a), from_f16(b)) };
+        to_f16(r)
+    })
 }
 
 /// Returns `a * b + c` with single rounding (fused multiply-add) as specific rounding mode.
 #[must_use = "function returns a new number and does not mutate the original value"]
+#[track_caller]
 #[inline]
 pub fn round_mul_add(a: f16, b: f16, c: f16, mode: &RoundingMode) -> f16 {
-    let r = unsafe {
-        c_fma_f16(
-            mode.as_berkeley_softfloat(),
-            from_f16(a),
-            from_f16(b),
-            from_f16(c),
-        )
-    };
-    to_f16(r)
+    call(Operation::MulAdd, mode, [a, b, c], |[a, b, c]| {
+        let r = unsafe {
+            c_fma_f16(
+                mode.as_berkeley_softfloat(),
+                from_f16(a),
+                from_f16(b),
+                from_f16(c),
+            )
+        };
+        to_f16(r)
+    })
 }
 
 /// Returns `a.sqrt()` as specific rounding mode.
 #[must_use = "function returns a new number and does not mutate the original value"]
+#[track_caller]
 #[inline]
 pub fn round_sqrt(a: f16, mode: &RoundingMode) -> f16 {
-    let r = unsafe { c_sqrt_f16(mode.as_berkeley_softfloat(), from_f16(a)) };
-    to_f16(r)
+    call(Operation::Sqrt, mode, [a], |[a]| {
+        let r = unsafe { c_sqrt_f16(mode.as_berkeley_softfloat(), from_f16(a)) };
+        to_f16(r)
+    })
 }
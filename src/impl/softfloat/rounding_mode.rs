@@ -3,8 +3,19 @@ use srmfpa_berkeley_softfloat_3::*;
 use crate::RoundingMode;
 
 impl RoundingMode {
+    /// Returns the corresponding rounding mode of Berkeley SoftFloat (e.g. `softfloat_round_max`).
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(
+            feature = "f16_softfloat",
+            feature = "f32_softfloat",
+            feature = "f64_softfloat",
+            feature = "f128_softfloat"
+        )))
+    )]
+    #[must_use]
     #[inline]
-    pub(crate) fn as_berkeley_softfloat(&self) -> u8 {
+    pub fn as_berkeley_softfloat(&self) -> u8 {
         match self {
             Self::NearestTiesEven => softfloat_round_near_even,
             Self::TowardPosInf => softfloat_round_max,
@@ -12,4 +23,27 @@ impl RoundingMode {
             Self::TowardZero => softfloat_round_minMag,
         }
     }
+
+    /// Returns the mode of Berkeley SoftFloat's rounding mode,
+    /// `None` on the modes which are not IEEE 754 rounding direction attributes (e.g. round to odd).
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(
+            feature = "f16_softfloat",
+            feature = "f32_softfloat",
+            feature = "f64_softfloat",
+            feature = "f128_softfloat"
+        )))
+    )]
+    #[must_use]
+    #[inline]
+    pub fn from_berkeley_softfloat(mode: u8) -> Option<Self> {
+        match mode {
+            softfloat_round_near_even => Some(Self::NearestTiesEven),
+            softfloat_round_max => Some(Self::TowardPosInf),
+            softfloat_round_min => Some(Self::TowardNegInf),
+            softfloat_round_minMag => Some(Self::TowardZero),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,24 @@
+use srmfpa_berkeley_softfloat_3::*;
+
+use crate::Tininess;
+
+impl Tininess {
+    /// Returns the corresponding tininess detection of Berkeley SoftFloat (e.g. `softfloat_tininess_afterRounding`).
+    #[inline]
+    pub(crate) const fn as_berkeley_softfloat(&self) -> u8 {
+        match self {
+            Self::BeforeRounding => softfloat_tininess_beforeRounding,
+            Self::AfterRounding => softfloat_tininess_afterRounding,
+        }
+    }
+
+    /// Returns the tininess detection of Berkeley SoftFloat's one.
+    #[inline]
+    pub(crate) const fn from_berkeley_softfloat(tininess: u8) -> Self {
+        if tininess == softfloat_tininess_beforeRounding {
+            Self::BeforeRounding
+        } else {
+            Self::AfterRounding
+        }
+    }
+}
@@ -0,0 +1,260 @@
+//! Stochastic rounding by [`rand_core`].
+//!
+//! An op draws a uniform `u64` from the RNG, and rounds the exact result up in magnitude
+//! iff the discarded fraction of the ulp (in 64 bits) plus it carries,
+//! that is, with the probability proportional to the distance to the neighbor toward zero.
+//! They are evaluated by the integer ops of [`FpEnv`],
+//! thus they depend on neither `<fenv.h>` nor the backend.
+
+use rand_core::{impls, RngCore, SeedableRng};
+
+use crate::FpEnv;
+
+/// Returns the environment rounding stochastically by a draw from `rng`.
+#[inline]
+pub(crate) fn env<R: RngCore + ?Sized>(rng: &mut R) -> FpEnv {
+    let mut env = FpEnv::default();
+    env.random = Some(rng.next_u64());
+    env
+}
+
+/// Small and fast seeded RNG by SplitMix64, for reproducible stochastic rounding.
+///
+/// It is not cryptographically secure, and any [`RngCore`] can be used instead.
+///
+/// ```
+/// use rand_core::{RngCore, SeedableRng};
+/// use srmfpa::SplitMix64;
+///
+/// let mut a = SplitMix64::seed_from_u64(42);
+/// let mut b = SplitMix64::seed_from_u64(42);
+/// assert_eq!(a.next_u64(), b.next_u64());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "stochastic")))]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    /// Returns the RNG with the state `seed`.
+    #[must_use]
+    #[inline]
+    pub const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+}
+
+impl RngCore for SplitMix64 {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    #[inline]
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dst)
+    }
+}
+
+impl SeedableRng for SplitMix64 {
+    type Seed = [u8; 8];
+
+    #[inline]
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::new(u64::from_le_bytes(seed))
+    }
+
+    /// Returns the RNG with the state `state`, as [`SplitMix64::new`].
+    #[inline]
+    fn seed_from_u64(state: u64) -> Self {
+        Self::new(state)
+    }
+}
+
+/// Generates `stochastic_*(a, .., rng)` ops.
+macro_rules! impl_stochastic {
+    ($ty:ident) => {
+        crate::r#impl::stochastic::impl_stochastic!(@binary $ty, stochastic_add, add_env, "a + b");
+        crate::r#impl::stochastic::impl_stochastic!(@binary $ty, stochastic_sub, sub_env, "a - b");
+        crate::r#impl::stochastic::impl_stochastic!(@binary $ty, stochastic_mul, mul_env, "a * b");
+        crate::r#impl::stochastic::impl_stochastic!(@binary $ty, stochastic_div, div_env, "a / b");
+
+        /// Returns `a.mul_add(b, c)` rounded stochastically by `rng`.
+        ///
+        /// The result is rounded up in magnitude with the probability proportional to
+        /// the distance to the neighbor toward zero, and one `u64` is drawn per call.
+        #[cfg_attr(docsrs, doc(cfg(feature = "stochastic")))]
+        #[must_use = "method returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn stochastic_mul_add<R>(a: $ty, b: $ty, c: $ty, rng: &mut R) -> $ty
+        where
+            R: rand_core::RngCore + ?Sized,
+        {
+            use crate::r#impl::format::BinaryFormat;
+
+            let bits = crate::r#impl::arith::mul_add_env(
+                &<$ty>::FORMAT,
+                a.to_raw(),
+                b.to_raw(),
+                c.to_raw(),
+                &mut crate::r#impl::stochastic::env(rng),
+            );
+            <$ty>::from_raw(bits)
+        }
+
+        /// Returns `a.sqrt()` rounded stochastically by `rng`.
+        ///
+        /// The result is rounded up in magnitude with the probability proportional to
+        /// the distance to the neighbor toward zero, and one `u64` is drawn per call.
+        #[cfg_attr(docsrs, doc(cfg(feature = "stochastic")))]
+        #[must_use = "method returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn stochastic_sqrt<R>(a: $ty, rng: &mut R) -> $ty
+        where
+            R: rand_core::RngCore + ?Sized,
+        {
+            use crate::r#impl::format::BinaryFormat;
+
+            let bits = crate::r#impl::arith::sqrt_env(
+                &<$ty>::FORMAT,
+                a.to_raw(),
+                &mut crate::r#impl::stochastic::env(rng),
+            );
+            <$ty>::from_raw(bits)
+        }
+    };
+    (@binary $ty:ident, $name:ident, $func:ident, $op:literal) => {
+        #[doc = concat!("Returns `", $op, "` rounded stochastically by `rng`.")]
+        ///
+        /// The result is rounded up in magnitude with the probability proportional to
+        /// the distance to the neighbor toward zero, and one `u64` is drawn per call.
+        #[cfg_attr(docsrs, doc(cfg(feature = "stochastic")))]
+        #[must_use = "method returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn $name<R>(a: $ty, b: $ty, rng: &mut R) -> $ty
+        where
+            R: rand_core::RngCore + ?Sized,
+        {
+            use crate::r#impl::format::BinaryFormat;
+
+            let bits = crate::r#impl::arith::$func(
+                &<$ty>::FORMAT,
+                a.to_raw(),
+                b.to_raw(),
+                &mut crate::r#impl::stochastic::env(rng),
+            );
+            <$ty>::from_raw(bits)
+        }
+    };
+}
+
+pub(crate) use impl_stochastic;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{f32, f64};
+
+    const N: usize = 10000;
+
+    /// Returns the ratio of `hi` in `N` results, which must be `lo` or `hi`.
+    fn ratio<T: PartialEq + core::fmt::Debug>(mut f: impl FnMut() -> T, lo: T, hi: T) -> f64 {
+        let mut count = 0;
+        for _ in 0..N {
+            let r = f();
+            assert!(r == lo || r == hi, "{r:?}");
+            if r == hi {
+                count += 1;
+            }
+        }
+        count as f64 / N as f64
+    }
+
+    #[test]
+    fn test_splitmix64() {
+        // the reference implementation
+        let mut rng = SplitMix64::seed_from_u64(1234567);
+        assert_eq!(rng.next_u64(), 6457827717110365317);
+        assert_eq!(rng.next_u64(), 3203168211198807973);
+        assert_eq!(rng.next_u64(), 9817491932198370423);
+
+        assert_eq!(
+            SplitMix64::from_seed(1234567u64.to_le_bytes()),
+            SplitMix64::new(1234567)
+        );
+    }
+
+    #[test]
+    fn test_exact() {
+        let mut rng = SplitMix64::new(0);
+        for _ in 0..100 {
+            assert_eq!(f32::stochastic_add(0.5, 0.25, &mut rng), 0.75);
+            assert_eq!(f32::stochastic_sub(0.5, 0.5, &mut rng).to_bits(), 0);
+            assert_eq!(f32::stochastic_mul(1.5, -3.0, &mut rng), -4.5);
+            assert_eq!(f32::stochastic_div(1.0, 8.0, &mut rng), 0.125);
+            assert_eq!(f32::stochastic_mul_add(2.0, 3.0, 1.0, &mut rng), 7.0);
+            assert_eq!(f32::stochastic_sqrt(9.0, &mut rng), 3.0);
+            assert!(f64::stochastic_sqrt(-1.0, &mut rng).is_nan());
+            assert_eq!(f64::stochastic_div(1.0, 0.0, &mut rng), f64::INFINITY);
+        }
+    }
+
+    #[test]
+    fn test_probability() {
+        let mut rng = SplitMix64::new(42);
+
+        // 1 + ulp/4
+        let r = ratio(
+            || f32::stochastic_add(1.0, f32::EPSILON / 4.0, &mut rng),
+            1.0,
+            1.0 + f32::EPSILON,
+        );
+        assert!((r - 0.25).abs() < 0.02, "{r}");
+
+        // -(1 + 3ulp/4)
+        let r = ratio(
+            || f32::stochastic_sub(-1.0, 0.75 * f32::EPSILON, &mut rng),
+            -1.0,
+            -1.0 - f32::EPSILON,
+        );
+        assert!((r - 0.75).abs() < 0.02, "{r}");
+
+        // 1/3, the mean is close to the exact value
+        let (lo, hi) = f64::round_div_bounds(1.0, 3.0);
+        let r = ratio(|| f64::stochastic_div(1.0, 3.0, &mut rng), lo, hi);
+        let mean = lo + r * (hi - lo);
+        assert!((mean - 1.0 / 3.0).abs() < 0.02 * (hi - lo), "{mean:e}");
+
+        // sqrt(2)
+        let (lo, hi) = f32::round_sqrt_bounds(2.0);
+        let r = ratio(|| f32::stochastic_sqrt(2.0, &mut rng), lo, hi);
+        assert!(r > 0.0 && r < 1.0, "{r}");
+
+        // subnormal, 2^-150 is the half of the minimum positive subnormal
+        let tiny = f32::from_bits(1);
+        let r = ratio(|| f32::stochastic_mul(tiny, 0.5, &mut rng), 0.0, tiny);
+        assert!((r - 0.5).abs() < 0.02, "{r}");
+    }
+
+    #[test]
+    fn test_reproducible() {
+        let run = |seed| {
+            let mut rng = SplitMix64::seed_from_u64(seed);
+            (0..100)
+                .map(|i| f32::stochastic_div(1.0, i as f32 + 3.0, &mut rng))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+}
@@ -0,0 +1,346 @@
+//! Directed rounding by computing in [`f64`], without `<fenv.h>` and C.
+//!
+//! For `f16` and `f32` operands, each op computes the result rounded to nearest in `f64`
+//! together with the exact residual (by TwoSum or fused multiply-add),
+//! then rounds it into the target format with the rounding mode.
+//! The special values and zero operands are handled by [`arith`][crate::r#impl::arith].
+//!
+//! As Rust's float ops, they require the thread's rounding mode to be to nearest.
+
+use crate::r#impl::format::{Format, Unpacked, F64};
+use crate::RoundingMode;
+
+#[cfg(feature = "f16_widening")]
+pub(crate) mod f16;
+#[cfg(feature = "f32_widening")]
+pub(crate) mod f32;
+
+/// Returns bits of the exact value `r + residual` rounded into `fmt` with `mode`,
+/// where `r` is a non-zero normal number rounded to nearest.
+#[inline]
+pub(crate) fn narrow(fmt: &Format, r: f64, residual: f64, mode: &RoundingMode) -> u128 {
+    match F64.unpack(r.to_bits() as u128) {
+        (sign, Unpacked::Finite { sig, exp }) => {
+            // the exact value is (sig + δ) * 2^exp or (sig - 1 + δ) * 2^exp in magnitude
+            let (sig, sticky) = if residual == 0.0 {
+                (sig, false)
+            } else if (residual > 0.0) != sign {
+                (sig, true)
+            } else {
+                (sig - 1, true)
+            };
+            fmt.round_pack(sign, sig, exp, sticky, mode).0
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Returns `(r, e)` where `r` is `a + b` rounded to nearest and `r + e = a + b` exactly.
+#[inline(always)]
+pub(crate) fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let r = a + b;
+    let bb = r - a;
+    let e = (a - (r - bb)) + (b - bb);
+    (r, e)
+}
+
+/// Returns the zero of exact `x - x`.
+#[inline(always)]
+pub(crate) fn exact_zero(mode: &RoundingMode) -> f64 {
+    match mode {
+        RoundingMode::TowardNegInf => -0.0,
+        _ => 0.0,
+    }
+}
+
+macro_rules! impl_widening {
+    ($ty:ident, $fmt:ident) => {
+        use crate::r#impl::arith;
+        use crate::r#impl::format::$fmt;
+        use crate::r#impl::widening::{exact_zero, narrow, two_sum};
+        use crate::RoundingMode;
+
+        #[inline(always)]
+        fn is_regular(x: $ty) -> bool {
+            x.is_finite() && x != 0.0
+        }
+
+        #[inline(always)]
+        fn add_impl(a: f64, b: f64, mode: &RoundingMode) -> $ty {
+            let (r, e) = two_sum(a, b);
+            if r == 0.0 {
+                return exact_zero(mode) as $ty;
+            }
+            <$ty>::from_bits(narrow(&$fmt, r, e, mode) as _)
+        }
+
+        /// Returns `a + b` as specific rounding mode.
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_add(a: $ty, b: $ty, mode: &RoundingMode) -> $ty {
+            if is_regular(a) && is_regular(b) {
+                add_impl(a as f64, b as f64, mode)
+            } else {
+                let r = arith::add(&$fmt, a.to_bits() as u128, b.to_bits() as u128, mode);
+                <$ty>::from_bits(r as _)
+            }
+        }
+
+        /// Returns `a - b` as specific rounding mode.
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_sub(a: $ty, b: $ty, mode: &RoundingMode) -> $ty {
+            if is_regular(a) && is_regular(b) {
+                add_impl(a as f64, -(b as f64), mode)
+            } else {
+                let r = arith::sub(&$fmt, a.to_bits() as u128, b.to_bits() as u128, mode);
+                <$ty>::from_bits(r as _)
+            }
+        }
+
+        /// Returns `a * b` as specific rounding mode.
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_mul(a: $ty, b: $ty, mode: &RoundingMode) -> $ty {
+            if is_regular(a) && is_regular(b) {
+                // exact
+                let r = a as f64 * b as f64;
+                <$ty>::from_bits(narrow(&$fmt, r, 0.0, mode) as _)
+            } else {
+                let r = arith::mul(&$fmt, a.to_bits() as u128, b.to_bits() as u128, mode);
+                <$ty>::from_bits(r as _)
+            }
+        }
+
+        /// Returns `a / b` as specific rounding mode.
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_div(a: $ty, b: $ty, mode: &RoundingMode) -> $ty {
+            if is_regular(a) && is_regular(b) {
+                let (a, b) = (a as f64, b as f64);
+                let r = a / b;
+                // a - r * b is exact, and a / b - r has the same sign as (a - r * b) / b
+                let rem = (-r).mul_add(b, a);
+                let residual = if b < 0.0 { -rem } else { rem };
+                <$ty>::from_bits(narrow(&$fmt, r, residual, mode) as _)
+            } else {
+                let r = arith::div(&$fmt, a.to_bits() as u128, b.to_bits() as u128, mode);
+                <$ty>::from_bits(r as _)
+            }
+        }
+
+        /// Returns `a * b + c` with single rounding (fused multiply-add) as specific rounding mode.
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_mul_add(a: $ty, b: $ty, c: $ty, mode: &RoundingMode) -> $ty {
+            if is_regular(a) && is_regular(b) && is_regular(c) {
+                // a * b is exact
+                add_impl(a as f64 * b as f64, c as f64, mode)
+            } else {
+                let r = arith::mul_add(
+                    &$fmt,
+                    a.to_bits() as u128,
+                    b.to_bits() as u128,
+                    c.to_bits() as u128,
+                    mode,
+                );
+                <$ty>::from_bits(r as _)
+            }
+        }
+
+        /// Returns `a.sqrt()` as specific rounding mode.
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_sqrt(a: $ty, mode: &RoundingMode) -> $ty {
+            if is_regular(a) && a > 0.0 {
+                let a = a as f64;
+                let r = a.sqrt();
+                let residual = (-r).mul_add(r, a);
+                <$ty>::from_bits(narrow(&$fmt, r, residual, mode) as _)
+            } else {
+                let r = arith::sqrt(&$fmt, a.to_bits() as u128, mode);
+                <$ty>::from_bits(r as _)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_widening;
+
+#[cfg(test)]
+mod test {
+    use crate::r#impl::arith;
+    use crate::RoundingMode as Mode;
+
+    const MODES: [Mode; 4] = [
+        Mode::NearestTiesEven,
+        Mode::TowardPosInf,
+        Mode::TowardNegInf,
+        Mode::TowardZero,
+    ];
+
+    fn xorshift(x: &mut u64) -> u64 {
+        *x ^= *x << 13;
+        *x ^= *x >> 7;
+        *x ^= *x << 17;
+        *x
+    }
+
+    #[cfg(feature = "f32_widening")]
+    fn next_f32(x: &mut u64) -> f32 {
+        let r = xorshift(x);
+        match r % 4 {
+            0 => f32::from_bits(r as u32),
+            1 => (r % 1024) as f32 / 64.0,
+            _ => f32::from_bits(r as u32 & 0x807F_FFFF | 0x3F80_0000),
+        }
+    }
+
+    #[cfg(feature = "f16_widening")]
+    fn next_f16(x: &mut u64) -> f16 {
+        let r = xorshift(x);
+        match r % 4 {
+            0 | 1 => f16::from_bits(r as u16),
+            _ => f16::from_bits(r as u16 & 0x83FF | 0x3C00),
+        }
+    }
+
+    macro_rules! check {
+        ($ty:ident, $fmt:ident, $next:expr) => {{
+            use super::$ty::*;
+            use crate::r#impl::format::$fmt;
+
+            let f = |x: $ty| x.to_bits() as u128;
+            let mut next = $next;
+            for _ in 0..20000 {
+                let (a, b, c) = (next(), next(), next());
+                let (ba, bb, bc) = (f(a), f(b), f(c));
+                for mode in &MODES {
+                    assert_eq!(f(round_add(a, b, mode)), arith::add(&$fmt, ba, bb, mode));
+                    assert_eq!(f(round_sub(a, b, mode)), arith::sub(&$fmt, ba, bb, mode));
+                    assert_eq!(f(round_mul(a, b, mode)), arith::mul(&$fmt, ba, bb, mode));
+                    assert_eq!(f(round_div(a, b, mode)), arith::div(&$fmt, ba, bb, mode));
+                    assert_eq!(f(round_sqrt(a, mode)), arith::sqrt(&$fmt, ba, mode));
+                    assert_eq!(
+                        f(round_mul_add(a, b, c, mode)),
+                        arith::mul_add(&$fmt, ba, bb, bc, mode)
+                    );
+                }
+            }
+        }};
+    }
+
+    /// Checks the ops against the SoftFloat ops of the format, independently of `arith`.
+    ///
+    /// The NaN results of SoftFloat follow the specialization chosen by the build host,
+    /// thus they are replaced by the ones of [`NanSemantics::NATIVE`] as
+    /// `set_softfloat_nan_semantics` does, and then compared bit by bit.
+    #[cfg(feature = "f64_softfloat")]
+    macro_rules! check_softfloat {
+        (
+            $ty:ident, $fmt:ident, $t:ident,
+            [$add:ident, $sub:ident, $mul:ident, $div:ident, $sqrt:ident, $fma:ident],
+            $next:expr
+        ) => {{
+            use super::$ty::*;
+            use crate::r#impl::format::$fmt;
+            use crate::{NanSemantics, Operation};
+            use srmfpa_berkeley_softfloat_3::*;
+
+            let f = |x: $ty| x.to_bits() as u128;
+            let v = |x: $ty| $t { v: x.to_bits() };
+            let soft = |op, xs: &[u128], r: $t| match <$ty>::from_bits(r.v) {
+                r if r.is_nan() => $fmt.nan_result(op, xs, NanSemantics::NATIVE),
+                r => f(r),
+            };
+            let mut next = $next;
+            for _ in 0..20000 {
+                let (a, b, c) = (next(), next(), next());
+                let (ba, bb, bc) = (f(a), f(b), f(c));
+                for mode in &MODES {
+                    let m = mode.as_berkeley_softfloat();
+                    let (add, sub, mul, div, sqrt, fma) = unsafe {
+                        (
+                            $add(m, v(a), v(b)),
+                            $sub(m, v(a), v(b)),
+                            $mul(m, v(a), v(b)),
+                            $div(m, v(a), v(b)),
+                            $sqrt(m, v(a)),
+                            $fma(m, v(a), v(b), v(c)),
+                        )
+                    };
+                    assert_eq!(
+                        f(round_add(a, b, mode)),
+                        soft(Operation::Add, &[ba, bb], add),
+                        "{a:?} + {b:?} {mode:?}"
+                    );
+                    assert_eq!(
+                        f(round_sub(a, b, mode)),
+                        soft(Operation::Sub, &[ba, bb], sub),
+                        "{a:?} - {b:?} {mode:?}"
+                    );
+                    assert_eq!(
+                        f(round_mul(a, b, mode)),
+                        soft(Operation::Mul, &[ba, bb], mul),
+                        "{a:?} * {b:?} {mode:?}"
+                    );
+                    assert_eq!(
+                        f(round_div(a, b, mode)),
+                        soft(Operation::Div, &[ba, bb], div),
+                        "{a:?} / {b:?} {mode:?}"
+                    );
+                    assert_eq!(
+                        f(round_sqrt(a, mode)),
+                        soft(Operation::Sqrt, &[ba], sqrt),
+                        "sqrt {a:?} {mode:?}"
+                    );
+                    assert_eq!(
+                        f(round_mul_add(a, b, c, mode)),
+                        soft(Operation::MulAdd, &[ba, bb, bc], fma),
+                        "{a:?} * {b:?} + {c:?} {mode:?}"
+                    );
+                }
+            }
+        }};
+    }
+
+    #[test]
+    #[cfg(feature = "f32_widening")]
+    fn test_f32() {
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        check!(f32, F32, || next_f32(&mut x));
+    }
+
+    #[test]
+    #[cfg(all(feature = "f32_widening", feature = "f64_softfloat"))]
+    fn test_f32_softfloat() {
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        check_softfloat!(
+            f32,
+            F32,
+            float32_t,
+            [c_add_f32, c_sub_f32, c_mul_f32, c_div_f32, c_sqrt_f32, c_fma_f32],
+            || next_f32(&mut x)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "f16_widening")]
+    fn test_f16() {
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        check!(f16, F16, || next_f16(&mut x));
+    }
+
+    #[test]
+    #[cfg(all(feature = "f16_widening", feature = "f64_softfloat"))]
+    fn test_f16_softfloat() {
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        check_softfloat!(
+            f16,
+            F16,
+            float16_t,
+            [c_add_f16, c_sub_f16, c_mul_f16, c_div_f16, c_sqrt_f16, c_fma_f16],
+            || next_f16(&mut x)
+        );
+    }
+}
@@ -0,0 +1 @@
+crate::r#impl::widening::impl_widening!(f16, F16);
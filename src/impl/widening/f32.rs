@@ -0,0 +1,4 @@
+crate::r#impl::widening::impl_widening!(f32, F32);
+
+#[cfg(feature = "rayon")]
+crate::r#impl::par::impl_round_slice!(f32);
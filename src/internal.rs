@@ -225,11 +225,451 @@ macro_rules! impl_non_round_ternary {
     };
 }
 
+macro_rules! impl_const_arith {
+    ($ty:ty, $fmt:ident) => {
+        impl_const_arith!(
+            @binary $ty, $fmt, "a + b", const_round_add => add;
+            @binary $ty, $fmt, "a - b", const_round_sub => sub;
+            @binary $ty, $fmt, "a * b", const_round_mul => mul;
+            @binary $ty, $fmt, "a / b", const_round_div => div;
+        );
+
+        #[doc = concat!(
+"Returns `a.mul_add(b, c)` as specified rounding mode, evaluated by integer ops.
+
+This is `const fn` and the result is identical with [`round_mul_add`]."
+        )]
+        #[must_use = "method returns a new number and does not mutate the original value"]
+        #[inline]
+        pub const fn const_round_mul_add(a: $ty, b: $ty, c: $ty, mode: &RoundingMode) -> $ty {
+            use crate::r#impl::format::$fmt;
+
+            let bits = crate::r#impl::arith::mul_add(
+                &$fmt,
+                a.to_bits() as u128,
+                b.to_bits() as u128,
+                c.to_bits() as u128,
+                mode,
+            );
+            <$ty>::from_bits(bits as _)
+        }
+
+        #[doc = concat!(
+"Returns `a.sqrt()` as specified rounding mode, evaluated by integer ops.
+
+This is `const fn` and the result is identical with [`round_sqrt`]."
+        )]
+        #[must_use = "method returns a new number and does not mutate the original value"]
+        #[inline]
+        pub const fn const_round_sqrt(a: $ty, mode: &RoundingMode) -> $ty {
+            use crate::r#impl::format::$fmt;
+
+            let bits = crate::r#impl::arith::sqrt(&$fmt, a.to_bits() as u128, mode);
+            <$ty>::from_bits(bits as _)
+        }
+    };
+    ($(@binary $ty:ty, $fmt:ident, $op:literal, $name:ident => $func:ident;)*) => {
+        $(
+            #[doc = concat!(
+"Returns `", $op, "` as specified rounding mode, evaluated by integer ops.
+
+This is `const fn` and the result is identical with [`round_", stringify!($func), "`]."
+            )]
+            #[must_use = "method returns a new number and does not mutate the original value"]
+            #[inline]
+            pub const fn $name(a: $ty, b: $ty, mode: &RoundingMode) -> $ty {
+                use crate::r#impl::format::$fmt;
+
+                let bits = crate::r#impl::arith::$func(
+                    &$fmt,
+                    a.to_bits() as u128,
+                    b.to_bits() as u128,
+                    mode,
+                );
+                <$ty>::from_bits(bits as _)
+            }
+        )*
+    };
+}
+
+macro_rules! impl_round_bounds {
+    (@doc $op:literal, $floor:ident, $ciel:ident) => {
+        concat!(
+            "Returns the lower and upper bounds of `",
+            $op,
+            "`, that is, `(",
+            stringify!($floor),
+            ", ",
+            stringify!($ciel),
+            ")`.
+
+The lower bound never exceeds the upper, and they are identical if the result is exact
+(but the bounds of exact zero are `(-0.0, 0.0)`).
+
+# Safety
+
+Panics when fail to set/restore rounding mode."
+        )
+    };
+    ($ty:ident) => {
+        #[doc = impl_round_bounds!(@doc "a + b", floor_add, ciel_add)]
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_add_bounds(a: $ty, b: $ty) -> ($ty, $ty) {
+            (floor_add(a, b), ciel_add(a, b))
+        }
+
+        #[doc = impl_round_bounds!(@doc "a - b", floor_sub, ciel_sub)]
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_sub_bounds(a: $ty, b: $ty) -> ($ty, $ty) {
+            (floor_sub(a, b), ciel_sub(a, b))
+        }
+
+        #[doc = impl_round_bounds!(@doc "a * b", floor_mul, ciel_mul)]
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_mul_bounds(a: $ty, b: $ty) -> ($ty, $ty) {
+            (floor_mul(a, b), ciel_mul(a, b))
+        }
+
+        #[doc = impl_round_bounds!(@doc "a / b", floor_div, ciel_div)]
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_div_bounds(a: $ty, b: $ty) -> ($ty, $ty) {
+            (floor_div(a, b), ciel_div(a, b))
+        }
+
+        #[doc = impl_round_bounds!(@doc "a.mul_add(b, c)", floor_mul_add, ciel_mul_add)]
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_mul_add_bounds(a: $ty, b: $ty, c: $ty) -> ($ty, $ty) {
+            (floor_mul_add(a, b, c), ciel_mul_add(a, b, c))
+        }
+
+        #[doc = impl_round_bounds!(@doc "a.sqrt()", floor_sqrt, ciel_sqrt)]
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_sqrt_bounds(a: $ty) -> ($ty, $ty) {
+            (floor_sqrt(a), ciel_sqrt(a))
+        }
+    };
+    // computes the nearest result and its residual by error-free transformations,
+    // and falls back to two directed ops around overflow and underflow,
+    // or when the thread's rounding mode is not to nearest, under which they are not valid.
+    // The operands and residuals are kept normal, since they are flushed under flush-to-zero
+    // and denormals-are-zero modes
+    ($ty:ident, eft) => {
+        mod bounds {
+            crate::r#impl::eft::impl_eft!($ty);
+
+            /// `2^(emin + 2 * precision)`, the residual of a product, quotient or square root
+            /// of an operand not less than this is a multiple of `2^emin`, thus it is not subnormal.
+            const TINY_PRODUCT: $ty = TINY / <$ty>::EPSILON * 2.0;
+
+            /// Returns the bounds of the exact value `r + residual` from the nearest `r`.
+            #[inline(always)]
+            pub(super) fn bounds(r: $ty, residual: $ty) -> ($ty, $ty) {
+                if residual > 0.0 {
+                    (r, next_up(r))
+                } else if residual < 0.0 {
+                    (next_down(r), r)
+                } else {
+                    (r, r)
+                }
+            }
+
+            /// Returns the bounds of `a + b`, or `None` if it is not safe.
+            #[inline(always)]
+            pub(super) fn add(a: $ty, b: $ty) -> Option<($ty, $ty)> {
+                if !crate::env::is_nearest() {
+                    return None;
+                }
+                sum(a, b)
+            }
+
+            /// [`add`] under the nearest thread's mode.
+            #[inline(always)]
+            fn sum(a: $ty, b: $ty) -> Option<($ty, $ty)> {
+                // every partial result is a multiple of `2^(emin + 1)`
+                if !(is_safe(a) && is_safe(b) && TINY <= a.abs() && TINY <= b.abs()) {
+                    return None;
+                }
+                match two_sum(a, b) {
+                    (r, _) if r == 0.0 => Some((-0.0, 0.0)),
+                    (r, e) => Some(bounds(r, e)),
+                }
+            }
+
+            #[inline(always)]
+            pub(super) fn mul(a: $ty, b: $ty) -> Option<($ty, $ty)> {
+                if !crate::env::is_nearest() {
+                    return None;
+                }
+                let r = a * b;
+                (TINY_PRODUCT <= r.abs() && r.abs() <= HUGE).then(|| bounds(r, a.mul_add(b, -r)))
+            }
+
+            #[inline(always)]
+            pub(super) fn div(a: $ty, b: $ty) -> Option<($ty, $ty)> {
+                if !crate::env::is_nearest() {
+                    return None;
+                }
+                let r = a / b;
+                (TINY_PRODUCT <= a.abs() && TINY <= r.abs() && r.abs() <= HUGE).then(|| {
+                    // a - r * b is exact, and a / b - r has the same sign as (a - r * b) / b
+                    let rem = (-r).mul_add(b, a);
+                    bounds(r, if b < 0.0 { -rem } else { rem })
+                })
+            }
+
+            #[inline(always)]
+            pub(super) fn mul_add(a: $ty, b: $ty, c: $ty) -> Option<($ty, $ty)> {
+                if !crate::env::is_nearest() {
+                    return None;
+                }
+                let p = a * b;
+                if TINY_PRODUCT <= p.abs() && p.abs() <= HUGE && a.mul_add(b, -p) == 0.0 {
+                    // a * b is exact
+                    sum(p, c)
+                } else {
+                    None
+                }
+            }
+
+            #[inline(always)]
+            pub(super) fn sqrt(a: $ty) -> Option<($ty, $ty)> {
+                if !crate::env::is_nearest() {
+                    return None;
+                }
+                (TINY_PRODUCT..=<$ty>::MAX).contains(&a).then(|| {
+                    let r = a.sqrt();
+                    bounds(r, (-r).mul_add(r, a))
+                })
+            }
+        }
+
+        #[doc = impl_round_bounds!(@doc "a + b", floor_add, ciel_add)]
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_add_bounds(a: $ty, b: $ty) -> ($ty, $ty) {
+            bounds::add(a, b).unwrap_or_else(|| (floor_add(a, b), ciel_add(a, b)))
+        }
+
+        #[doc = impl_round_bounds!(@doc "a - b", floor_sub, ciel_sub)]
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_sub_bounds(a: $ty, b: $ty) -> ($ty, $ty) {
+            bounds::add(a, -b).unwrap_or_else(|| (floor_sub(a, b), ciel_sub(a, b)))
+        }
+
+        #[doc = impl_round_bounds!(@doc "a * b", floor_mul, ciel_mul)]
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_mul_bounds(a: $ty, b: $ty) -> ($ty, $ty) {
+            bounds::mul(a, b).unwrap_or_else(|| (floor_mul(a, b), ciel_mul(a, b)))
+        }
+
+        #[doc = impl_round_bounds!(@doc "a / b", floor_div, ciel_div)]
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_div_bounds(a: $ty, b: $ty) -> ($ty, $ty) {
+            bounds::div(a, b).unwrap_or_else(|| (floor_div(a, b), ciel_div(a, b)))
+        }
+
+        #[doc = impl_round_bounds!(@doc "a.mul_add(b, c)", floor_mul_add, ciel_mul_add)]
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_mul_add_bounds(a: $ty, b: $ty, c: $ty) -> ($ty, $ty) {
+            bounds::mul_add(a, b, c)
+                .unwrap_or_else(|| (floor_mul_add(a, b, c), ciel_mul_add(a, b, c)))
+        }
+
+        #[doc = impl_round_bounds!(@doc "a.sqrt()", floor_sqrt, ciel_sqrt)]
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_sqrt_bounds(a: $ty) -> ($ty, $ty) {
+            bounds::sqrt(a).unwrap_or_else(|| (floor_sqrt(a), ciel_sqrt(a)))
+        }
+    };
+}
+
+macro_rules! impl_round_to {
+    (@doc $op:literal) => {
+        concat!(
+            "Returns `",
+            $op,
+            "` rounded once into the format `U` with specified rounding mode,
+IEEE 754 formatOf operation.
+
+It does not round twice even if `U` is narrower, e.g.
+`round_add_to::<f32>` of `f64` operands differs from `round_add(a, b, mode) as f32`.
+
+# Safety
+
+Panics when fail to set/restore rounding mode."
+        )
+    };
+    ($ty:ident) => {
+        #[doc = impl_round_to!(@doc "a + b")]
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_add_to<U: crate::FormatOf<$ty>>(a: $ty, b: $ty, mode: &RoundingMode) -> U {
+            U::round_add_from(a, b, mode)
+        }
+
+        #[doc = impl_round_to!(@doc "a - b")]
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_sub_to<U: crate::FormatOf<$ty>>(a: $ty, b: $ty, mode: &RoundingMode) -> U {
+            U::round_sub_from(a, b, mode)
+        }
+
+        #[doc = impl_round_to!(@doc "a * b")]
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_mul_to<U: crate::FormatOf<$ty>>(a: $ty, b: $ty, mode: &RoundingMode) -> U {
+            U::round_mul_from(a, b, mode)
+        }
+
+        #[doc = impl_round_to!(@doc "a / b")]
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_div_to<U: crate::FormatOf<$ty>>(a: $ty, b: $ty, mode: &RoundingMode) -> U {
+            U::round_div_from(a, b, mode)
+        }
+
+        #[doc = impl_round_to!(@doc "a.mul_add(b, c)")]
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_mul_add_to<U: crate::FormatOf<$ty>>(
+            a: $ty,
+            b: $ty,
+            c: $ty,
+            mode: &RoundingMode,
+        ) -> U {
+            U::round_mul_add_from(a, b, c, mode)
+        }
+
+        #[doc = impl_round_to!(@doc "a.sqrt()")]
+        #[must_use = "function returns a new number and does not mutate the original value"]
+        #[inline]
+        pub fn round_sqrt_to<U: crate::FormatOf<$ty>>(a: $ty, mode: &RoundingMode) -> U {
+            U::round_sqrt_from(a, mode)
+        }
+    };
+}
+
+macro_rules! impl_hex {
+    ($ty:ty, $fmt:ident) => {
+        #[doc = concat!(
+"Returns the hexadecimal string of `x`, e.g. `0x1.999999999999ap-4`, as C's `printf(\"%a\", x)` does.
+
+Subnormal numbers are printed as `0x0.<fraction>p<minimum exponent>`,
+infinities as `inf` (`-inf`) and NaNs as `nan`."
+        )]
+        #[must_use]
+        #[inline]
+        pub fn to_hex_string(x: $ty) -> String {
+            crate::r#impl::print::format_hex(x)
+        }
+
+        #[doc = concat!(
+"Parses a hexadecimal string, e.g. `0x1.999999999999ap-4`, into [`", stringify!($ty), "`].
+
+The literal is rounded with specified rounding mode when it has more bits than [`", stringify!($ty), "`].
+The binary exponent `p<exp>` is optional, and it accepts `inf`, `infinity` and `nan` as [`round_parse`] does.
+
+# Errors
+
+Returns [`ParseError`][crate::ParseError] when `s` is not a valid hexadecimal float literal."
+        )]
+        #[inline]
+        pub const fn from_hex_str(s: &str, mode: &RoundingMode) -> Result<$ty, crate::ParseError> {
+            use crate::r#impl::format::$fmt;
+            use crate::r#impl::parse::parse_hex;
+
+            match parse_hex(s.as_bytes(), &$fmt, mode) {
+                Ok((bits, _)) => Ok(<$ty>::from_bits(bits as _)),
+                Err(e) => Err(e),
+            }
+        }
+    };
+}
+
+macro_rules! impl_round_format {
+    ($ty:ty) => {
+        #[doc = concat!(
+"Returns the decimal string of `x` rounded to `digits` fractional digits with specified rounding mode.
+
+Unlike `format!(\"{:.6}\", x)` rounds to nearest always,
+the result is a lower (upper) bound of `x` with [`RoundingMode::TowardNegInf`] ([`RoundingMode::TowardPosInf`]).
+See also [`Directed`][crate::Directed]."
+        )]
+        #[must_use]
+        #[inline]
+        pub fn round_format(x: $ty, digits: usize, mode: &RoundingMode) -> String {
+            crate::Directed(x, *mode).precision(digits).to_string()
+        }
+    };
+}
+
+macro_rules! impl_round_parse {
+    ($ty:ty, $fmt:ident) => {
+        #[doc = concat!(
+"Parses a decimal string into [`", stringify!($ty), "`] with specified rounding mode.
+
+It accepts the same format as [`str::parse`] does, e.g. `\"1.5\"`, `\"-0.1e-3\"`, `\"inf\"` and `\"NaN\"`.
+
+# Errors
+
+Returns [`ParseError`][crate::ParseError] when `s` is not a valid float literal."
+        )]
+        #[inline]
+        pub const fn round_parse(s: &str, mode: &RoundingMode) -> Result<$ty, crate::ParseError> {
+            match round_parse_inexact(s, mode) {
+                Ok((x, _)) => Ok(x),
+                Err(e) => Err(e),
+            }
+        }
+
+        #[doc = concat!(
+"Parses a decimal string into [`", stringify!($ty), "`] with specified rounding mode,
+and returns `true` together if the result is inexact.
+
+See [`round_parse`] for the accepted format.
+
+# Errors
+
+Returns [`ParseError`][crate::ParseError] when `s` is not a valid float literal."
+        )]
+        #[inline]
+        pub const fn round_parse_inexact(
+            s: &str,
+            mode: &RoundingMode,
+        ) -> Result<($ty, bool), crate::ParseError> {
+            use crate::r#impl::format::$fmt;
+            use crate::r#impl::parse::{limbs, parse_decimal};
+
+            match parse_decimal::<{ limbs(&$fmt) }>(s.as_bytes(), &$fmt, mode) {
+                Ok((bits, inexact)) => Ok((<$ty>::from_bits(bits as _), inexact)),
+                Err(e) => Err(e),
+            }
+        }
+    };
+}
+
+pub(crate) use impl_const_arith;
 pub(crate) use impl_func_binary;
 pub(crate) use impl_func_unary;
+pub(crate) use impl_hex;
 pub(crate) use impl_non_round_binary;
 pub(crate) use impl_non_round_func_binary_all;
 pub(crate) use impl_non_round_ternary;
 pub(crate) use impl_round_binary;
+pub(crate) use impl_round_bounds;
+pub(crate) use impl_round_format;
 pub(crate) use impl_round_func_binary_all;
+pub(crate) use impl_round_parse;
 pub(crate) use impl_round_ternary;
+pub(crate) use impl_round_to;
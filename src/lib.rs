@@ -1,4 +1,7 @@
-#![cfg_attr(any(feature = "f16", feature = "f16_softfloat",), feature(f16))]
+#![cfg_attr(
+    any(feature = "f16", feature = "f16_softfloat", feature = "f16_widening"),
+    feature(f16)
+)]
 #![cfg_attr(any(feature = "f128", feature = "f128_softfloat",), feature(f128))]
 #![cfg_attr(test, feature(float_next_up_down))]
 #![cfg_attr(docsrs, feature(doc_cfg))]
@@ -38,6 +41,25 @@
 //! - `f64_softfloat`: support softfloat `f64` by [Berkeley SoftFloat 3][softfloat].
 //! - `f16_softfloat`: support softfloat `f16` by [Berkeley SoftFloat 3][softfloat].
 //! - `f128_softfloat`: support softfloat `f128` by [Berkeley SoftFloat 3][softfloat].
+//! - `pure_rust`: use pure Rust implementation for `f32` and `f64`
+//!   (enable `f32_pure_rust` and `f64_pure_rust`).
+//! - `f32_pure_rust`: support pure Rust `f32` by error-free transformations.
+//! - `f64_pure_rust`: support pure Rust `f64` by error-free transformations.
+//! - `f16_widening`: support pure Rust `f16` by computing in `f64`.
+//! - `f32_widening`: support pure Rust `f32` by computing in `f64`.
+//! - `asm`: use inline assembly for `f32` and `f64` on x86_64
+//!   (enable `f32_asm` and `f64_asm`).
+//! - `f32_asm`: support `f32` by SSE instructions and MXCSR on x86_64, otherwise the default.
+//! - `f64_asm`: support `f64` by SSE instructions and MXCSR on x86_64, otherwise the default.
+//! - `simd`: support packed vectors of `f32` and `f64` by SSE/AVX instructions on x86_64,
+//!   otherwise by a scalar loop.
+//! - `rayon`: support parallel ops on slices of `f32` and `f64` by [`rayon`][rayon]
+//!   (e.g. `f64::par_round_add_slice`).
+//! - `macros`: support the [`rounded!`] macro and the [`#[with_rounding]`][with_rounding] attribute,
+//!   which rewrite arithmetic into directed ops.
+//! - `serde`: support serialization of [`RoundingMode`] by [`serde`][serde] as its name.
+//! - `stochastic`: support stochastic rounding ops of `f16`, `f32` and `f64` with an RNG of
+//!   [`rand_core`][rand_core] (e.g. `f32::stochastic_add`).
 //!
 //! # Notes on Correctness and Configuration
 //!
@@ -48,6 +70,16 @@
 //! [`srmfpa`][mod@self] supports softfloat ops by [Berkeley SoftFloat 3][softfloat] with `fN_softfloat` features.
 //! It provides correct rounding ops for evey IEEE 754 rounding modes.
 //!
+//! [`srmfpa`][mod@self] also supports pure Rust ops with `fN_pure_rust` features.
+//! They compute the result rounded to nearest and correct it by the sign of the exact residual
+//! (error-free transformations), thus they do not depend on `<fenv.h>`.
+//! On x86_64, when `f32` and `f64` use the `fN_pure_rust`, `fN_widening` or `fN_asm` backends
+//! and neither `f16` nor `f128` is enabled, [`srmfpa`][mod@self] compiles no C code
+//! and accesses the thread's rounding mode by MXCSR and x87 FPU control word instead of `<fenv.h>`.
+//! On the other targets, it still calls `<fenv.h>` for [`set_rounding_mode`] etc.
+//! As Rust's float ops, they (and the `fN_widening` ones) require the thread's rounding mode
+//! to be to nearest (the default), see [`set_rounding_mode`].
+//!
 //! [`srmfpa`][mod@self] uses the default C compiler options of `cc`,
 //! and does not (explicitly) specify other options.
 //! It is recommended to pass corresponding options (`-std=c11`, `-lm`, `-frounding-math`, `-mfma` etc.)
@@ -56,34 +88,119 @@
 //!
 //! [softfloat]: https://github.com/ucb-bar/berkeley-softfloat-3
 //! [cc_doc]: https://docs.rs/cc/latest/cc/index.html
+//! [rayon]: https://docs.rs/rayon/latest/rayon/
+//! [serde]: https://docs.rs/serde/latest/serde/
+//! [rand_core]: https://docs.rs/rand_core/latest/rand_core/
 #[cfg(all(feature = "f128", feature = "f128_softfloat"))]
 compile_error!("not supported features combination, `f128` and `f128_softfloat`");
 #[cfg(all(feature = "f16", feature = "f16_softfloat"))]
 compile_error!("not supported features combination, `f16` and `f16_softfloat`");
+#[cfg(all(feature = "f16", feature = "f16_widening"))]
+compile_error!("not supported features combination, `f16` and `f16_widening`");
+#[cfg(all(feature = "f16_softfloat", feature = "f16_widening"))]
+compile_error!("not supported features combination, `f16_softfloat` and `f16_widening`");
+#[cfg(all(feature = "f32_softfloat", feature = "f32_widening"))]
+compile_error!("not supported features combination, `f32_softfloat` and `f32_widening`");
+#[cfg(all(feature = "f32_pure_rust", feature = "f32_widening"))]
+compile_error!("not supported features combination, `f32_pure_rust` and `f32_widening`");
+#[cfg(all(
+    feature = "f32_asm",
+    any(
+        feature = "f32_softfloat",
+        feature = "f32_pure_rust",
+        feature = "f32_widening"
+    )
+))]
+compile_error!("not supported features combination, `f32_asm` and other `f32` backend");
+#[cfg(all(
+    feature = "f64_asm",
+    any(feature = "f64_softfloat", feature = "f64_pure_rust")
+))]
+compile_error!("not supported features combination, `f64_asm` and other `f64` backend");
+#[cfg(all(feature = "f32_softfloat", feature = "f32_pure_rust"))]
+compile_error!("not supported features combination, `f32_softfloat` and `f32_pure_rust`");
+#[cfg(all(feature = "f64_softfloat", feature = "f64_pure_rust"))]
+compile_error!("not supported features combination, `f64_softfloat` and `f64_pure_rust`");
 
 use core::ffi::c_int;
 
+// for `::srmfpa` paths of the macros in tests
+#[cfg(all(test, feature = "macros"))]
+extern crate self as srmfpa;
+
+pub use directed::{Directed, DirectedPrecision};
+pub use env::{
+    current_denormal_mode, current_rounding_mode, set_denormal_mode, set_rounding_mode,
+    with_denormal_mode, with_rounding_mode,
+};
+#[cfg(any(
+    feature = "f16_softfloat",
+    feature = "f32_softfloat",
+    feature = "f64_softfloat",
+    feature = "f128_softfloat"
+))]
+pub use env::{
+    current_softfloat_nan_semantics, current_softfloat_rounding_mode, current_softfloat_tininess,
+    set_softfloat_nan_semantics, set_softfloat_tininess, with_softfloat_tininess,
+};
+pub use error::{
+    DenormalModeError, ParseError, ParseErrorKind, ParseRoundingModeError, RoundingModeError,
+};
+pub use exception::{
+    catch_exception, exception_handler, set_exception_handler, Action, Exception, ExceptionHandler,
+    ExceptionKind, Operation,
+};
+pub use float::RoundingFloat;
+pub use format_of::FormatOf;
+pub use fpenv::{DenormalMode, EnvFloat, ExceptionFlags, FpEnv, NanSemantics, Tininess};
+#[cfg(feature = "macros")]
+#[doc(hidden)]
+pub use macros::__private;
+#[cfg(feature = "macros")]
+#[cfg_attr(docsrs, doc(cfg(feature = "macros")))]
+pub use macros::{rounded, with_rounding};
 #[cfg(any(feature = "f128", feature = "f128_softfloat"))]
 #[cfg_attr(docsrs, doc(cfg(feature = "f128_softfloat")))]
 pub use r#impl::f128;
-#[cfg(any(feature = "f16", feature = "f16_softfloat"))]
+#[cfg(any(feature = "f16", feature = "f16_softfloat", feature = "f16_widening"))]
 #[cfg_attr(docsrs, doc(cfg(feature = "f16_softfloat")))]
 pub use r#impl::f16;
 pub use r#impl::f32;
 pub use r#impl::f64;
-
+#[cfg(feature = "simd")]
+#[cfg_attr(docsrs, doc(cfg(feature = "simd")))]
+pub use r#impl::simd;
+#[cfg(feature = "stochastic")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stochastic")))]
+pub use r#impl::stochastic::SplitMix64;
+pub use rounded::{Down, Mode, Nearest, Rounded, Up, Zero};
+
+mod directed;
+mod env;
+mod error;
+mod exception;
+mod float;
+mod format_of;
+mod fpenv;
 mod r#impl;
 mod internal;
+mod literal;
+#[cfg(feature = "macros")]
+mod macros;
+mod rounded;
+mod rounding_mode;
 
 /// [fpa_specr][mod@self]’s prelude.
 pub mod prelude {
     // provides RoundingMode and traits only.
+    pub use crate::RoundingFloat;
     pub use crate::RoundingMode;
     pub use crate::{CielArithmetic, FloorArithmetic, RoundTiesEvenArithmetic, TruncArithmetic};
     pub use crate::{CielMath, FloorMath, RoundTiesEvenMath, TruncMath};
     pub use crate::{RoundingArithmetic, RoundingMath};
 }
 
+#[cfg(srmfpa_fenv)]
 extern "C" {
     static c_TO_NEAREST: c_int;
     static c_UPWARD: c_int;
@@ -93,8 +210,30 @@ extern "C" {
     fn c_supported(round: c_int) -> bool;
 }
 
+/// The shift of the values of the C macros of `<fenv.h>` on x86_64,
+/// they are the rounding control bits of x87 FPU control word (shifted by 2 on MSVC).
+#[cfg(not(srmfpa_fenv))]
+const FE_SHIFT: u32 = if cfg!(target_env = "msvc") { 8 } else { 10 };
+
+#[cfg(not(srmfpa_fenv))]
+#[inline]
+unsafe fn c_supported(round: c_int) -> bool {
+    0 <= round
+}
+
 /// IEEE 754 rounding mode
-#[derive(Debug, Copy, Clone)]
+///
+/// It is parsed from and displayed as names, see [`FromStr`][RoundingMode#impl-FromStr-for-RoundingMode].
+///
+/// ```
+/// use srmfpa::RoundingMode;
+///
+/// let mode: RoundingMode = "ciel".parse().unwrap();
+/// assert_eq!(mode, RoundingMode::TowardPosInf);
+/// assert_eq!(mode.to_string(), "roundTowardPositive");
+/// assert_eq!(mode.opposite(), RoundingMode::TowardNegInf);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum RoundingMode {
     /// To nearest, ties to even.
     NearestTiesEven,
@@ -107,14 +246,73 @@ pub enum RoundingMode {
 }
 
 impl RoundingMode {
+    /// All rounding modes.
+    pub const ALL: [RoundingMode; 4] = [
+        Self::NearestTiesEven,
+        Self::TowardZero,
+        Self::TowardPosInf,
+        Self::TowardNegInf,
+    ];
+
+    /// Returns the mirrored mode, that is,
+    /// the result of `-x` with the returned mode is the negation of the one of `x` with `self`.
+    ///
+    /// For example, `floor(-x) = -ciel(x)`.
+    ///
+    /// ```
+    /// use srmfpa::prelude::*;
+    ///
+    /// let mode = RoundingMode::TowardPosInf;
+    /// assert_eq!(
+    ///     (-0.1f64).round_add(-0.2, &mode.opposite()),
+    ///     -(0.1f64.round_add(0.2, &mode))
+    /// );
+    /// ```
+    #[must_use]
     #[inline]
-    pub(crate) fn as_c_int(&self) -> c_int {
+    pub const fn opposite(&self) -> Self {
         match self {
+            Self::TowardPosInf => Self::TowardNegInf,
+            Self::TowardNegInf => Self::TowardPosInf,
+            Self::NearestTiesEven => Self::NearestTiesEven,
+            Self::TowardZero => Self::TowardZero,
+        }
+    }
+
+    /// Returns the corresponding value of the C macro of `<fenv.h>` (e.g. `FE_UPWARD`).
+    ///
+    /// It returns a negative value when the macro is not defined, see [`supported`].
+    ///
+    /// [`supported`]: RoundingMode::supported
+    #[must_use]
+    #[inline]
+    pub fn as_c_int(&self) -> c_int {
+        #[cfg(srmfpa_fenv)]
+        let round = match self {
             Self::NearestTiesEven => unsafe { c_TO_NEAREST },
             Self::TowardPosInf => unsafe { c_UPWARD },
             Self::TowardNegInf => unsafe { c_DOWNWARD },
             Self::TowardZero => unsafe { c_TOWARD_ZERO },
-        }
+        };
+        #[cfg(not(srmfpa_fenv))]
+        let round = match self {
+            Self::NearestTiesEven => 0,
+            Self::TowardNegInf => 1 << FE_SHIFT,
+            Self::TowardPosInf => 2 << FE_SHIFT,
+            Self::TowardZero => 3 << FE_SHIFT,
+        };
+        round
+    }
+
+    /// Returns the mode of the value of the C macro of `<fenv.h>` (e.g. `FE_UPWARD`).
+    ///
+    /// It returns `None` when no supported mode corresponds to the value.
+    #[must_use]
+    #[inline]
+    pub fn from_c_int(round: c_int) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.supported() && mode.as_c_int() == round)
     }
 
     /// Returns `true` if the mode is supported.
@@ -136,7 +334,7 @@ impl RoundingMode {
 mod sealed {
     pub trait Sealed {}
 
-    #[cfg(any(feature = "f16", feature = "f16_softfloat",))]
+    #[cfg(any(feature = "f16", feature = "f16_softfloat", feature = "f16_widening"))]
     impl Sealed for f16 {}
     #[cfg(any(feature = "f128", feature = "f128_softfloat",))]
     impl Sealed for f128 {}
@@ -0,0 +1,121 @@
+//! Float literals with rounding mode, evaluated at compile time.
+
+/// Returns the float of decimal literal rounded toward +∞, evaluated at compile time.
+///
+/// The first argument is the float type (`f16`, `f32`, `f64` or `f128`)
+/// and the second is a decimal string accepted by `round_parse`, e.g. [`f64::round_parse`][crate::f64::round_parse].
+///
+/// It fails to compile if the string is not a valid float literal.
+///
+/// ```compile_fail
+/// let x = srmfpa::ciel!(f64, "0.1.2");
+/// ```
+///
+/// ```
+/// const G: f64 = srmfpa::ciel!(f64, "6.67430e-11");
+///
+/// assert_eq!(srmfpa::ciel!(f64, "0.1"), 0.1);
+/// assert_eq!(srmfpa::ciel!(f32, "0.1"), 0.1);
+/// assert_eq!(srmfpa::ciel!(f64, "0.3"), 0.30000000000000004);
+/// ```
+#[macro_export]
+macro_rules! ciel {
+    ($ty:ident, $s:literal) => {
+        $crate::__round_literal!($ty, $s, TowardPosInf)
+    };
+}
+
+/// Returns the float of decimal literal rounded toward -∞, evaluated at compile time.
+///
+/// See [`ciel!`][crate::ciel] for details.
+///
+/// ```
+/// assert_eq!(srmfpa::floor!(f64, "0.1"), 0.09999999999999999);
+/// assert_eq!(srmfpa::floor!(f32, "3.14159"), 3.1415899);
+/// assert_eq!(srmfpa::floor!(f64, "0.3"), 0.3);
+/// ```
+#[macro_export]
+macro_rules! floor {
+    ($ty:ident, $s:literal) => {
+        $crate::__round_literal!($ty, $s, TowardNegInf)
+    };
+}
+
+/// Returns the float of decimal literal rounded toward 0, evaluated at compile time.
+///
+/// See [`ciel!`][crate::ciel] for details.
+///
+/// ```
+/// assert_eq!(srmfpa::trunc!(f64, "0.1"), 0.09999999999999999);
+/// assert_eq!(srmfpa::trunc!(f64, "-0.1"), -0.09999999999999999);
+/// ```
+#[macro_export]
+macro_rules! trunc {
+    ($ty:ident, $s:literal) => {
+        $crate::__round_literal!($ty, $s, TowardZero)
+    };
+}
+
+/// Returns the float of decimal literal rounded to nearest, ties to even, evaluated at compile time.
+///
+/// See [`ciel!`][crate::ciel] for details.
+///
+/// ```
+/// assert_eq!(srmfpa::round_ties_even!(f64, "0.1"), 0.1);
+/// ```
+#[macro_export]
+macro_rules! round_ties_even {
+    ($ty:ident, $s:literal) => {
+        $crate::__round_literal!($ty, $s, NearestTiesEven)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __round_literal {
+    ($ty:ident, $s:literal, $mode:ident) => {{
+        const VALUE: $ty = match $crate::$ty::round_parse($s, &$crate::RoundingMode::$mode) {
+            ::core::result::Result::Ok(x) => x,
+            ::core::result::Result::Err(_) => {
+                ::core::panic!(concat!("invalid float literal: ", $s))
+            }
+        };
+        VALUE
+    }};
+}
+
+#[cfg(test)]
+mod test {
+    use crate::RoundingMode as Mode;
+
+    const TABLE: [f64; 3] = [ciel!(f64, "0.1"), floor!(f64, "0.1"), trunc!(f64, "-0.1")];
+
+    #[test]
+    fn test_const() {
+        assert_eq!(TABLE, [0.1, 0.09999999999999999, -0.09999999999999999]);
+    }
+
+    #[test]
+    fn test_runtime() {
+        for (x, s, mode) in [
+            (
+                ciel!(f64, "2.718281828459045235360"),
+                "2.718281828459045235360",
+                Mode::TowardPosInf,
+            ),
+            (
+                floor!(f64, "2.718281828459045235360"),
+                "2.718281828459045235360",
+                Mode::TowardNegInf,
+            ),
+            (ciel!(f64, "-1e-320"), "-1e-320", Mode::TowardPosInf),
+            (floor!(f64, "1e400"), "1e400", Mode::TowardNegInf),
+            (round_ties_even!(f64, "inf"), "inf", Mode::NearestTiesEven),
+        ] {
+            assert_eq!(x, crate::f64::round_parse(s, &mode).unwrap());
+        }
+
+        assert_eq!(ciel!(f32, "1e-50"), f32::from_bits(1));
+        assert_eq!(floor!(f32, "1e-50"), 0.0);
+    }
+}
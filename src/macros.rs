@@ -0,0 +1,331 @@
+pub use srmfpa_macros::{rounded, with_rounding};
+
+/// Implementation details of the macros, not public API.
+#[doc(hidden)]
+pub mod __private {
+    use crate::{RoundingArithmetic, RoundingMode};
+
+    /// The ops the macros rewrite `+`, `-`, `*` and `/` into,
+    /// floats round with the mode and integers ignore it.
+    ///
+    /// As the std ops, they are implemented for values and references.
+    pub trait Arith<Rhs = Self> {
+        type Output;
+
+        fn round_add(self, rhs: Rhs, mode: &RoundingMode) -> Self::Output;
+        fn round_sub(self, rhs: Rhs, mode: &RoundingMode) -> Self::Output;
+        fn round_mul(self, rhs: Rhs, mode: &RoundingMode) -> Self::Output;
+        fn round_div(self, rhs: Rhs, mode: &RoundingMode) -> Self::Output;
+    }
+
+    /// The op the macros rewrite `%` into, of integers only,
+    /// since the remainder of floats is not rounded.
+    #[diagnostic::on_unimplemented(
+        message = "`%` of `{Self}` is not supported with a rounding mode",
+        label = "`%` is supported only on integers"
+    )]
+    pub trait Rem<Rhs = Self> {
+        type Output;
+
+        fn rem(self, rhs: Rhs) -> Self::Output;
+    }
+
+    /// The ops the macros contract `a * b + c`, `a * b - c` and `c - a * b` into,
+    /// floats round once with the mode and integers ignore it.
+    pub trait MulAdd<B, C> {
+        type Output;
+
+        fn round_mul_add(self, b: B, c: C, mode: &RoundingMode) -> Self::Output;
+        fn round_mul_sub(self, b: B, c: C, mode: &RoundingMode) -> Self::Output;
+        fn round_neg_mul_add(self, b: B, c: C, mode: &RoundingMode) -> Self::Output;
+    }
+
+    /// Dereferences a number.
+    pub trait Value<T> {
+        fn value(self) -> T;
+    }
+
+    macro_rules! impl_arith {
+        (float: $($ty:ty),*) => {$(
+            impl_arith!(@refs $ty, |a, b, mode| {
+                round_add => RoundingArithmetic::round_add(a, b, mode),
+                round_sub => RoundingArithmetic::round_sub(a, b, mode),
+                round_mul => RoundingArithmetic::round_mul(a, b, mode),
+                round_div => RoundingArithmetic::round_div(a, b, mode),
+            });
+            // negation is exact
+            impl_arith!(@mul_add $ty, |a, b, c, mode| {
+                round_mul_add => RoundingArithmetic::round_mul_add(a, b, c, mode),
+                round_mul_sub => RoundingArithmetic::round_mul_add(a, b, -c, mode),
+                round_neg_mul_add => RoundingArithmetic::round_mul_add(-a, b, c, mode),
+            });
+        )*};
+        (int: $($ty:ty),*) => {$(
+            impl_arith!(@refs $ty, |a, b, _mode| {
+                round_add => a + b,
+                round_sub => a - b,
+                round_mul => a * b,
+                round_div => a / b,
+            });
+            impl_arith!(@mul_add $ty, |a, b, c, _mode| {
+                round_mul_add => a * b + c,
+                round_mul_sub => a * b - c,
+                round_neg_mul_add => c - a * b,
+            });
+            impl_arith!(@rem [], $ty, $ty);
+            impl_arith!(@rem ['a], $ty, &'a $ty);
+            impl_arith!(@rem ['a], &'a $ty, $ty);
+            impl_arith!(@rem ['a, 'b], &'a $ty, &'b $ty);
+        )*};
+        (@rem [$($lt:lifetime),*], $lhs:ty, $rhs:ty) => {
+            impl<$($lt),*> Rem<$rhs> for $lhs {
+                type Output = <$lhs as core::ops::Rem<$rhs>>::Output;
+
+                #[inline(always)]
+                fn rem(self, rhs: $rhs) -> Self::Output {
+                    self % rhs
+                }
+            }
+        };
+        (
+            @mul_add [$($lt:lifetime),*], $lhs:ty, $ty:ty,
+            |$a:ident, $b:ident, $c:ident, $mode:ident| { $($name:ident => $e:expr,)* }
+        ) => {
+            impl<$($lt,)* B: Value<$ty>, C: Value<$ty>> MulAdd<B, C> for $lhs {
+                type Output = $ty;
+
+                $(
+                    #[inline(always)]
+                    fn $name(self, b: B, c: C, $mode: &RoundingMode) -> Self::Output {
+                        let ($a, $b, $c) = (self.value(), b.value(), c.value());
+                        $e
+                    }
+                )*
+            }
+        };
+        (@mul_add $ty:ty, $($body:tt)*) => {
+            impl_arith!(@mul_add [], $ty, $ty, $($body)*);
+            impl_arith!(@mul_add ['a], &'a $ty, $ty, $($body)*);
+        };
+        (@refs $ty:ty, $($body:tt)*) => {
+            impl Value<$ty> for $ty {
+                #[inline(always)]
+                fn value(self) -> $ty {
+                    self
+                }
+            }
+
+            impl Value<$ty> for &$ty {
+                #[inline(always)]
+                fn value(self) -> $ty {
+                    *self
+                }
+            }
+
+            impl_arith!(@impl [], $ty, $ty, $($body)*);
+            impl_arith!(@impl ['a], $ty, &'a $ty, $($body)*);
+            impl_arith!(@impl ['a], &'a $ty, $ty, $($body)*);
+            impl_arith!(@impl ['a, 'b], &'a $ty, &'b $ty, $($body)*);
+        };
+        (
+            @impl [$($lt:lifetime),*], $lhs:ty, $rhs:ty,
+            |$a:ident, $b:ident, $mode:ident| { $($name:ident => $e:expr,)* }
+        ) => {
+            impl<$($lt),*> Arith<$rhs> for $lhs {
+                type Output = <$lhs as core::ops::Add<$rhs>>::Output;
+
+                $(
+                    #[inline(always)]
+                    fn $name(self, rhs: $rhs, $mode: &RoundingMode) -> Self::Output {
+                        let ($a, $b) = (self.value(), rhs.value());
+                        $e
+                    }
+                )*
+            }
+        };
+    }
+
+    impl_arith!(float: f32, f64);
+    #[cfg(any(feature = "f16", feature = "f16_softfloat", feature = "f16_widening"))]
+    impl_arith!(float: f16);
+    #[cfg(any(feature = "f128", feature = "f128_softfloat"))]
+    impl_arith!(float: f128);
+    impl_arith!(int: i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::f64::*;
+    use crate::rounded;
+    use crate::RoundingMode;
+
+    #[test]
+    fn test_rounded() {
+        let (a, b, c, d) = (0.1, 0.2, 0.3, 3.0);
+
+        assert_eq!(rounded!(TowardPosInf, a + b), ciel_add(a, b));
+        assert_eq!(rounded!(TowardNegInf, a - b), floor_sub(a, b));
+        assert_eq!(rounded!(TowardZero, -a * b), trunc_mul(-a, b));
+        assert_eq!(rounded!(NearestTiesEven, a / b), a / b);
+        assert_eq!(
+            rounded!(TowardPosInf, a * b + c / d.sqrt()),
+            ciel_add(ciel_mul(a, b), ciel_div(c, ciel_sqrt(d)))
+        );
+        assert_eq!(
+            rounded!(TowardNegInf, a.mul_add(b, c) * 2.0),
+            floor_mul(floor_mul_add(a, b, c), 2.0)
+        );
+
+        let mode = RoundingMode::TowardPosInf;
+        assert_eq!(rounded!(mode, (a + b) * c), ciel_mul(ciel_add(a, b), c));
+    }
+
+    #[test]
+    fn test_rounded_contract() {
+        let (a, b, c) = (0.1, 0.2, 0.3);
+
+        assert_eq!(
+            rounded!(TowardPosInf, contract, a * b + c),
+            ciel_mul_add(a, b, c)
+        );
+        assert_eq!(
+            rounded!(TowardPosInf, contract, c + a * b),
+            ciel_mul_add(a, b, c)
+        );
+        assert_eq!(
+            rounded!(TowardPosInf, contract, (a * b) - c),
+            ciel_mul_add(a, b, -c)
+        );
+        assert_eq!(
+            rounded!(TowardPosInf, contract, c - a * b),
+            ciel_mul_add(-a, b, c)
+        );
+        assert_eq!(
+            rounded!(TowardNegInf, contract, a * b + c * a + b),
+            floor_add(floor_mul_add(a, b, floor_mul(c, a)), b)
+        );
+
+        let x = 0.1f32;
+        assert_eq!(
+            rounded!(TowardPosInf, contract, &x * 3.0 - x),
+            crate::f32::ciel_mul_add(x, 3.0, -x)
+        );
+
+        // integers are not contracted, but compile
+        let (i, j, k) = (3usize, 4usize, 5usize);
+        assert_eq!(rounded!(TowardPosInf, contract, i * j + k), 17);
+        assert_eq!(rounded!(TowardPosInf, contract, i * j - k), 7);
+        assert_eq!(rounded!(TowardPosInf, contract, 20 - i * j), 8);
+        assert_eq!(rounded!(TowardNegInf, contract, -2i32 * 3 + 1), -5);
+    }
+
+    #[test]
+    fn test_rounded_int() {
+        let xs = [0.1, 0.2, 0.3];
+        let (i, mut s) = (1usize, 0.5);
+
+        assert_eq!(
+            rounded!(TowardPosInf, xs[i + 1] * xs[i - 1]),
+            ciel_mul(0.3, 0.1)
+        );
+        assert_eq!(rounded!(TowardPosInf, i * 2 + 1), 3);
+
+        rounded!(TowardPosInf, s += xs[i] / 3.0);
+        assert_eq!(s, ciel_add(0.5, ciel_div(0.2, 3.0)));
+    }
+
+    #[test]
+    fn test_with_rounding() {
+        use crate::{with_rounding, Down, Mode, Up};
+
+        #[with_rounding(M)]
+        fn dot<M: Mode>(a: &[f64], b: &[f64]) -> f64 {
+            let mut s = 0.0;
+            for i in 0..a.len() {
+                s += a[i] * b[i];
+            }
+            s
+        }
+
+        #[with_rounding(M, contract)]
+        fn dot_fma<M: Mode>(a: &[f64], b: &[f64]) -> f64 {
+            let mut s = 0.0;
+            for (x, y) in a.iter().zip(b) {
+                s = *x * *y + s;
+            }
+            s
+        }
+
+        #[with_rounding(TowardZero)]
+        fn norm(x: f64, y: f64) -> f64 {
+            (x * x + y * y).sqrt()
+        }
+
+        #[with_rounding(mode)]
+        fn poly(x: f64, mode: RoundingMode) -> f64 {
+            let f = |t: f64| t * t;
+            f(x) - 2.0 * x + 1.0
+        }
+
+        let (a, b) = ([0.1, 0.2, 0.3], [0.4, 0.5, 0.6]);
+        let e = |add: fn(f64, f64) -> f64, mul: fn(f64, f64) -> f64| {
+            add(add(add(0.0, mul(0.1, 0.4)), mul(0.2, 0.5)), mul(0.3, 0.6))
+        };
+        assert_eq!(dot::<Down>(&a, &b), e(floor_add, floor_mul));
+        assert_eq!(dot::<Up>(&a, &b), e(ciel_add, ciel_mul));
+        assert!(dot::<Down>(&a, &b) < dot::<Up>(&a, &b));
+
+        let e = ciel_mul_add(
+            0.3,
+            0.6,
+            ciel_mul_add(0.2, 0.5, ciel_mul_add(0.1, 0.4, 0.0)),
+        );
+        assert_eq!(dot_fma::<Up>(&a, &b), e);
+
+        assert_eq!(
+            norm(0.1, 0.2),
+            trunc_sqrt(trunc_add(trunc_mul(0.1, 0.1), trunc_mul(0.2, 0.2)))
+        );
+
+        let e = floor_add(floor_sub(floor_mul(0.1, 0.1), floor_mul(2.0, 0.1)), 1.0);
+        assert_eq!(poly(0.1, RoundingMode::TowardNegInf), e);
+    }
+
+    #[test]
+    fn test_with_rounding_place() {
+        use crate::{with_rounding, Mode, Up};
+
+        #[with_rounding(M)]
+        fn axpy<M: Mode>(a: f64, x: &[f64], y: &mut [f64]) {
+            for i in 0..x.len() {
+                y[i] += a * x[i];
+            }
+        }
+
+        #[with_rounding(M)]
+        fn odd<M: Mode>(x: &mut [f64]) {
+            let mut k = 7usize;
+            for i in 0..x.len() {
+                if i % 2 == 1 {
+                    x[i] /= 3.0 + (x.len() % 2) as f64;
+                }
+                k %= 4;
+            }
+            x[0] -= k as f64;
+        }
+
+        let mut y = [0.1, 0.2];
+        axpy::<Up>(0.3, &[0.4, 0.5], &mut y);
+        assert_eq!(
+            y,
+            [
+                ciel_add(0.1, ciel_mul(0.3, 0.4)),
+                ciel_add(0.2, ciel_mul(0.3, 0.5))
+            ]
+        );
+
+        let mut x = [1.0, 1.0];
+        odd::<Up>(&mut x);
+        assert_eq!(x, [-2.0, ciel_div(1.0, 3.0)]);
+    }
+}
@@ -0,0 +1,268 @@
+use core::fmt::{Display, Formatter};
+use core::marker::PhantomData;
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::{RoundingArithmetic, RoundingMath, RoundingMode};
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for super::Up {}
+    impl Sealed for super::Down {}
+    impl Sealed for super::Zero {}
+    impl Sealed for super::Nearest {}
+}
+
+/// Type-level rounding mode, see [`Rounded`].
+pub trait Mode: sealed::Sealed + Copy + Default {
+    /// The corresponding rounding mode.
+    const MODE: RoundingMode;
+}
+
+/// Type-level rounding mode toward +∞ (aka ciel).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Up;
+
+/// Type-level rounding mode toward -∞ (aka floor).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Down;
+
+/// Type-level rounding mode toward 0 (aka trunc).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Zero;
+
+/// Type-level rounding mode to nearest, ties to even.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Nearest;
+
+impl Mode for Up {
+    const MODE: RoundingMode = RoundingMode::TowardPosInf;
+}
+
+impl Mode for Down {
+    const MODE: RoundingMode = RoundingMode::TowardNegInf;
+}
+
+impl Mode for Zero {
+    const MODE: RoundingMode = RoundingMode::TowardZero;
+}
+
+impl Mode for Nearest {
+    const MODE: RoundingMode = RoundingMode::NearestTiesEven;
+}
+
+/// Float whose every op rounds with the type-level rounding mode `M`.
+///
+/// The ops of `Rounded<T, Up>` are the ones of [`CielArithmetic`][crate::CielArithmetic]
+/// and [`CielMath`][crate::CielMath], and so on.
+/// It is a zero-cost wrapper, and mixing modes is a type error.
+///
+/// ```
+/// use srmfpa::{Down, Rounded, Up};
+/// use srmfpa::f64::{ciel_add, ciel_mul, floor_add, floor_mul};
+///
+/// let (a, b, c) = (0.1, 0.2, 0.3);
+///
+/// let x = Rounded::<f64, Up>::new(a) * b + c;
+/// assert_eq!(x.get(), ciel_add(ciel_mul(a, b), c));
+///
+/// let y = Rounded::<f64, Down>::new(a) * b + c;
+/// assert_eq!(y.get(), floor_add(floor_mul(a, b), c));
+/// ```
+///
+/// ```compile_fail
+/// use srmfpa::{Down, Rounded, Up};
+///
+/// // mixing modes
+/// let x = Rounded::<f64, Up>::new(0.1) + Rounded::<f64, Down>::new(0.2);
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq, PartialOrd)]
+#[repr(transparent)]
+pub struct Rounded<T, M> {
+    value: T,
+    mode: PhantomData<M>,
+}
+
+impl<T, M: Mode> Rounded<T, M> {
+    /// Wraps `value`.
+    #[must_use]
+    #[inline]
+    pub const fn new(value: T) -> Self {
+        Self {
+            value,
+            mode: PhantomData,
+        }
+    }
+
+    /// Returns the wrapped value.
+    #[must_use]
+    #[inline]
+    pub fn get(self) -> T {
+        self.value
+    }
+
+    /// Returns the rounding mode.
+    #[must_use]
+    #[inline]
+    pub const fn mode(&self) -> RoundingMode {
+        M::MODE
+    }
+}
+
+impl<T: RoundingArithmetic<Output = T>, M: Mode> Rounded<T, M> {
+    /// Returns `self * a + b` with single rounding (fused multiply-add) with the mode `M`.
+    ///
+    /// # Safety
+    ///
+    /// Panics when fail to set/restore rounding mode.
+    #[must_use = "method returns a new number and does not mutate the original value"]
+    #[inline]
+    pub fn mul_add(self, a: impl Into<Self>, b: impl Into<Self>) -> Self {
+        let (a, b) = (a.into(), b.into());
+        Self::new(self.value.round_mul_add(a.value, b.value, &M::MODE))
+    }
+}
+
+impl<T: RoundingMath<Output = T>, M: Mode> Rounded<T, M> {
+    /// Returns `self.sqrt()` with the mode `M`.
+    ///
+    /// # Safety
+    ///
+    /// Panics when fail to set/restore rounding mode.
+    #[must_use = "method returns a new number and does not mutate the original value"]
+    #[inline]
+    pub fn sqrt(self) -> Self {
+        Self::new(self.value.round_sqrt(&M::MODE))
+    }
+}
+
+impl<T, M: Mode> From<T> for Rounded<T, M> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Display, M> Display for Rounded<T, M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T: Neg<Output = T>, M: Mode> Neg for Rounded<T, M> {
+    type Output = Self;
+
+    /// Negation is exact.
+    #[inline]
+    fn neg(self) -> Self {
+        Self::new(-self.value)
+    }
+}
+
+macro_rules! impl_ops {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $round:ident) => {
+        impl<T: RoundingArithmetic<Output = T>, M: Mode> $trait for Rounded<T, M> {
+            type Output = Self;
+
+            #[inline]
+            fn $method(self, rhs: Self) -> Self {
+                Self::new(self.value.$round(rhs.value, &M::MODE))
+            }
+        }
+
+        impl<T: RoundingArithmetic<Output = T>, M: Mode> $trait<T> for Rounded<T, M> {
+            type Output = Self;
+
+            #[inline]
+            fn $method(self, rhs: T) -> Self {
+                Self::new(self.value.$round(rhs, &M::MODE))
+            }
+        }
+
+        impl<T: RoundingArithmetic<Output = T> + Copy, M: Mode> $assign_trait for Rounded<T, M> {
+            #[inline]
+            fn $assign_method(&mut self, rhs: Self) {
+                self.value = self.value.$round(rhs.value, &M::MODE);
+            }
+        }
+
+        impl<T: RoundingArithmetic<Output = T> + Copy, M: Mode> $assign_trait<T> for Rounded<T, M> {
+            #[inline]
+            fn $assign_method(&mut self, rhs: T) {
+                self.value = self.value.$round(rhs, &M::MODE);
+            }
+        }
+    };
+}
+
+impl_ops!(Add, add, AddAssign, add_assign, round_add);
+impl_ops!(Sub, sub, SubAssign, sub_assign, round_sub);
+impl_ops!(Mul, mul, MulAssign, mul_assign, round_mul);
+impl_ops!(Div, div, DivAssign, div_assign, round_div);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::f64::*;
+
+    #[test]
+    fn test_ops() {
+        let (a, b, c) = (0.1, 0.2, 0.3);
+
+        let x = Rounded::<f64, Up>::new(a);
+        assert_eq!((x + b).get(), ciel_add(a, b));
+        assert_eq!((x - b).get(), ciel_sub(a, b));
+        assert_eq!((x * b).get(), ciel_mul(a, b));
+        assert_eq!((x / b).get(), ciel_div(a, b));
+        assert_eq!(x.mul_add(b, c).get(), ciel_mul_add(a, b, c));
+        assert_eq!(x.sqrt().get(), ciel_sqrt(a));
+        assert_eq!((-x).get(), -a);
+
+        let x = Rounded::<f64, Down>::new(a);
+        assert_eq!((x + Rounded::new(b)).get(), floor_add(a, b));
+        assert_eq!((x - Rounded::new(b)).get(), floor_sub(a, b));
+        assert_eq!((x * Rounded::new(b)).get(), floor_mul(a, b));
+        assert_eq!((x / Rounded::new(b)).get(), floor_div(a, b));
+        assert_eq!(x.sqrt().get(), floor_sqrt(a));
+
+        let x = Rounded::<f64, Zero>::new(-a);
+        assert_eq!((x / b).get(), trunc_div(-a, b));
+        assert_eq!(x.mul_add(b, c).get(), trunc_mul_add(-a, b, c));
+
+        let x = Rounded::<f64, Nearest>::new(a);
+        assert_eq!((x + b).get(), a + b);
+        assert!(matches!(x.mode(), RoundingMode::NearestTiesEven));
+    }
+
+    #[test]
+    fn test_assign() {
+        let (a, b) = (0.1f32, 0.2f32);
+
+        let mut x = Rounded::<f32, Up>::new(a);
+        x += b;
+        assert_eq!(x.get(), crate::f32::ciel_add(a, b));
+        x -= Rounded::new(b);
+        assert_eq!(x.get(), crate::f32::ciel_sub(crate::f32::ciel_add(a, b), b));
+
+        let mut x = Rounded::<f32, Down>::new(a);
+        x *= b;
+        assert_eq!(x.get(), crate::f32::floor_mul(a, b));
+        x /= b;
+        assert_eq!(
+            x.get(),
+            crate::f32::floor_div(crate::f32::floor_mul(a, b), b)
+        );
+    }
+
+    #[test]
+    fn test_zero_cost() {
+        assert_eq!(
+            core::mem::size_of::<Rounded<f64, Up>>(),
+            core::mem::size_of::<f64>()
+        );
+        assert_eq!(
+            core::mem::size_of::<Rounded<f32, Down>>(),
+            core::mem::size_of::<f32>()
+        );
+    }
+}
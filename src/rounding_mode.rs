@@ -0,0 +1,182 @@
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+
+use crate::{ParseRoundingModeError, RoundingMode};
+
+impl Display for RoundingMode {
+    /// Writes the name of IEEE 754 rounding-direction attribute (e.g. `roundTowardPositive`).
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let s = match self {
+            Self::NearestTiesEven => "roundTiesToEven",
+            Self::TowardZero => "roundTowardZero",
+            Self::TowardPosInf => "roundTowardPositive",
+            Self::TowardNegInf => "roundTowardNegative",
+        };
+        f.pad(s)
+    }
+}
+
+impl FromStr for RoundingMode {
+    type Err = ParseRoundingModeError;
+
+    /// Parses a name of rounding mode.
+    ///
+    /// It accepts the variant name (e.g. `TowardPosInf`), the name of IEEE 754 (e.g. `roundTowardPositive`),
+    /// the name of the ops of this crate (e.g. `ciel`) and common names (e.g. `up`, `ceil`).
+    /// It ignores case, `_`, `-` and whitespaces.
+    ///
+    /// | Mode                | Names                                                                         |
+    /// |---------------------|-------------------------------------------------------------------------------|
+    /// | [`NearestTiesEven`] | `NearestTiesEven`, `roundTiesToEven`, `round_ties_even`, `TiesToEven`, `nearest`, `even` |
+    /// | [`TowardZero`]      | `TowardZero`, `roundTowardZero`, `trunc`, `zero`                              |
+    /// | [`TowardPosInf`]    | `TowardPosInf`, `roundTowardPositive`, `TowardPositive`, `ciel`, `ceil`, `up`, `upward` |
+    /// | [`TowardNegInf`]    | `TowardNegInf`, `roundTowardNegative`, `TowardNegative`, `floor`, `down`, `downward` |
+    ///
+    /// [`NearestTiesEven`]: RoundingMode::NearestTiesEven
+    /// [`TowardZero`]: RoundingMode::TowardZero
+    /// [`TowardPosInf`]: RoundingMode::TowardPosInf
+    /// [`TowardNegInf`]: RoundingMode::TowardNegInf
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s
+            .chars()
+            .filter(|c| !matches!(c, '_' | '-') && !c.is_whitespace())
+            .map(|c| c.to_ascii_lowercase())
+            .collect::<String>();
+
+        match name.as_str() {
+            "nearesttieseven" | "roundtiestoeven" | "roundtieseven" | "tiestoeven" | "nearest"
+            | "even" => Ok(Self::NearestTiesEven),
+            "towardzero" | "roundtowardzero" | "trunc" | "zero" => Ok(Self::TowardZero),
+            "towardposinf"
+            | "roundtowardpositive"
+            | "towardpositive"
+            | "ciel"
+            | "ceil"
+            | "up"
+            | "upward" => Ok(Self::TowardPosInf),
+            "towardneginf"
+            | "roundtowardnegative"
+            | "towardnegative"
+            | "floor"
+            | "down"
+            | "downward" => Ok(Self::TowardNegInf),
+            _ => Err(ParseRoundingModeError::new()),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl serde::Serialize for RoundingMode {
+    /// Serializes as the name of [`Display`].
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> serde::Deserialize<'de> for RoundingMode {
+    /// Deserializes from a name of [`FromStr`].
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = RoundingMode;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+                f.write_str("a name of rounding mode")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.parse()
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        for mode in RoundingMode::ALL {
+            assert_eq!(mode.to_string().parse::<RoundingMode>(), Ok(mode));
+            assert_eq!(format!("{mode:?}").parse::<RoundingMode>(), Ok(mode));
+        }
+        assert_eq!(
+            format!("{:>20}", RoundingMode::TowardZero),
+            "     roundTowardZero"
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        use RoundingMode::*;
+
+        for (s, mode) in [
+            ("nearest", NearestTiesEven),
+            ("round_ties_even", NearestTiesEven),
+            ("trunc", TowardZero),
+            ("toward-zero", TowardZero),
+            ("ciel", TowardPosInf),
+            ("Ceil", TowardPosInf),
+            ("up", TowardPosInf),
+            ("towardPositive", TowardPosInf),
+            ("floor", TowardNegInf),
+            ("DOWN", TowardNegInf),
+            ("toward negative", TowardNegInf),
+        ] {
+            assert_eq!(s.parse(), Ok(mode), "{s}");
+        }
+
+        for s in ["", "nearest away", "odd", "ciel!"] {
+            assert_eq!(
+                s.parse::<RoundingMode>(),
+                Err(ParseRoundingModeError::new())
+            );
+        }
+    }
+
+    #[test]
+    fn test_opposite() {
+        use crate::f64::round_mul;
+
+        for mode in RoundingMode::ALL {
+            assert_eq!(mode.opposite().opposite(), mode);
+            assert_eq!(
+                round_mul(-0.1, 0.2, &mode.opposite()),
+                -round_mul(0.1, 0.2, &mode)
+            );
+        }
+    }
+
+    #[test]
+    fn test_c_int() {
+        for mode in RoundingMode::ALL {
+            assert!(mode.supported());
+            assert_eq!(RoundingMode::from_c_int(mode.as_c_int()), Some(mode));
+        }
+        assert_eq!(RoundingMode::from_c_int(-1), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_tokens, Token};
+
+        assert_tokens(
+            &RoundingMode::TowardPosInf,
+            &[Token::Str("roundTowardPositive")],
+        );
+        assert_de_tokens(&RoundingMode::TowardNegInf, &[Token::Str("floor")]);
+        assert_de_tokens_error::<RoundingMode>(
+            &[Token::Str("odd")],
+            "invalid value: string \"odd\", expected a name of rounding mode",
+        );
+    }
+}
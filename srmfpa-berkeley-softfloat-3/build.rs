@@ -1152,6 +1152,7 @@ fn main() {
     builder.file("src/c/f32.c");
     builder.file("src/c/f64.c");
     builder.file("src/c/f128.c");
+    builder.file("src/c/state.c");
 
     let root = Path::new("berkeley-softfloat-3");
     let source = root.join("source");
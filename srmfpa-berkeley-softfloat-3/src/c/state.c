@@ -0,0 +1,25 @@
+#include "softfloat.h"
+
+uint_fast8_t c_get_rounding_mode(void) {
+  return softfloat_roundingMode;
+}
+
+void c_set_rounding_mode(uint_fast8_t const mode) {
+  softfloat_roundingMode = mode;
+}
+
+uint_fast8_t c_get_exception_flags(void) {
+  return softfloat_exceptionFlags;
+}
+
+void c_set_exception_flags(uint_fast8_t const flags) {
+  softfloat_exceptionFlags = flags;
+}
+
+uint_fast8_t c_get_detect_tininess(void) {
+  return softfloat_detectTininess;
+}
+
+void c_set_detect_tininess(uint_fast8_t const tininess) {
+  softfloat_detectTininess = tininess;
+}
@@ -28,6 +28,13 @@ extern "C" {
     pub fn c_div_f128(mode: u8, a: float128_t, b: float128_t) -> float128_t;
     pub fn c_fma_f128(mode: u8, a: float128_t, b: float128_t, c: float128_t) -> float128_t;
     pub fn c_sqrt_f128(mode: u8, a: float128_t) -> float128_t;
+    //
+    pub fn c_get_rounding_mode() -> u8;
+    pub fn c_set_rounding_mode(mode: u8);
+    pub fn c_get_exception_flags() -> u8;
+    pub fn c_set_exception_flags(flags: u8);
+    pub fn c_get_detect_tininess() -> u8;
+    pub fn c_set_detect_tininess(tininess: u8);
 }
 
 #[repr(C)]
@@ -54,3 +61,12 @@ pub const softfloat_round_near_even: u8 = 0;
 pub const softfloat_round_minMag: u8 = 1;
 pub const softfloat_round_min: u8 = 2;
 pub const softfloat_round_max: u8 = 3;
+
+pub const softfloat_tininess_beforeRounding: u8 = 0;
+pub const softfloat_tininess_afterRounding: u8 = 1;
+
+pub const softfloat_flag_inexact: u8 = 1;
+pub const softfloat_flag_underflow: u8 = 2;
+pub const softfloat_flag_overflow: u8 = 4;
+pub const softfloat_flag_infinite: u8 = 8;
+pub const softfloat_flag_invalid: u8 = 16;
@@ -0,0 +1,365 @@
+//! Procedural macros for [`srmfpa`](https://docs.rs/srmfpa).
+//!
+//! Use them via the `macros` feature of `srmfpa`, e.g. `srmfpa::rounded!`.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::visit_mut::{self, VisitMut};
+use syn::{parse_macro_input, parse_quote, BinOp, Expr, Ident, Token};
+
+/// Rewrites ordinary arithmetic into directed ops with the rounding mode bound to `mode`.
+struct Rewriter {
+    mode: Ident,
+    contract: bool,
+}
+
+impl Rewriter {
+    fn new(contract: bool) -> Self {
+        Self {
+            // hygienic, it does not conflict with the user's
+            mode: Ident::new("__srmfpa_mode", Span::mixed_site()),
+            contract,
+        }
+    }
+
+    fn binary(&self, op: &BinOp, lhs: &Expr, rhs: &Expr) -> Option<Expr> {
+        let mode = &self.mode;
+        let name = match op {
+            BinOp::Add(_) | BinOp::AddAssign(_) => quote!(round_add),
+            BinOp::Sub(_) | BinOp::SubAssign(_) => quote!(round_sub),
+            BinOp::Mul(_) | BinOp::MulAssign(_) => quote!(round_mul),
+            BinOp::Div(_) | BinOp::DivAssign(_) => quote!(round_div),
+            // integers only, floats fail to compile
+            BinOp::Rem(_) | BinOp::RemAssign(_) => {
+                return Some(parse_quote!(::srmfpa::__private::Rem::rem(#lhs, #rhs)))
+            }
+            _ => return None,
+        };
+        Some(parse_quote!(::srmfpa::__private::Arith::#name(#lhs, #rhs, &#mode)))
+    }
+
+    /// Contracts `a * b + c`, `c + a * b`, `a * b - c` and `c - a * b` into `mul_add`.
+    fn contract(&mut self, e: &mut Expr) -> bool {
+        let Expr::Binary(bin) = e else {
+            return false;
+        };
+        let sub = match bin.op {
+            BinOp::Add(_) => false,
+            BinOp::Sub(_) => true,
+            _ => return false,
+        };
+        let (mut a, mut b, mut c, left) = match (as_mul(&bin.left), as_mul(&bin.right)) {
+            (Some((a, b)), _) => (a.clone(), b.clone(), (*bin.right).clone(), true),
+            (None, Some((a, b))) => (a.clone(), b.clone(), (*bin.left).clone(), false),
+            (None, None) => return false,
+        };
+        self.visit_expr_mut(&mut a);
+        self.visit_expr_mut(&mut b);
+        self.visit_expr_mut(&mut c);
+
+        let name = match (sub, left) {
+            (false, _) => quote!(round_mul_add),
+            (true, true) => quote!(round_mul_sub),
+            (true, false) => quote!(round_neg_mul_add),
+        };
+        let mode = &self.mode;
+        *e = parse_quote!(::srmfpa::__private::MulAdd::#name(#a, #b, #c, &#mode));
+        true
+    }
+}
+
+/// Returns the operands of `a * b`, through parentheses.
+fn as_mul(e: &Expr) -> Option<(&Expr, &Expr)> {
+    match e {
+        Expr::Binary(bin) if matches!(bin.op, BinOp::Mul(_)) => Some((&bin.left, &bin.right)),
+        Expr::Paren(p) => as_mul(&p.expr),
+        _ => None,
+    }
+}
+
+/// Returns `true` if evaluating `e` twice is the same as once.
+fn is_place(e: &Expr) -> bool {
+    match e {
+        Expr::Path(_) => true,
+        Expr::Field(f) => is_place(&f.base),
+        Expr::Paren(p) => is_place(&p.expr),
+        Expr::Unary(u) if matches!(u.op, syn::UnOp::Deref(_)) => is_place(&u.expr),
+        _ => false,
+    }
+}
+
+impl VisitMut for Rewriter {
+    fn visit_expr_mut(&mut self, e: &mut Expr) {
+        if self.contract && self.contract(e) {
+            return;
+        }
+
+        visit_mut::visit_expr_mut(self, e);
+
+        let mode = &self.mode;
+        match e {
+            Expr::Binary(bin) => match bin.op {
+                BinOp::Add(_) | BinOp::Sub(_) | BinOp::Mul(_) | BinOp::Div(_) | BinOp::Rem(_) => {
+                    *e = self.binary(&bin.op, &bin.left, &bin.right).unwrap();
+                }
+                BinOp::AddAssign(_)
+                | BinOp::SubAssign(_)
+                | BinOp::MulAssign(_)
+                | BinOp::DivAssign(_)
+                | BinOp::RemAssign(_) => {
+                    // the right operand is evaluated first, as the std compound assignment
+                    let var = Ident::new("__srmfpa_rhs", Span::mixed_site());
+                    let (lhs, rhs) = (&bin.left, &bin.right);
+                    if is_place(lhs) {
+                        let op = self.binary(&bin.op, lhs, &parse_quote!(#var)).unwrap();
+                        *e = parse_quote!({
+                            let #var = #rhs;
+                            #lhs = #op;
+                        });
+                    } else {
+                        // e.g. indices, evaluated once
+                        let place = Ident::new("__srmfpa_place", Span::mixed_site());
+                        let op = self
+                            .binary(&bin.op, &parse_quote!(*#place), &parse_quote!(#var))
+                            .unwrap();
+                        *e = parse_quote!({
+                            let #var = #rhs;
+                            let #place = &mut #lhs;
+                            *#place = #op;
+                        });
+                    }
+                }
+                _ => {}
+            },
+            // parentheses are needless around the calls
+            Expr::Paren(p) if matches!(*p.expr, Expr::Call(_) | Expr::MethodCall(_)) => {
+                *e = (*p.expr).clone();
+            }
+            Expr::MethodCall(call) if call.method == "sqrt" && call.args.is_empty() => {
+                let receiver = &call.receiver;
+                *e = parse_quote!(::srmfpa::RoundingMath::round_sqrt(#receiver, &#mode));
+            }
+            Expr::MethodCall(call) if call.method == "mul_add" && call.args.len() == 2 => {
+                let receiver = &call.receiver;
+                let (a, b) = (&call.args[0], &call.args[1]);
+                *e = parse_quote!(::srmfpa::RoundingArithmetic::round_mul_add(#receiver, #a, #b, &#mode));
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_expr_index_mut(&mut self, e: &mut syn::ExprIndex) {
+        // the index is an integer
+        self.visit_expr_mut(&mut e.expr);
+    }
+
+    fn visit_expr_range_mut(&mut self, _: &mut syn::ExprRange) {
+        // ranges are of integers
+    }
+
+    fn visit_item_mut(&mut self, _: &mut syn::Item) {
+        // nested items are not in the scope of the mode
+    }
+}
+
+/// Returns the rounding mode expression, a bare variant name is of `RoundingMode`.
+fn mode_expr(mode: Expr) -> Expr {
+    if let Expr::Path(p) = &mode {
+        if let Some(ident) = p.path.get_ident() {
+            if [
+                "NearestTiesEven",
+                "TowardZero",
+                "TowardPosInf",
+                "TowardNegInf",
+            ]
+            .iter()
+            .any(|v| ident == v)
+            {
+                return parse_quote!(::srmfpa::RoundingMode::#ident);
+            }
+        }
+    }
+    mode
+}
+
+struct RoundedInput {
+    mode: Expr,
+    contract: bool,
+    expr: Expr,
+}
+
+impl Parse for RoundedInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mode = mode_expr(input.parse()?);
+        input.parse::<Token![,]>()?;
+        let contract = input.peek(Ident) && input.peek2(Token![,]) && {
+            let fork = input.fork();
+            fork.parse::<Ident>()? == "contract"
+        };
+        if contract {
+            input.parse::<Ident>()?;
+            input.parse::<Token![,]>()?;
+        }
+        let expr = input.parse()?;
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+        }
+        Ok(Self {
+            mode,
+            contract,
+            expr,
+        })
+    }
+}
+
+/// Evaluates an expression with directed rounding.
+///
+/// It rewrites `+`, `-`, `*`, `/` (and their compound assignments),
+/// `.sqrt()` and `.mul_add(a, b)` inside the expression
+/// into the corresponding directed ops with the rounding mode.
+/// With `contract`, it contracts `a * b + c` (and `a * b - c` etc.) into a single `mul_add`.
+///
+/// The rounding mode is a variant name of `RoundingMode` (e.g. `TowardPosInf`)
+/// or an expression of `RoundingMode`, evaluated once.
+///
+/// Operands are floats (`f32`, `f64` etc.) or integers,
+/// integer ops, indices and ranges are kept as is.
+/// Negation is exact, and `%` is supported only on integers, it fails to compile on floats.
+///
+/// ```
+/// use srmfpa::f64::{ciel_add, ciel_div, ciel_mul, ciel_mul_add, ciel_sqrt};
+/// use srmfpa::rounded;
+///
+/// let (a, b, c, d) = (0.1, 0.2, 0.3, 3.0);
+///
+/// assert_eq!(
+///     rounded!(TowardPosInf, a * b + c / d.sqrt()),
+///     ciel_add(ciel_mul(a, b), ciel_div(c, ciel_sqrt(d)))
+/// );
+///
+/// // contraction
+/// assert_eq!(rounded!(TowardPosInf, contract, a * b + c), ciel_mul_add(a, b, c));
+///
+/// // mode of variable
+/// let mode = srmfpa::RoundingMode::TowardNegInf;
+/// assert_eq!(rounded!(mode, a + b), srmfpa::f64::floor_add(a, b));
+/// ```
+#[proc_macro]
+pub fn rounded(input: TokenStream) -> TokenStream {
+    let RoundedInput {
+        mode,
+        contract,
+        mut expr,
+    } = parse_macro_input!(input as RoundedInput);
+
+    let mut rewriter = Rewriter::new(contract);
+    rewriter.visit_expr_mut(&mut expr);
+
+    let var = &rewriter.mode;
+    quote!({
+        #[allow(unused_variables)]
+        let #var: ::srmfpa::RoundingMode = #mode;
+        #expr
+    })
+    .into()
+}
+
+struct WithRoundingArgs {
+    mode: Expr,
+    contract: bool,
+}
+
+impl Parse for WithRoundingArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mode = input.parse()?;
+        let mut contract = false;
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+            if !input.is_empty() {
+                let ident = input.parse::<Ident>()?;
+                if ident != "contract" {
+                    return Err(syn::Error::new_spanned(ident, "expected `contract`"));
+                }
+                contract = true;
+                if input.peek(Token![,]) {
+                    input.parse::<Token![,]>()?;
+                }
+            }
+        }
+        Ok(Self { mode, contract })
+    }
+}
+
+/// Compiles a function with directed rounding.
+///
+/// It rewrites the arithmetic in the function body as [`rounded!`] does,
+/// the rounding mode is
+///
+/// - a variant name of `RoundingMode` (e.g. `TowardNegInf`),
+/// - a generic type parameter bounded by `srmfpa::Mode` (e.g. `M`), the mode is `M::MODE`,
+/// - or an expression of `RoundingMode` (e.g. a parameter `mode`), evaluated once at the beginning.
+///
+/// With `contract` (e.g. `#[with_rounding(TowardNegInf, contract)]`),
+/// it contracts `a * b + c` into a single `mul_add`.
+/// Nested items and macro invocations (e.g. `println!`) in the body are kept as is.
+///
+/// ```
+/// use srmfpa::{with_rounding, Down, Mode, Up};
+/// use srmfpa::f64::{ciel_add, ciel_mul, floor_add, floor_mul};
+///
+/// #[with_rounding(M)]
+/// fn dot<M: Mode>(a: &[f64], b: &[f64]) -> f64 {
+///     let mut s = 0.0;
+///     for i in 0..a.len() {
+///         s += a[i] * b[i];
+///     }
+///     s
+/// }
+///
+/// let (a, b) = ([0.1, 0.2], [0.3, 0.4]);
+///
+/// // lower and upper bounds by one body
+/// assert_eq!(dot::<Down>(&a, &b), floor_add(floor_mul(0.1, 0.3), floor_mul(0.2, 0.4)));
+/// assert_eq!(dot::<Up>(&a, &b), ciel_add(ciel_mul(0.1, 0.3), ciel_mul(0.2, 0.4)));
+///
+/// #[with_rounding(TowardNegInf)]
+/// fn lower(a: f64, b: f64) -> f64 {
+///     a * b + 1.0
+/// }
+///
+/// assert_eq!(lower(0.1, 0.3), floor_add(floor_mul(0.1, 0.3), 1.0));
+/// ```
+#[proc_macro_attribute]
+pub fn with_rounding(args: TokenStream, item: TokenStream) -> TokenStream {
+    let WithRoundingArgs { mode, contract } = parse_macro_input!(args as WithRoundingArgs);
+    let mut item = parse_macro_input!(item as syn::ItemFn);
+
+    // generic mode parameter
+    let generic = match &mode {
+        Expr::Path(p) => p.path.get_ident().filter(|ident| {
+            item.sig
+                .generics
+                .type_params()
+                .any(|param| &param.ident == *ident)
+        }),
+        _ => None,
+    };
+    let mode: Expr = match generic {
+        Some(ident) => parse_quote!(<#ident as ::srmfpa::Mode>::MODE),
+        None => mode_expr(mode.clone()),
+    };
+
+    let mut rewriter = Rewriter::new(contract);
+    rewriter.visit_block_mut(&mut item.block);
+
+    let var = &rewriter.mode;
+    let block = &item.block;
+    item.block = parse_quote!({
+        #[allow(unused_variables)]
+        let #var: ::srmfpa::RoundingMode = #mode;
+        #block
+    });
+    quote!(#item).into()
+}